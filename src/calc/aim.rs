@@ -0,0 +1,169 @@
+//! 声影响图（AIM），用于按缺陷取向选择TFM模式
+//!
+//! 在成像区域每个位置放一个平面反射体，对每对收发阵元比较入射声线经反射体镜面反射后的方向
+//! 与返回接收阵元的声线方向，偏差越小贡献越大；反射时允许波型转换（按斯涅尔定律）。
+//! 指向性按长度为`reflector_length`的条带近似为高斯瓣，不计反射系数，结果用于各模式相对比较。
+
+use super::grid::ImagingGrid;
+use super::multimode::{backwall_range, leg_weight, plate_pixels, LegSolver, TfmMode, WedgeArray};
+use super::ray::Point;
+use super::tfm::TfmImage;
+use super::{positive, CalcError, Result};
+
+/// 反射体倾角单位度，0为水平（平行于表面），90为竖直；长度单位mm，频率MHz
+#[derive(Debug, Clone, PartialEq)]
+pub struct AimInput {
+    pub array: WedgeArray,
+    pub frequency: f64,
+    pub reflector_angle: f64,
+    pub reflector_length: f64,
+    pub grid: ImagingGrid,
+}
+
+/// 一种模式的灵敏度分布，幅度为各收发阵元对贡献的平均值乘以声程相对幅度
+#[derive(Debug, Clone, PartialEq)]
+pub struct AimMap {
+    pub mode: TfmMode,
+    pub image: TfmImage,
+    /// 成像区域内的平均灵敏度
+    pub mean: f64,
+    pub peak: f64,
+}
+
+//单位向量
+fn direction(from: Point, to: Point) -> Point {
+    let (dx, dz) = (to.0 - from.0, to.1 - from.1);
+    let length = dx.hypot(dz).max(1e-12);
+    (dx / length, dz / length)
+}
+
+/// 入射方向`incoming`经法向为`normal`的平面反射后的方向，反射波与入射波声速比为`ratio`，
+/// 切向分量超过1时不存在该反射波
+fn reflect(incoming: Point, normal: Point, ratio: f64) -> Option<Point> {
+    let along = incoming.0 * normal.0 + incoming.1 * normal.1;
+    let tangent = ((incoming.0 - along * normal.0) * ratio, (incoming.1 - along * normal.1) * ratio);
+    let sin2 = tangent.0 * tangent.0 + tangent.1 * tangent.1;
+    if sin2 > 1.0 {
+        return None;
+    }
+    let back = -along.signum() * (1.0 - sin2).sqrt();
+    Some((tangent.0 + back * normal.0, tangent.1 + back * normal.1))
+}
+
+/// 按模式计算各位置的灵敏度，结果与模式顺序一致
+pub fn aim(input: &AimInput, modes: &[TfmMode]) -> Result<Vec<AimMap>> {
+    let array = &input.array;
+    array.check()?;
+    positive("频率", input.frequency)?;
+    positive("反射体长度", input.reflector_length)?;
+    if input.reflector_angle.is_nan() || input.reflector_angle.abs() > 90.0 {
+        return Err(CalcError::OutOfRange("反射体倾角"));
+    }
+    let pixels = plate_pixels(&input.grid, array.thickness)?;
+    let points: Vec<Point> = pixels.iter().map(|&(_, point)| point).collect();
+
+    let angle = input.reflector_angle.to_radians();
+    let normal = (-angle.sin(), angle.cos());
+    let positions = array.positions();
+    let (x_min, x_max, _, _) = input.grid.bounds();
+    let range = backwall_range(&positions, x_min, x_max);
+    let pairs = (array.elements * array.elements) as f64;
+
+    let mut maps = Vec::new();
+    for &mode in modes.iter() {
+        //每个阵元到各像素的到达方向
+        let arrivals = |leg| -> Vec<Vec<Point>> {
+            positions
+                .iter()
+                .map(|&source| {
+                    let solver = LegSolver::new(array, leg, source, range);
+                    points
+                        .iter()
+                        .map(|&target| direction(solver.arrival(target).1, target))
+                        .collect()
+                })
+                .collect()
+        };
+        let transmit = arrivals(mode.transmit);
+        let receive = arrivals(mode.receive);
+        let (incoming, outgoing) = (mode.transmit.arriving(), mode.receive.arriving());
+        let ratio = array.velocity(outgoing) / array.velocity(incoming);
+        //条带指向性的−6dB半角
+        let lambda = array.velocity(outgoing) / 1000.0 / input.frequency;
+        let half_width = (0.3 * lambda / input.reflector_length).min(1.0).asin();
+        let weight = leg_weight(mode.transmit) * leg_weight(mode.receive);
+
+        let mut values = vec![0f32; points.len()];
+        for (pixel, amplitude) in values.iter_mut().enumerate() {
+            let mut sum = 0.0;
+            for tx in transmit.iter() {
+                let reflected = match reflect(tx[pixel], normal, ratio) {
+                    Some(reflected) => reflected,
+                    None => continue,
+                };
+                for rx in receive.iter() {
+                    //返回接收阵元的方向与到达方向相反
+                    let cos = -(reflected.0 * rx[pixel].0 + reflected.1 * rx[pixel].1);
+                    let mismatch = cos.clamp(-1.0, 1.0).acos();
+                    sum += 0.5f64.powf((mismatch / half_width).powi(2));
+                }
+            }
+            *amplitude = (sum / pairs * weight) as f32;
+        }
+        let mean = values.iter().map(|&a| a as f64).sum::<f64>() / values.len() as f64;
+        let image = TfmImage::from_pixels(input.grid.clone(), &pixels, &values);
+        let peak = image.peak().0 as f64;
+        maps.push(AimMap { mode, image, mean, peak });
+    }
+    Ok(maps)
+}
+
+/// 平均灵敏度最高的模式
+pub fn recommend(maps: &[AimMap]) -> Option<&AimMap> {
+    maps.iter().fold(None, |best: Option<&AimMap>, map| match best {
+        Some(best) if best.mean >= map.mean => Some(best),
+        _ => Some(map),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::grid::Grid;
+    use super::*;
+
+    fn close(a: Point, b: Point) -> bool {
+        (a.0 - b.0).abs() < 1e-12 && (a.1 - b.1).abs() < 1e-12
+    }
+
+    #[test]
+    fn specular_reflection_off_horizontal_reflector() {
+        let angle = 30f64.to_radians();
+        let reflected = reflect((angle.sin(), angle.cos()), (0.0, 1.0), 1.0).unwrap();
+        assert!(close(reflected, (angle.sin(), -angle.cos())));
+    }
+
+    #[test]
+    fn mode_conversion_past_critical_angle() {
+        //横波转换为纵波，入射角超过asin(3240/5920)时没有反射纵波
+        let ratio = 5920.0 / 3240.0;
+        let steep = 40f64.to_radians();
+        assert_eq!(reflect((steep.sin(), steep.cos()), (0.0, 1.0), ratio), None);
+        let shallow = 20f64.to_radians();
+        let reflected = reflect((shallow.sin(), shallow.cos()), (0.0, 1.0), ratio).unwrap();
+        assert!((reflected.0 - shallow.sin() * ratio).abs() < 1e-12 && reflected.1 < 0.0);
+    }
+
+    #[test]
+    fn recommend_highest_mean() {
+        let grid = ImagingGrid::Rectangular(Grid { x_min: 0.0, x_max: 1.0, z_min: 1.0, z_max: 2.0, resolution: 1.0 });
+        let map = |name: &str, mean: f64| AimMap {
+            mode: TfmMode::parse(name).unwrap(),
+            image: TfmImage::from_pixels(grid.clone(), &[], &[]),
+            mean,
+            peak: mean,
+        };
+        let maps = vec![map("L-L", 0.2), map("TT-T", 0.5), map("T-T", 0.4)];
+        assert_eq!(recommend(&maps).unwrap().mode.to_string(), "TT-T");
+        assert!(recommend(&[]).is_none());
+    }
+}
@@ -0,0 +1,351 @@
+//! A扫描信号处理：平均、带通滤波、检波、频谱和闸门
+//!
+//! 时间单位µs，频率单位MHz。
+
+use super::fmc::FmcData;
+use super::fmc_file::read_npy_array;
+use super::signal::{envelope, fft, Complex};
+use super::{positive, CalcError, Result};
+
+/// 每次采集的采样点数上限
+pub const MAX_SAMPLES: usize = 65536;
+
+/// 一次或多次采集的A扫描
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waveform {
+    pub sample_rate: f64,
+    /// 第一个采样点对应的时间
+    pub time_offset: f64,
+    /// 每次采集的采样点数相同
+    pub acquisitions: Vec<Vec<f32>>,
+}
+
+impl Waveform {
+    pub fn samples(&self) -> usize {
+        self.acquisitions.first().map_or(0, Vec::len)
+    }
+
+    fn check(&self) -> Result<()> {
+        positive("采样频率", self.sample_rate)?;
+        let samples = self.samples();
+        if !(2..=MAX_SAMPLES).contains(&samples) {
+            return Err(CalcError::Invalid(format!("采样点数{}超出允许范围（2~{}）", samples, MAX_SAMPLES)));
+        }
+        Ok(())
+    }
+}
+
+/// 读取CSV，每行一个采样点，可以有一行表头
+///
+/// 只有一列时为幅度，采样频率取`sample_rate`；多列时第一列为时间（µs），其余各列为多次采集的幅度。
+pub fn parse_csv(text: &str, sample_rate: f64) -> Result<Waveform> {
+    let mut rows: Vec<Vec<f64>> = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let values: std::result::Result<Vec<f64>, _> =
+            line.split(&[',', ';', '\t'][..]).map(|value| value.trim().parse::<f64>()).collect();
+        match values {
+            Ok(values) => {
+                if let Some(first) = rows.first() {
+                    if first.len() != values.len() {
+                        return Err(CalcError::Invalid(format!(
+                            "第{}行有{}列，与前面的{}列不一致",
+                            number + 1,
+                            values.len(),
+                            first.len()
+                        )));
+                    }
+                }
+                rows.push(values);
+            }
+            //表头
+            Err(_) if rows.is_empty() => continue,
+            Err(_) => return Err(CalcError::Invalid(format!("第{}行“{}”不是数字", number + 1, line))),
+        }
+    }
+    let columns = rows.first().map_or(0, Vec::len);
+    let waveform = if columns == 1 {
+        Waveform {
+            sample_rate,
+            time_offset: 0.0,
+            acquisitions: vec![rows.iter().map(|row| row[0] as f32).collect()],
+        }
+    } else {
+        if rows.len() < 2 {
+            return Err(CalcError::Invalid("CSV至少需要两行数据".into()));
+        }
+        let (first, last) = (rows[0][0], rows[rows.len() - 1][0]);
+        if last.is_nan() || last <= first {
+            return Err(CalcError::Invalid("第一列时间必须递增".into()));
+        }
+        Waveform {
+            sample_rate: (rows.len() - 1) as f64 / (last - first),
+            time_offset: first,
+            acquisitions: (1..columns).map(|column| rows.iter().map(|row| row[column] as f32).collect()).collect(),
+        }
+    };
+    waveform.check()?;
+    Ok(waveform)
+}
+
+/// 读取.npy，形状为(采样点)或(采集次数, 采样点)
+pub fn read_npy(bytes: &[u8], sample_rate: f64) -> Result<Waveform> {
+    let (shape, data) = read_npy_array(bytes)?;
+    let samples = match shape.as_slice() {
+        [samples] | [_, samples] => *samples,
+        _ => return Err(CalcError::Invalid(format!(".npy数据形状{:?}应为(采样点)或(采集次数, 采样点)", shape))),
+    };
+    let waveform = Waveform {
+        sample_rate,
+        time_offset: 0.0,
+        acquisitions: data.chunks(samples.max(1)).map(<[f32]>::to_vec).collect(),
+    };
+    waveform.check()?;
+    Ok(waveform)
+}
+
+/// 取FMC数据中的一条A扫描
+pub fn from_fmc(fmc: &FmcData, tx: usize, rx: usize) -> Waveform {
+    Waveform {
+        sample_rate: fmc.sample_rate,
+        time_offset: fmc.time_offset,
+        acquisitions: vec![fmc.ascan(tx, rx).to_vec()],
+    }
+}
+
+/// 前`count`次采集取平均，`count`为0或超过采集次数时全部平均
+pub fn average(waveform: &Waveform, count: usize) -> Vec<f32> {
+    let count = if count == 0 { waveform.acquisitions.len() } else { count.min(waveform.acquisitions.len()) };
+    let mut sum = vec![0f32; waveform.samples()];
+    for acquisition in waveform.acquisitions.iter().take(count) {
+        for (value, &sample) in sum.iter_mut().zip(acquisition.iter()) {
+            *value += sample;
+        }
+    }
+    sum.iter().map(|value| value / count.max(1) as f32).collect()
+}
+
+/// 带通滤波，频域截止并在两侧各留10%带宽的余弦过渡
+pub fn band_pass(signal: &[f32], sample_rate: f64, low: f64, high: f64) -> Result<Vec<f32>> {
+    positive("滤波上限频率", high)?;
+    if low.is_nan() || low < 0.0 || low >= high || high > sample_rate / 2.0 {
+        return Err(CalcError::OutOfRange("滤波频率范围"));
+    }
+    let n = signal.len().next_power_of_two();
+    let mut spectrum = vec![Complex::default(); n];
+    for (value, &sample) in spectrum.iter_mut().zip(signal.iter()) {
+        value.re = sample as f64;
+    }
+    fft(&mut spectrum, false);
+    let taper = 0.1 * (high - low);
+    let gain = |frequency: f64| {
+        let distance = if frequency < low {
+            low - frequency
+        } else if frequency > high {
+            frequency - high
+        } else {
+            return 1.0;
+        };
+        if distance >= taper {
+            0.0
+        } else {
+            0.5 * (1.0 + (std::f64::consts::PI * distance / taper).cos())
+        }
+    };
+    for (k, value) in spectrum.iter_mut().enumerate() {
+        //负频率与对应的正频率相同
+        let frequency = k.min(n - k) as f64 * sample_rate / n as f64;
+        *value = value.scale(gain(frequency));
+    }
+    fft(&mut spectrum, true);
+    Ok(spectrum.iter().take(signal.len()).map(|value| value.re as f32).collect())
+}
+
+/// 检波方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Detection {
+    /// 射频，不检波
+    Rf,
+    /// 全波
+    Full,
+    /// 正半波
+    Positive,
+    /// 负半波
+    Negative,
+    /// 希尔伯特包络
+    Envelope,
+}
+
+pub fn detect(signal: &[f32], detection: Detection) -> Vec<f32> {
+    match detection {
+        Detection::Rf => signal.to_vec(),
+        Detection::Full => signal.iter().map(|value| value.abs()).collect(),
+        Detection::Positive => signal.iter().map(|value| value.max(0.0)).collect(),
+        Detection::Negative => signal.iter().map(|value| (-value).max(0.0)).collect(),
+        Detection::Envelope => envelope(signal).iter().map(|&value| value as f32).collect(),
+    }
+}
+
+/// 幅度谱及其特征频率，幅度已归一化到峰值为1
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum {
+    /// 频率间隔
+    pub resolution: f64,
+    pub magnitude: Vec<f64>,
+    pub peak_frequency: f64,
+    /// −6dB下限和上限频率
+    pub lower: f64,
+    pub upper: f64,
+    /// 中心频率，取−6dB上下限的平均值
+    pub centre: f64,
+    /// −6dB相对带宽，%
+    pub bandwidth: f64,
+}
+
+//频谱补零后的最少点数，提高频率读数的分辨率
+const SPECTRUM_POINTS: usize = 8192;
+
+pub fn spectrum(signal: &[f32], sample_rate: f64) -> Result<Spectrum> {
+    positive("采样频率", sample_rate)?;
+    if signal.len() < 2 {
+        return Err(CalcError::Invalid("信号为空".into()));
+    }
+    //去掉直流分量
+    let mean = signal.iter().map(|&value| value as f64).sum::<f64>() / signal.len() as f64;
+    let n = signal.len().max(SPECTRUM_POINTS).next_power_of_two();
+    let mut data = vec![Complex::default(); n];
+    for (value, &sample) in data.iter_mut().zip(signal.iter()) {
+        value.re = sample as f64 - mean;
+    }
+    fft(&mut data, false);
+    let mut magnitude: Vec<f64> = data.iter().take(n / 2 + 1).map(|value| value.norm()).collect();
+    let (peak_index, peak) = magnitude
+        .iter()
+        .enumerate()
+        .fold((0, 0.0), |best, (i, &value)| if value > best.1 { (i, value) } else { best });
+    if peak <= 0.0 {
+        return Err(CalcError::Invalid("信号幅度为0".into()));
+    }
+    for value in magnitude.iter_mut() {
+        *value /= peak;
+    }
+    let resolution = sample_rate / n as f64;
+    //从峰值向两侧找幅度降到一半的位置，线性插值
+    let crossing = |indices: &mut dyn Iterator<Item = usize>, step: isize| {
+        for i in indices {
+            if magnitude[i] < 0.5 {
+                let previous = (i as isize - step) as usize;
+                let w = (magnitude[previous] - 0.5) / (magnitude[previous] - magnitude[i]);
+                return (previous as f64 + w * step as f64) * resolution;
+            }
+        }
+        if step < 0 {
+            0.0
+        } else {
+            (magnitude.len() - 1) as f64 * resolution
+        }
+    };
+    let lower = crossing(&mut (0..peak_index).rev(), -1);
+    let upper = crossing(&mut (peak_index + 1..magnitude.len()), 1);
+    let centre = (lower + upper) / 2.0;
+    Ok(Spectrum {
+        resolution,
+        peak_frequency: peak_index as f64 * resolution,
+        lower,
+        upper,
+        centre,
+        bandwidth: (upper - lower) / centre * 100.0,
+        magnitude,
+    })
+}
+
+/// 闸门，起点和宽度单位µs，阈值为满屏高度的百分比
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gate {
+    pub start: f64,
+    pub width: f64,
+    pub threshold: f64,
+}
+
+/// 闸门读数，幅度为满屏高度的百分比
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GateReading {
+    pub peak_time: f64,
+    pub peak_amplitude: f64,
+    /// 第一次超过阈值的时间
+    pub crossing_time: Option<f64>,
+}
+
+/// 闸门内的最大幅度和过阈值时间，`full_scale`为满屏高度对应的幅度
+pub fn gate(signal: &[f32], sample_rate: f64, time_offset: f64, gate: &Gate, full_scale: f64) -> Option<GateReading> {
+    if gate.width <= 0.0 || full_scale <= 0.0 {
+        return None;
+    }
+    let first = ((gate.start - time_offset) * sample_rate).ceil().max(0.0) as usize;
+    let last = (((gate.start + gate.width - time_offset) * sample_rate).floor().max(0.0) as usize).min(signal.len());
+    let time = |i: usize| time_offset + i as f64 / sample_rate;
+    let percent = |i: usize| (signal[i] as f64).abs() / full_scale * 100.0;
+    let peak = (first..last).fold(None, |best: Option<usize>, i| match best {
+        Some(best) if percent(best) >= percent(i) => Some(best),
+        _ => Some(i),
+    })?;
+    Some(GateReading {
+        peak_time: time(peak),
+        peak_amplitude: percent(peak),
+        crossing_time: (first..last).find(|&i| percent(i) >= gate.threshold).map(time),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fmc::{pulse, pulse_sigma};
+    use super::*;
+
+    //5MHz、−6dB带宽60%的高斯脉冲，采样100MHz，中心在5µs
+    fn gaussian_pulse() -> Vec<f32> {
+        let sigma = pulse_sigma(5.0, 60.0);
+        (0..1024).map(|i| pulse(i as f64 / 100.0 - 5.0, 5.0, sigma) as f32).collect()
+    }
+
+    #[test]
+    fn centre_frequency_and_bandwidth() {
+        let spectrum = spectrum(&gaussian_pulse(), 100.0).unwrap();
+        assert!((spectrum.centre - 5.0).abs() < 0.05, "中心频率{}", spectrum.centre);
+        assert!((spectrum.peak_frequency - 5.0).abs() < 0.05);
+        assert!((spectrum.lower - 3.5).abs() < 0.05 && (spectrum.upper - 6.5).abs() < 0.05);
+        assert!((spectrum.bandwidth - 60.0).abs() < 1.0, "带宽{}", spectrum.bandwidth);
+    }
+
+    #[test]
+    fn band_pass_removes_out_of_band_tone() {
+        //5MHz脉冲叠加25MHz正弦（正好落在频率点上），滤波1~12MHz后只剩脉冲
+        let clean = gaussian_pulse();
+        let noisy: Vec<f32> = clean
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| value + 0.5 * (2.0 * std::f64::consts::PI * 25.0 * i as f64 / 100.0).sin() as f32)
+            .collect();
+        let filtered = band_pass(&noisy, 100.0, 1.0, 12.0).unwrap();
+        assert!(filtered.iter().zip(clean.iter()).all(|(a, b)| (a - b).abs() < 0.02));
+        assert_eq!(band_pass(&noisy, 100.0, 8.0, 2.0), Err(CalcError::OutOfRange("滤波频率范围")));
+        assert_eq!(band_pass(&noisy, 100.0, 2.0, 60.0), Err(CalcError::OutOfRange("滤波频率范围")));
+    }
+
+    #[test]
+    fn rectification_and_averaging() {
+        let signal = [0.5, -1.0, 0.25];
+        assert_eq!(detect(&signal, Detection::Full), vec![0.5, 1.0, 0.25]);
+        assert_eq!(detect(&signal, Detection::Positive), vec![0.5, 0.0, 0.25]);
+        assert_eq!(detect(&signal, Detection::Negative), vec![0.0, 1.0, 0.0]);
+        let waveform = Waveform {
+            sample_rate: 100.0,
+            time_offset: 0.0,
+            acquisitions: vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![8.0, 0.0]],
+        };
+        assert_eq!(average(&waveform, 2), vec![2.0, 3.0]);
+        assert_eq!(average(&waveform, 0), vec![4.0, 2.0]);
+    }
+}
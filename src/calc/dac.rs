@@ -0,0 +1,202 @@
+//! DAC（距离-波幅）曲线与TCG增益表
+//!
+//! 在试块上测得各深度反射体的回波幅度，DAC曲线在校准点之间按dB线性插值。
+//! TCG把各深度的回波补偿到同一参考波高，增益即该深度DAC相对最高点的dB差。
+
+use super::{positive, CalcError, Result};
+
+/// 校准点幅度的输入方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AmplitudeUnit {
+    /// 同一增益下的波高，%满屏
+    Percent,
+    /// 回波幅度，dB（可以是任意参考）
+    Decibel,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DacInput {
+    /// 校准点（深度mm, 幅度）
+    pub points: Vec<(f64, f64)>,
+    pub unit: AmplitudeUnit,
+    /// 最高点对应的参考波高，%满屏
+    pub reference: f64,
+}
+
+/// DAC曲线上的校准点
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DacPoint {
+    pub depth: f64,
+    /// 相对最高点的dB，不大于0
+    pub level: f64,
+    /// 换算到参考波高后的波高，%满屏
+    pub amplitude: f64,
+    /// TCG增益，dB
+    pub gain: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DacOutput {
+    pub reference: f64,
+    /// 按深度排序
+    pub points: Vec<DacPoint>,
+}
+
+impl DacOutput {
+    /// 任意深度处相对最高点的dB，超出校准范围时取端点值
+    pub fn level(&self, depth: f64) -> f64 {
+        let points = &self.points;
+        if depth <= points[0].depth {
+            return points[0].level;
+        }
+        for pair in points.windows(2) {
+            if depth <= pair[1].depth {
+                let w = (depth - pair[0].depth) / (pair[1].depth - pair[0].depth);
+                return pair[0].level + w * (pair[1].level - pair[0].level);
+            }
+        }
+        points[points.len() - 1].level
+    }
+
+    /// 任意深度处偏移`offset`dB的DAC波高，%满屏
+    pub fn amplitude(&self, depth: f64, offset: f64) -> f64 {
+        self.reference * 10f64.powf((self.level(depth) + offset) / 20.0)
+    }
+
+    /// 导出CSV：深度、DAC、−6dB、−12dB波高和TCG增益
+    pub fn to_csv(&self) -> String {
+        let mut csv = "depth_mm,dac_percent,dac_minus_6db_percent,dac_minus_12db_percent,tcg_gain_db\n".to_string();
+        for point in self.points.iter() {
+            csv += &format!(
+                "{},{:.2},{:.2},{:.2},{:.2}\n",
+                point.depth,
+                point.amplitude,
+                self.amplitude(point.depth, -6.0),
+                self.amplitude(point.depth, -12.0),
+                point.gain
+            );
+        }
+        csv
+    }
+}
+
+/// 解析校准点，每个点为“深度,幅度”，以分号或换行分隔
+pub fn parse_points(text: &str) -> Result<Vec<(f64, f64)>> {
+    let mut points = Vec::new();
+    for item in text.split(&[';', '\n', '；'][..]) {
+        let item = item.trim();
+        if item.is_empty() {
+            continue;
+        }
+        let values: Vec<f64> = item
+            .split(&[',', '，'][..])
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| CalcError::Invalid(format!("校准点“{}”格式错误，应为深度,幅度", item)))?;
+        match values.as_slice() {
+            [depth, amplitude] => points.push((*depth, *amplitude)),
+            _ => return Err(CalcError::Invalid(format!("校准点“{}”格式错误，应为深度,幅度", item))),
+        }
+    }
+    Ok(points)
+}
+
+pub fn build(input: &DacInput) -> Result<DacOutput> {
+    positive("参考波高", input.reference)?;
+    if input.reference > 100.0 {
+        return Err(CalcError::OutOfRange("参考波高"));
+    }
+    if input.points.len() < 2 {
+        return Err(CalcError::Invalid("至少需要两个校准点".into()));
+    }
+    let mut points = input.points.clone();
+    points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    for pair in points.windows(2) {
+        if pair[0].0 == pair[1].0 {
+            return Err(CalcError::Invalid(format!("深度{}mm有重复的校准点", pair[0].0)));
+        }
+    }
+    let mut levels = Vec::with_capacity(points.len());
+    for &(depth, value) in points.iter() {
+        positive("反射体深度", depth)?;
+        levels.push(match input.unit {
+            AmplitudeUnit::Percent => 20.0 * positive("波高", value)?.log10(),
+            AmplitudeUnit::Decibel if value.is_finite() => value,
+            AmplitudeUnit::Decibel => return Err(CalcError::Invalid(format!("深度{}mm的dB值无效", depth))),
+        });
+    }
+    let peak = levels.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    Ok(DacOutput {
+        reference: input.reference,
+        points: points
+            .iter()
+            .zip(levels.iter())
+            .map(|(&(depth, _), &level)| DacPoint {
+                depth,
+                level: level - peak,
+                amplitude: input.reference * 10f64.powf((level - peak) / 20.0),
+                gain: peak - level,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    fn percent(points: &[(f64, f64)]) -> DacInput {
+        DacInput {
+            points: points.to_vec(),
+            unit: AmplitudeUnit::Percent,
+            reference: 80.0,
+        }
+    }
+
+    #[test]
+    fn halving_amplitude_is_six_db() {
+        //乱序输入按深度排序
+        let output = build(&percent(&[(40.0, 20.0), (10.0, 80.0), (20.0, 40.0)])).unwrap();
+        let depths: Vec<f64> = output.points.iter().map(|point| point.depth).collect();
+        assert_eq!(depths, vec![10.0, 20.0, 40.0]);
+        let six = 20.0 * 2f64.log10();
+        assert!(close(output.points[0].gain, 0.0));
+        assert!(close(output.points[1].gain, six));
+        assert!(close(output.points[2].level, -2.0 * six));
+        assert!(close(output.points[1].amplitude, 40.0));
+    }
+
+    #[test]
+    fn interpolates_in_db() {
+        let output = build(&percent(&[(10.0, 80.0), (20.0, 40.0)])).unwrap();
+        assert!(close(output.level(15.0), -10.0 * 2f64.log10()));
+        //超出校准范围取端点值
+        assert!(close(output.amplitude(5.0, 0.0), 80.0));
+        assert!(close(output.amplitude(30.0, 0.0), 40.0));
+        assert!(close(output.amplitude(10.0, -20.0), 8.0));
+    }
+
+    #[test]
+    fn decibel_input() {
+        let input = DacInput {
+            points: vec![(10.0, 12.0), (30.0, 0.0)],
+            unit: AmplitudeUnit::Decibel,
+            reference: 80.0,
+        };
+        let output = build(&input).unwrap();
+        assert!(close(output.points[1].gain, 12.0));
+    }
+
+    #[test]
+    fn parse_and_errors() {
+        assert_eq!(parse_points("10,80; 20，40\n"), Ok(vec![(10.0, 80.0), (20.0, 40.0)]));
+        assert!(parse_points("10").is_err());
+        assert!(build(&percent(&[(10.0, 80.0)])).is_err());
+        assert!(build(&percent(&[(10.0, 80.0), (10.0, 40.0)])).is_err());
+        assert_eq!(build(&percent(&[(10.0, 80.0), (20.0, 0.0)])), Err(CalcError::NonPositive("波高")));
+    }
+}
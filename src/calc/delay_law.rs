@@ -0,0 +1,215 @@
+//! 相控阵延时法则（聚焦法则）
+//!
+//! 楔块上的阵元按第一阵元最低、沿声束前进方向（+x）逐个升高排列，
+//! 第一阵元中心位于x = 0、高度为`first_element_height`。
+
+use super::ray::{self, Point};
+use super::{positive, CalcError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanType {
+    /// 线扫：固定角度，孔径沿阵列平移
+    Linear,
+    /// 扇扫：固定孔径，按角度范围偏转，不聚焦
+    Sectorial,
+    /// 聚焦扇扫：按角度范围偏转，并聚焦在指定深度
+    Focused,
+}
+
+/// 延时法则参数，长度单位mm，声速单位m/s，角度单位度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DelayLawInput {
+    pub scan: ScanType,
+    pub elements: usize,
+    /// 激发孔径阵元数，扇扫从第一阵元开始
+    pub aperture: usize,
+    pub pitch: f64,
+    pub wedge_angle: f64,
+    pub wedge_velocity: f64,
+    pub first_element_height: f64,
+    pub material_velocity: f64,
+    /// 工件中的折射角范围，线扫只使用`angle_start`
+    pub angle_start: f64,
+    pub angle_end: f64,
+    pub angle_step: f64,
+    /// 聚焦深度，线扫为0时不聚焦
+    pub focal_depth: f64,
+}
+
+/// 一条聚焦法则
+#[derive(Debug, Clone, PartialEq)]
+pub struct FocalLaw {
+    pub angle: f64,
+    /// 孔径起始阵元（从1开始）
+    pub first_element: usize,
+    /// 孔径内各阵元的激发延时，单位ns
+    pub delays: Vec<f64>,
+}
+
+/// 聚焦法则数量上限
+pub const MAX_LAWS: usize = 512;
+
+fn element_position(input: &DelayLawInput, index: usize) -> Point {
+    let wedge = input.wedge_angle.to_radians();
+    let offset = index as f64 * input.pitch;
+    (offset * wedge.cos(), -(input.first_element_height + offset * wedge.sin()))
+}
+
+/// 工件中折射角对应的楔块中入射角（度）
+fn incidence_angle(input: &DelayLawInput, angle: f64) -> Result<f64> {
+    let sin = angle.to_radians().sin() * input.wedge_velocity / input.material_velocity;
+    if sin.abs() >= 1.0 {
+        Err(CalcError::OutOfRange("折射角"))
+    } else {
+        Ok(sin.asin().to_degrees())
+    }
+}
+
+fn law(input: &DelayLawInput, angle: f64, first: usize, focal_depth: Option<f64>) -> Result<FocalLaw> {
+    let incidence = incidence_angle(input, angle)?.to_radians();
+    let positions: Vec<Point> = (first..first + input.aperture)
+        .map(|index| element_position(input, index))
+        .collect();
+
+    let times: Vec<f64> = match focal_depth {
+        //不聚焦：楔块中为平面波，按阵元在传播方向上的投影计算
+        None => {
+            let direction = (incidence.sin(), incidence.cos());
+            let velocity = input.wedge_velocity / 1000.0;
+            positions
+                .iter()
+                .map(|p| -(p.0 * direction.0 + p.1 * direction.1) / velocity)
+                .collect()
+        }
+        //聚焦：孔径中心声线的出射点沿折射角到达聚焦深度
+        Some(depth) => {
+            let count = positions.len() as f64;
+            let centre = (
+                positions.iter().map(|p| p.0).sum::<f64>() / count,
+                positions.iter().map(|p| p.1).sum::<f64>() / count,
+            );
+            let exit = centre.0 - centre.1 * incidence.tan();
+            let focus = (exit + depth * angle.to_radians().tan(), depth);
+            positions
+                .iter()
+                .map(|&p| ray::refracted(p, input.wedge_velocity, focus, input.material_velocity, 0.0).0)
+                .collect()
+        }
+    };
+
+    //最远的阵元最先激发
+    let latest = times.iter().cloned().fold(f64::MIN, f64::max);
+    Ok(FocalLaw {
+        angle,
+        first_element: first + 1,
+        delays: times.iter().map(|t| (latest - t) * 1000.0).collect(),
+    })
+}
+
+pub fn delay_laws(input: &DelayLawInput) -> Result<Vec<FocalLaw>> {
+    if input.elements == 0 {
+        return Err(CalcError::NonPositive("阵元数"));
+    }
+    if input.aperture == 0 || input.aperture > input.elements {
+        return Err(CalcError::OutOfRange("孔径阵元数"));
+    }
+    positive("阵元间距", input.pitch)?;
+    positive("楔块声速", input.wedge_velocity)?;
+    positive("工件声速", input.material_velocity)?;
+    if input.first_element_height < 0.0 {
+        return Err(CalcError::OutOfRange("第一阵元高度"));
+    }
+
+    let mut laws = Vec::new();
+    match input.scan {
+        ScanType::Linear => {
+            let focal_depth = if input.focal_depth > 0.0 { Some(input.focal_depth) } else { None };
+            for first in 0..=input.elements - input.aperture {
+                laws.push(law(input, input.angle_start, first, focal_depth)?);
+            }
+        }
+        ScanType::Sectorial | ScanType::Focused => {
+            let focal_depth = if input.scan == ScanType::Focused {
+                Some(positive("聚焦深度", input.focal_depth)?)
+            } else {
+                None
+            };
+            let span = input.angle_end - input.angle_start;
+            let count = if span == 0.0 {
+                1
+            } else {
+                let step = positive("角度步进", input.angle_step)?;
+                (span.abs() / step + 1e-9).floor() as usize + 1
+            };
+            if count > MAX_LAWS {
+                return Err(CalcError::OutOfRange("聚焦法则数量"));
+            }
+            for i in 0..count {
+                let angle = input.angle_start + span.signum() * input.angle_step * i as f64;
+                laws.push(law(input, angle, 0, focal_depth)?);
+            }
+        }
+    }
+    Ok(laws)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(scan: ScanType) -> DelayLawInput {
+        DelayLawInput {
+            scan,
+            elements: 16,
+            aperture: 16,
+            pitch: 0.6,
+            wedge_angle: 0.0,
+            wedge_velocity: 2337.0,
+            first_element_height: 5.0,
+            material_velocity: 3240.0,
+            angle_start: 30.0,
+            angle_end: 30.0,
+            angle_step: 1.0,
+            focal_depth: 20.0,
+        }
+    }
+
+    #[test]
+    fn plane_wave_delays() {
+        //平楔块上相邻阵元的延时差为p·sinθ/v，与楔块声速无关
+        let laws = delay_laws(&input(ScanType::Sectorial)).unwrap();
+        assert_eq!(laws.len(), 1);
+        let step = 0.6 * 30f64.to_radians().sin() / 3.24 * 1000.0;
+        for pair in laws[0].delays.windows(2) {
+            assert!(((pair[0] - pair[1]).abs() - step).abs() < 1e-6);
+        }
+        assert!(laws[0].delays.iter().any(|&delay| delay.abs() < 1e-9));
+    }
+
+    #[test]
+    fn focused_law_is_symmetric_at_zero_degrees() {
+        let focused = DelayLawInput { angle_start: 0.0, angle_end: 0.0, ..input(ScanType::Focused) };
+        let delays = &delay_laws(&focused).unwrap()[0].delays;
+        for (a, b) in delays.iter().zip(delays.iter().rev()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+        //中心阵元离焦点最近，最后激发
+        assert!(delays[7] > delays[0]);
+    }
+
+    #[test]
+    fn linear_scan_steps_aperture() {
+        let linear = DelayLawInput { aperture: 8, ..input(ScanType::Linear) };
+        let laws = delay_laws(&linear).unwrap();
+        assert_eq!(laws.len(), 9);
+        assert_eq!(laws[8].first_element, 9);
+    }
+
+    #[test]
+    fn too_many_laws() {
+        let fine = DelayLawInput { angle_start: 0.0, angle_end: 60.0, angle_step: 0.1, ..input(ScanType::Sectorial) };
+        assert_eq!(delay_laws(&fine), Err(CalcError::OutOfRange("聚焦法则数量")));
+        let wide = DelayLawInput { aperture: 17, ..input(ScanType::Sectorial) };
+        assert_eq!(delay_laws(&wide), Err(CalcError::OutOfRange("孔径阵元数")));
+    }
+}
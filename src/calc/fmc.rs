@@ -0,0 +1,275 @@
+//! 全矩阵采集（FMC）数据与仿真
+//!
+//! 线阵探头直接接触工件，阵元中心位于z = 0，以阵列中心为x = 0。
+
+use super::{positive, CalcError, Result};
+
+/// FMC数据，按[发射阵元][接收阵元][采样点]顺序存放
+#[derive(Debug, Clone, PartialEq)]
+pub struct FmcData {
+    pub elements: usize,
+    pub samples: usize,
+    /// 采样频率，MHz
+    pub sample_rate: f64,
+    /// 第一个采样点对应的时间，µs
+    pub time_offset: f64,
+    pub data: Vec<f32>,
+}
+
+impl FmcData {
+    pub fn new(elements: usize, samples: usize, sample_rate: f64, time_offset: f64) -> Self {
+        FmcData {
+            elements,
+            samples,
+            sample_rate,
+            time_offset,
+            data: vec![0.0; elements * elements * samples],
+        }
+    }
+
+    pub fn ascan(&self, tx: usize, rx: usize) -> &[f32] {
+        let start = (tx * self.elements + rx) * self.samples;
+        &self.data[start..start + self.samples]
+    }
+
+    fn ascan_mut(&mut self, tx: usize, rx: usize) -> &mut [f32] {
+        let start = (tx * self.elements + rx) * self.samples;
+        &mut self.data[start..start + self.samples]
+    }
+}
+
+/// 点散射体，坐标单位mm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scatterer {
+    pub x: f64,
+    pub z: f64,
+    pub amplitude: f64,
+}
+
+/// 仿真参数，频率MHz，声速m/s，间距mm，带宽为−6dB相对带宽（%）
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationInput {
+    pub elements: usize,
+    pub pitch: f64,
+    pub frequency: f64,
+    pub velocity: f64,
+    pub bandwidth: f64,
+    pub sample_rate: f64,
+    pub scatterers: Vec<Scatterer>,
+    /// 底面深度，`None`时没有底面
+    pub backwall: Option<f64>,
+}
+
+/// 阵元数和采样点数上限，避免浏览器中占用过多内存
+pub const MAX_ELEMENTS: usize = 128;
+pub const MAX_SAMPLES: usize = 4096;
+
+/// 阵元中心x坐标，阵列中心为0
+pub fn element_positions(elements: usize, pitch: f64) -> Vec<f64> {
+    let centre = (elements as f64 - 1.0) / 2.0;
+    (0..elements).map(|i| (i as f64 - centre) * pitch).collect()
+}
+
+/// 高斯包络脉冲的时间标准差（µs），由中心频率和−6dB相对带宽决定
+pub fn pulse_sigma(frequency: f64, bandwidth: f64) -> f64 {
+    let sigma_f = bandwidth / 100.0 * frequency / (2.0 * (2.0 * 2f64.ln()).sqrt());
+    1.0 / (2.0 * std::f64::consts::PI * sigma_f)
+}
+
+/// 带限脉冲：高斯包络调制的余弦，t单位µs
+pub fn pulse(t: f64, frequency: f64, sigma: f64) -> f64 {
+    (-t * t / (2.0 * sigma * sigma)).exp() * (2.0 * std::f64::consts::PI * frequency * t).cos()
+}
+
+/// 把一个到达时间为`arrival`、幅度为`amplitude`的回波叠加到A扫描上
+pub(crate) fn add_echo(ascan: &mut [f32], sample_rate: f64, time_offset: f64, arrival: f64, amplitude: f64, frequency: f64, sigma: f64) {
+    let half_width = 4.0 * sigma;
+    let first = (((arrival - half_width - time_offset) * sample_rate).floor().max(0.0)) as usize;
+    let last = (((arrival + half_width - time_offset) * sample_rate).ceil().max(0.0)) as usize;
+    for (i, sample) in ascan.iter_mut().enumerate().take(last + 1).skip(first) {
+        let t = time_offset + i as f64 / sample_rate - arrival;
+        *sample += (amplitude * pulse(t, frequency, sigma)) as f32;
+    }
+}
+
+/// 解析散射体列表，每个散射体为“x,z”或“x,z,幅度”，以分号或换行分隔
+pub fn parse_scatterers(text: &str) -> Result<Vec<Scatterer>> {
+    let mut scatterers = Vec::new();
+    for item in text.split(&[';', '\n', '；'][..]) {
+        let item = item.trim();
+        if item.is_empty() {
+            continue;
+        }
+        let values: Vec<f64> = item
+            .split(&[',', '，'][..])
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| CalcError::Invalid(format!("散射体“{}”格式错误，应为x,z或x,z,幅度", item)))?;
+        let scatterer = match values.as_slice() {
+            [x, z] => Scatterer { x: *x, z: *z, amplitude: 1.0 },
+            [x, z, amplitude] => Scatterer { x: *x, z: *z, amplitude: *amplitude },
+            _ => return Err(CalcError::Invalid(format!("散射体“{}”格式错误，应为x,z或x,z,幅度", item))),
+        };
+        if scatterer.z <= 0.0 {
+            return Err(CalcError::Invalid(format!("散射体“{}”的深度必须大于0", item)));
+        }
+        scatterers.push(scatterer);
+    }
+    Ok(scatterers)
+}
+
+pub(crate) fn check_array(elements: usize, pitch: f64, frequency: f64, velocity: f64, bandwidth: f64, sample_rate: f64) -> Result<()> {
+    if elements == 0 {
+        return Err(CalcError::NonPositive("阵元数"));
+    }
+    if elements > MAX_ELEMENTS {
+        return Err(CalcError::OutOfRange("阵元数"));
+    }
+    positive("阵元间距", pitch)?;
+    positive("频率", frequency)?;
+    positive("声速", velocity)?;
+    positive("带宽", bandwidth)?;
+    positive("采样频率", sample_rate)?;
+    //至少满足奈奎斯特采样
+    if sample_rate < 2.0 * frequency * (1.0 + bandwidth / 200.0) {
+        return Err(CalcError::OutOfRange("采样频率（低于奈奎斯特频率）"));
+    }
+    Ok(())
+}
+
+/// 合成N×N条A扫描，散射体按二维柱面波扩散衰减，底面按镜面反射
+pub fn simulate(input: &SimulationInput) -> Result<FmcData> {
+    check_array(input.elements, input.pitch, input.frequency, input.velocity, input.bandwidth, input.sample_rate)?;
+    if let Some(depth) = input.backwall {
+        positive("底面深度", depth)?;
+    }
+    if input.scatterers.is_empty() && input.backwall.is_none() {
+        return Err(CalcError::Invalid("请至少输入一个散射体或底面深度".into()));
+    }
+
+    let velocity = input.velocity / 1000.0;
+    let positions = element_positions(input.elements, input.pitch);
+    let aperture = input.pitch * (input.elements as f64 - 1.0);
+    let sigma = pulse_sigma(input.frequency, input.bandwidth);
+
+    //最远回波决定采样长度
+    let farthest = input
+        .scatterers
+        .iter()
+        .map(|s| 2.0 * (s.z * s.z + (s.x.abs() + aperture / 2.0).powi(2)).sqrt())
+        .chain(input.backwall.map(|depth| (4.0 * depth * depth + aperture * aperture).sqrt()))
+        .fold(0.0, f64::max);
+    let duration = farthest / velocity + 8.0 * sigma;
+    let samples = (duration * input.sample_rate).ceil() as usize;
+    if samples > MAX_SAMPLES {
+        return Err(CalcError::OutOfRange("采样长度（请减小深度或采样频率）"));
+    }
+
+    let mut fmc = FmcData::new(input.elements, samples, input.sample_rate, 0.0);
+    for tx in 0..input.elements {
+        for rx in 0..input.elements {
+            let (xt, xr) = (positions[tx], positions[rx]);
+            let ascan = fmc.ascan_mut(tx, rx);
+            for s in input.scatterers.iter() {
+                let rt = ((s.x - xt).powi(2) + s.z * s.z).sqrt();
+                let rr = ((s.x - xr).powi(2) + s.z * s.z).sqrt();
+                let amplitude = s.amplitude / (rt * rr).sqrt();
+                add_echo(ascan, input.sample_rate, 0.0, (rt + rr) / velocity, amplitude, input.frequency, sigma);
+            }
+            if let Some(depth) = input.backwall {
+                let path = ((xt - xr).powi(2) + 4.0 * depth * depth).sqrt();
+                add_echo(ascan, input.sample_rate, 0.0, path / velocity, 1.0 / path.sqrt(), input.frequency, sigma);
+            }
+        }
+    }
+
+    //归一化到最大幅度为1
+    let peak = fmc.data.iter().fold(0f32, |peak, v| peak.max(v.abs()));
+    if peak > 0.0 {
+        for v in fmc.data.iter_mut() {
+            *v /= peak;
+        }
+    }
+    Ok(fmc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(scatterers: Vec<Scatterer>, backwall: Option<f64>) -> SimulationInput {
+        SimulationInput {
+            elements: 4,
+            pitch: 1.0,
+            frequency: 5.0,
+            velocity: 5920.0,
+            bandwidth: 60.0,
+            sample_rate: 100.0,
+            scatterers,
+            backwall,
+        }
+    }
+
+    //最大正峰所在的时间，µs
+    fn peak_time(fmc: &FmcData, tx: usize, rx: usize) -> f64 {
+        let ascan = fmc.ascan(tx, rx);
+        let index = (0..ascan.len()).fold(0, |best, i| if ascan[i] > ascan[best] { i } else { best });
+        fmc.time_offset + index as f64 / fmc.sample_rate
+    }
+
+    #[test]
+    fn scatterer_echo_arrival() {
+        let fmc = simulate(&input(vec![Scatterer { x: 1.0, z: 12.0, amplitude: 1.0 }], None)).unwrap();
+        let positions = element_positions(4, 1.0);
+        assert_eq!(positions, vec![-1.5, -0.5, 0.5, 1.5]);
+        let rt = (1.0 - positions[0]).hypot(12.0);
+        let rr = (1.0 - positions[3]).hypot(12.0);
+        assert!((peak_time(&fmc, 0, 3) - (rt + rr) / 5.92).abs() <= 0.5 / 100.0);
+        assert_eq!(fmc.ascan(0, 3), fmc.ascan(3, 0));
+    }
+
+    #[test]
+    fn backwall_echo_path() {
+        let fmc = simulate(&input(Vec::new(), Some(10.0))).unwrap();
+        //镜像：收发阵元间距3mm，路径√(3² + 20²)
+        let path = 3f64.hypot(20.0);
+        assert!((peak_time(&fmc, 0, 3) - path / 5.92).abs() <= 0.5 / 100.0);
+        assert!((peak_time(&fmc, 1, 1) - 20.0 / 5.92).abs() <= 0.5 / 100.0);
+    }
+
+    #[test]
+    fn rejected_inputs() {
+        let slow = SimulationInput { sample_rate: 10.0, ..input(Vec::new(), Some(10.0)) };
+        assert_eq!(simulate(&slow), Err(CalcError::OutOfRange("采样频率（低于奈奎斯特频率）")));
+        let deep = input(Vec::new(), Some(200.0));
+        assert_eq!(simulate(&deep), Err(CalcError::OutOfRange("采样长度（请减小深度或采样频率）")));
+        assert!(simulate(&input(Vec::new(), None)).is_err());
+        let many = SimulationInput { elements: MAX_ELEMENTS + 1, ..input(Vec::new(), Some(10.0)) };
+        assert_eq!(simulate(&many), Err(CalcError::OutOfRange("阵元数")));
+    }
+
+    #[test]
+    fn scatterer_list() {
+        let scatterers = parse_scatterers("0,20；-5，30, 0.5\n 2,8; ").unwrap();
+        assert_eq!(
+            scatterers,
+            vec![
+                Scatterer { x: 0.0, z: 20.0, amplitude: 1.0 },
+                Scatterer { x: -5.0, z: 30.0, amplitude: 0.5 },
+                Scatterer { x: 2.0, z: 8.0, amplitude: 1.0 },
+            ]
+        );
+        assert_eq!(
+            parse_scatterers("1,a"),
+            Err(CalcError::Invalid("散射体“1,a”格式错误，应为x,z或x,z,幅度".into()))
+        );
+        assert_eq!(
+            parse_scatterers("1,2,3,4"),
+            Err(CalcError::Invalid("散射体“1,2,3,4”格式错误，应为x,z或x,z,幅度".into()))
+        );
+        assert_eq!(
+            parse_scatterers("0,20；1，-2"),
+            Err(CalcError::Invalid("散射体“1，-2”的深度必须大于0".into()))
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! 读取仪器采集的FMC数据文件
+//!
+//! 支持两种格式：
+//! - 小端int16或float32原始数据，另附JSON说明文件给出阵元数、采样点数、采样频率和时间偏移；
+//! - NumPy `.npy`文件，形状为(发射, 接收, 采样点)或(发射×接收, 采样点)。
+//!
+//! 数据都按[发射阵元][接收阵元][采样点]顺序存放，与`FmcData`一致。
+
+use super::fmc::{FmcData, MAX_ELEMENTS, MAX_SAMPLES};
+use super::{positive, CalcError, Result};
+
+/// 原始数据的采样格式
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum SampleFormat {
+    #[serde(rename = "int16")]
+    Int16,
+    #[serde(rename = "float32")]
+    Float32,
+    #[serde(rename = "float64")]
+    Float64,
+}
+
+impl SampleFormat {
+    fn bytes(self) -> usize {
+        match self {
+            SampleFormat::Int16 => 2,
+            SampleFormat::Float32 => 4,
+            SampleFormat::Float64 => 8,
+        }
+    }
+}
+
+/// JSON说明文件，例如`{"elements": 64, "samples": 2000, "sample_rate": 100, "time_offset": 2.5, "format": "int16"}`
+///
+/// 采样频率单位MHz，时间偏移单位µs；未给出格式时按文件长度判断。
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Sidecar {
+    pub elements: usize,
+    pub samples: usize,
+    pub sample_rate: f64,
+    #[serde(default)]
+    pub time_offset: f64,
+    #[serde(default)]
+    pub format: Option<SampleFormat>,
+}
+
+pub fn parse_sidecar(text: &str) -> Result<Sidecar> {
+    let sidecar: Sidecar =
+        serde_json::from_str(text).map_err(|err| CalcError::Invalid(format!("JSON说明文件格式错误：{}", err)))?;
+    positive("采样频率", sidecar.sample_rate)?;
+    check_dimensions(sidecar.elements, sidecar.samples)?;
+    Ok(sidecar)
+}
+
+fn check_dimensions(elements: usize, samples: usize) -> Result<()> {
+    if !(1..=MAX_ELEMENTS).contains(&elements) {
+        return Err(CalcError::Invalid(format!("阵元数{}超出允许范围（1~{}）", elements, MAX_ELEMENTS)));
+    }
+    if !(2..=MAX_SAMPLES).contains(&samples) {
+        return Err(CalcError::Invalid(format!("采样点数{}超出允许范围（2~{}）", samples, MAX_SAMPLES)));
+    }
+    Ok(())
+}
+
+/// 读取原始数据，int16换算到±1
+pub fn read_raw(bytes: &[u8], sidecar: &Sidecar) -> Result<FmcData> {
+    let count = sidecar.elements * sidecar.elements * sidecar.samples;
+    let format = match sidecar.format {
+        Some(format) => format,
+        None if bytes.len() == count * 2 => SampleFormat::Int16,
+        None if bytes.len() == count * 4 => SampleFormat::Float32,
+        None => {
+            return Err(CalcError::Invalid(format!(
+                "数据文件长度{}字节与说明文件不符：{}×{}×{}个采样点应为{}字节（int16）或{}字节（float32）",
+                bytes.len(),
+                sidecar.elements,
+                sidecar.elements,
+                sidecar.samples,
+                count * 2,
+                count * 4
+            )))
+        }
+    };
+    if bytes.len() != count * format.bytes() {
+        return Err(CalcError::Invalid(format!(
+            "数据文件长度{}字节与说明文件不符：{}×{}×{}个采样点按{}应为{}字节",
+            bytes.len(),
+            sidecar.elements,
+            sidecar.elements,
+            sidecar.samples,
+            match format {
+                SampleFormat::Int16 => "int16",
+                SampleFormat::Float32 => "float32",
+                SampleFormat::Float64 => "float64",
+            },
+            count * format.bytes()
+        )));
+    }
+    let mut fmc = FmcData::new(sidecar.elements, sidecar.samples, sidecar.sample_rate, sidecar.time_offset);
+    decode(bytes, format, &mut fmc.data);
+    Ok(fmc)
+}
+
+fn decode(bytes: &[u8], format: SampleFormat, data: &mut [f32]) {
+    match format {
+        SampleFormat::Int16 => {
+            for (value, chunk) in data.iter_mut().zip(bytes.chunks_exact(2)) {
+                *value = i16::from_le_bytes([chunk[0], chunk[1]]) as f32 / 32768.0;
+            }
+        }
+        SampleFormat::Float32 => {
+            for (value, chunk) in data.iter_mut().zip(bytes.chunks_exact(4)) {
+                *value = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            }
+        }
+        //显示和成像都用f32，精度足够
+        SampleFormat::Float64 => {
+            for (value, chunk) in data.iter_mut().zip(bytes.chunks_exact(8)) {
+                let mut raw = [0; 8];
+                raw.copy_from_slice(chunk);
+                *value = f64::from_le_bytes(raw) as f32;
+            }
+        }
+    }
+}
+
+//.npy文件头：魔数、版本、头长度，之后是Python字典形式的描述
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+
+//取出头部字典中某个键后面的内容
+fn npy_field<'a>(header: &'a str, key: &str) -> Result<&'a str> {
+    let start = header
+        .find(&format!("'{}'", key))
+        .ok_or_else(|| CalcError::Invalid(format!(".npy文件头缺少{}", key)))?;
+    let rest = &header[start + key.len() + 2..];
+    let colon = rest.find(':').ok_or_else(|| CalcError::Invalid(".npy文件头格式错误".into()))?;
+    Ok(rest[colon + 1..].trim_start())
+}
+
+/// 读取.npy数组，返回形状和按C顺序展开的数据，支持int16、float32和float64（小端）
+pub(crate) fn read_npy_array(bytes: &[u8]) -> Result<(Vec<usize>, Vec<f32>)> {
+    if bytes.len() < 10 || &bytes[..6] != NPY_MAGIC {
+        return Err(CalcError::Invalid("不是有效的.npy文件".into()));
+    }
+    let (header_start, header_len) = match bytes[6] {
+        1 => (10, u16::from_le_bytes([bytes[8], bytes[9]]) as usize),
+        2 | 3 if bytes.len() >= 12 => (12, u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize),
+        version => return Err(CalcError::Invalid(format!("不支持的.npy版本{}", version))),
+    };
+    let data_start = header_len
+        .checked_add(header_start)
+        .ok_or_else(|| CalcError::Invalid(".npy文件头格式错误".into()))?;
+    let header = bytes
+        .get(header_start..data_start)
+        .and_then(|header| std::str::from_utf8(header).ok())
+        .ok_or_else(|| CalcError::Invalid(".npy文件头格式错误".into()))?;
+
+    let descr = npy_field(header, "descr")?;
+    let format = if descr.starts_with("'<i2'") || descr.starts_with("'|i2'") {
+        SampleFormat::Int16
+    } else if descr.starts_with("'<f4'") {
+        SampleFormat::Float32
+    } else if descr.starts_with("'<f8'") {
+        SampleFormat::Float64
+    } else {
+        return Err(CalcError::Invalid(format!(
+            "不支持的.npy数据类型{}，请保存为小端int16、float32或float64",
+            descr.split(',').next().unwrap_or("")
+        )));
+    };
+    if npy_field(header, "fortran_order")?.starts_with("True") {
+        return Err(CalcError::Invalid(".npy数据为Fortran顺序，请用C顺序保存".into()));
+    }
+    let shape = npy_field(header, "shape")?;
+    let shape: Vec<usize> = shape
+        .get(1..shape.find(')').unwrap_or(1))
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.parse::<usize>())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| CalcError::Invalid(".npy文件头中的形状格式错误".into()))?;
+
+    //形状来自文件头，乘积可能溢出
+    let too_large = || CalcError::Invalid(format!(".npy数据形状{:?}过大", shape));
+    let count = shape
+        .iter()
+        .try_fold(1usize, |count, &dim| count.checked_mul(dim))
+        .ok_or_else(too_large)?;
+    let expected = count
+        .checked_mul(format.bytes())
+        .and_then(|size| size.checked_add(data_start))
+        .ok_or_else(too_large)?;
+    if bytes.len() != expected {
+        return Err(CalcError::Invalid(format!(
+            ".npy文件长度{}字节，按形状{:?}应为{}字节",
+            bytes.len(),
+            shape,
+            expected
+        )));
+    }
+    let mut data = vec![0.0; count];
+    decode(&bytes[data_start..], format, &mut data);
+    Ok((shape, data))
+}
+
+/// 读取.npy格式的FMC数据
+///
+/// 有说明文件时从中取采样频率和时间偏移并核对维数，否则使用`sample_rate`，时间偏移为0。
+pub fn read_npy(bytes: &[u8], sidecar: Option<&Sidecar>, sample_rate: f64) -> Result<FmcData> {
+    let (shape, data) = read_npy_array(bytes)?;
+    let (elements, samples) = match shape.as_slice() {
+        [tx, rx, samples] if tx == rx => (*tx, *samples),
+        [tx, rx, _] => return Err(CalcError::Invalid(format!("发射阵元数{}与接收阵元数{}不相等", tx, rx))),
+        [ascans, samples] => {
+            let elements = (*ascans as f64).sqrt().round() as usize;
+            if elements * elements != *ascans {
+                return Err(CalcError::Invalid(format!("A扫描条数{}不是阵元数的平方", ascans)));
+            }
+            (elements, *samples)
+        }
+        _ => {
+            return Err(CalcError::Invalid(format!(
+                ".npy数据形状{:?}应为(发射, 接收, 采样点)或(发射×接收, 采样点)",
+                shape
+            )))
+        }
+    };
+    check_dimensions(elements, samples)?;
+    let (sample_rate, time_offset) = match sidecar {
+        Some(sidecar) if sidecar.elements != elements || sidecar.samples != samples => {
+            return Err(CalcError::Invalid(format!(
+                ".npy数据为{}阵元、{}个采样点，与说明文件的{}阵元、{}个采样点不符",
+                elements, samples, sidecar.elements, sidecar.samples
+            )))
+        }
+        Some(sidecar) => (sidecar.sample_rate, sidecar.time_offset),
+        None => (positive("采样频率", sample_rate)?, 0.0),
+    };
+    Ok(FmcData {
+        elements,
+        samples,
+        sample_rate,
+        time_offset,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn npy(descr: &str, shape: &str, data: &[u8]) -> Vec<u8> {
+        let header = format!("{{'descr': '{}', 'fortran_order': False, 'shape': ({}), }}\n", descr, shape);
+        let mut bytes = NPY_MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn float64_npy_is_decoded() {
+        let data: Vec<u8> = [0.5f64, -0.25, 1.0].iter().flat_map(|value| value.to_le_bytes().to_vec()).collect();
+        let (shape, data) = read_npy_array(&npy("<f8", "3,", &data)).unwrap();
+        assert_eq!(shape, vec![3]);
+        assert_eq!(data, vec![0.5, -0.25, 1.0]);
+    }
+
+    #[test]
+    fn oversized_shape_is_rejected() {
+        let shape = format!("{}, {}", usize::MAX, 2);
+        assert!(matches!(read_npy_array(&npy("<f4", &shape, &[])), Err(CalcError::Invalid(_))));
+        let shape = format!("{},", usize::MAX / 2);
+        assert!(matches!(read_npy_array(&npy("<f8", &shape, &[])), Err(CalcError::Invalid(_))));
+    }
+}
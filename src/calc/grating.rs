@@ -0,0 +1,176 @@
+//! 栅瓣预测与阵列因子
+
+use super::probe::wavelength;
+use super::{positive, CalcError, Result};
+
+/// 栅瓣计算参数，频率MHz，声速m/s，间距mm，带宽为−6dB相对带宽（%），角度单位度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GratingInput {
+    pub frequency: f64,
+    pub velocity: f64,
+    pub pitch: f64,
+    pub elements: usize,
+    pub bandwidth: f64,
+    pub steering_min: f64,
+    pub steering_max: f64,
+    pub steering_step: f64,
+}
+
+/// 某一偏转角下可见区内的栅瓣
+#[derive(Debug, Clone, PartialEq)]
+pub struct SteeringLobes {
+    pub steering: f64,
+    /// 中心频率下的栅瓣角度
+    pub lobes: Vec<f64>,
+    /// 带宽上限频率下没有栅瓣进入可见区
+    pub safe: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GratingOutput {
+    pub lambda: f64,
+    /// 带宽上限频率对应的波长
+    pub lambda_min: f64,
+    /// 整个偏转范围内不出现栅瓣的最大阵元间距
+    pub max_pitch: f64,
+    pub rows: Vec<SteeringLobes>,
+}
+
+/// 偏转角数量上限
+const MAX_STEPS: usize = 361;
+
+/// 中心频率下的栅瓣角度 sinθm = sinθs + mλ/p（m ≠ 0）
+fn lobes(pitch: f64, lambda: f64, steering: f64) -> Vec<f64> {
+    let sin_steering = steering.to_radians().sin();
+    let order = (2.0 * pitch / lambda).ceil() as i32;
+    (-order..=order)
+        .filter(|&m| m != 0)
+        .map(|m| sin_steering + m as f64 * lambda / pitch)
+        .filter(|sin| sin.abs() <= 1.0)
+        .map(|sin| sin.asin().to_degrees())
+        .collect()
+}
+
+fn upper_frequency(input: &GratingInput) -> f64 {
+    input.frequency * (1.0 + input.bandwidth / 200.0)
+}
+
+pub fn grating_lobes(input: &GratingInput) -> Result<GratingOutput> {
+    let lambda = wavelength(input.velocity, input.frequency)?;
+    let pitch = positive("阵元间距", input.pitch)?;
+    if input.elements == 0 {
+        return Err(CalcError::NonPositive("阵元数"));
+    }
+    if input.bandwidth < 0.0 {
+        return Err(CalcError::OutOfRange("带宽"));
+    }
+    let lambda_min = wavelength(input.velocity, upper_frequency(input))?;
+
+    let span = input.steering_max - input.steering_min;
+    let count = if span == 0.0 {
+        1
+    } else {
+        let step = positive("角度步进", input.steering_step)?;
+        (span.abs() / step + 1e-9).floor() as usize + 1
+    };
+    if count > MAX_STEPS {
+        return Err(CalcError::OutOfRange("偏转角数量"));
+    }
+    let mut rows = Vec::with_capacity(count);
+    let mut widest: f64 = 0.0;
+    for i in 0..count {
+        let steering = input.steering_min + span.signum() * input.steering_step * i as f64;
+        if steering.abs() >= 90.0 {
+            return Err(CalcError::OutOfRange("偏转角"));
+        }
+        let sin_steering = steering.to_radians().sin().abs();
+        widest = widest.max(sin_steering);
+        rows.push(SteeringLobes {
+            steering,
+            lobes: lobes(pitch, lambda, steering),
+            safe: pitch < lambda_min / (1.0 + sin_steering),
+        });
+    }
+
+    Ok(GratingOutput {
+        lambda,
+        lambda_min,
+        max_pitch: lambda_min / (1.0 + widest),
+        rows,
+    })
+}
+
+/// 归一化阵列因子 |sin(Nψ)/(N sinψ)|，ψ = πp(sinθ − sinθs)/λ
+pub fn array_factor(pitch: f64, elements: usize, lambda: f64, steering: f64, angle: f64) -> f64 {
+    let psi = std::f64::consts::PI * pitch * (angle.to_radians().sin() - steering.to_radians().sin()) / lambda;
+    let denominator = elements as f64 * psi.sin();
+    if denominator.abs() < 1e-9 {
+        1.0
+    } else {
+        ((elements as f64 * psi).sin() / denominator).abs()
+    }
+}
+
+/// −90度～90度的阵列因子曲线，(角度, dB)，最低−60dB
+pub fn array_factor_curve(input: &GratingInput, steering: f64, upper_band: bool) -> Result<Vec<(f64, f64)>> {
+    let frequency = if upper_band { upper_frequency(input) } else { input.frequency };
+    let lambda = wavelength(input.velocity, frequency)?;
+    let pitch = positive("阵元间距", input.pitch)?;
+    Ok((0..=360)
+        .map(|i| {
+            let angle = -90.0 + i as f64 * 0.5;
+            let level = array_factor(pitch, input.elements, lambda, steering, angle);
+            (angle, (20.0 * level.log10()).max(-60.0))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(pitch: f64, bandwidth: f64) -> GratingInput {
+        GratingInput {
+            frequency: 5.0,
+            velocity: 5920.0,
+            pitch,
+            elements: 16,
+            bandwidth,
+            steering_min: -80.0,
+            steering_max: 80.0,
+            steering_step: 10.0,
+        }
+    }
+
+    #[test]
+    fn half_wavelength_pitch_is_safe() {
+        let output = grating_lobes(&input(1.184 / 2.0, 0.0)).unwrap();
+        assert_eq!(output.rows.len(), 17);
+        assert!(output.rows.iter().all(|row| row.lobes.is_empty() && row.safe));
+    }
+
+    #[test]
+    fn wavelength_pitch_lobes_at_ninety_degrees() {
+        let lambda = wavelength(5920.0, 5.0).unwrap();
+        assert_eq!(lobes(lambda, lambda, 0.0), vec![-90.0, 90.0]);
+        let output = grating_lobes(&GratingInput { steering_min: 0.0, steering_max: 0.0, ..input(lambda, 0.0) }).unwrap();
+        assert!(!output.rows[0].safe);
+    }
+
+    #[test]
+    fn max_pitch_for_steering_range() {
+        let output = grating_lobes(&input(0.5, 60.0)).unwrap();
+        let lambda_min = 5.92 / (5.0 * 1.3);
+        assert!((output.lambda_min - lambda_min).abs() < 1e-12);
+        assert!((output.max_pitch - lambda_min / (1.0 + 80f64.to_radians().sin())).abs() < 1e-12);
+    }
+
+    #[test]
+    fn array_factor_peaks_at_steering_angle() {
+        for &steering in [-30.0, 0.0, 45.0].iter() {
+            assert!((array_factor(0.6, 16, 1.184, steering, steering) - 1.0).abs() < 1e-12);
+            assert!(array_factor(0.6, 16, 1.184, steering, steering + 10.0) < 1.0);
+        }
+        assert_eq!(grating_lobes(&input(0.0, 0.0)), Err(CalcError::NonPositive("阵元间距")));
+    }
+}
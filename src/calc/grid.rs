@@ -0,0 +1,372 @@
+//! TFM成像网格：矩形、扇形（极坐标）和多边形区域
+//!
+//! 像素按行（深度或声程）存放；扇形网格的列对应角度、行对应到原点的距离。
+//! 多边形区域在矩形网格上屏蔽区域外的像素，只计算区域内的像素。
+
+use super::ray::Point;
+use super::{positive, CalcError, Result};
+
+/// 像素总数上限
+pub const MAX_PIXELS: usize = 250_000;
+
+/// 矩形成像区域，单位mm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Grid {
+    pub x_min: f64,
+    pub x_max: f64,
+    pub z_min: f64,
+    pub z_max: f64,
+    /// 像素间距
+    pub resolution: f64,
+}
+
+impl Grid {
+    pub fn columns(&self) -> usize {
+        ((self.x_max - self.x_min) / self.resolution).floor() as usize + 1
+    }
+
+    pub fn rows(&self) -> usize {
+        ((self.z_max - self.z_min) / self.resolution).floor() as usize + 1
+    }
+
+    pub fn point(&self, column: usize, row: usize) -> Point {
+        (
+            self.x_min + column as f64 * self.resolution,
+            self.z_min + row as f64 * self.resolution,
+        )
+    }
+
+    fn check(&self) -> Result<()> {
+        positive("像素间距", self.resolution)?;
+        if self.x_max.is_nan() || self.x_max <= self.x_min {
+            return Err(CalcError::OutOfRange("成像区域x范围"));
+        }
+        if self.z_max.is_nan() || self.z_max <= self.z_min || self.z_min < 0.0 {
+            return Err(CalcError::OutOfRange("成像区域深度范围"));
+        }
+        if self.columns() * self.rows() > MAX_PIXELS {
+            return Err(CalcError::OutOfRange("像素数（请增大像素间距或缩小成像区域）"));
+        }
+        Ok(())
+    }
+}
+
+/// 扇形网格，角度单位度，从竖直向下方向起算、偏向+x为正；距离单位mm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolarGrid {
+    pub origin: Point,
+    pub angle_min: f64,
+    pub angle_max: f64,
+    pub angle_step: f64,
+    pub radius_min: f64,
+    pub radius_max: f64,
+    pub radius_step: f64,
+}
+
+impl PolarGrid {
+    pub fn columns(&self) -> usize {
+        ((self.angle_max - self.angle_min) / self.angle_step + 1e-9).floor() as usize + 1
+    }
+
+    pub fn rows(&self) -> usize {
+        ((self.radius_max - self.radius_min) / self.radius_step + 1e-9).floor() as usize + 1
+    }
+
+    pub fn point(&self, column: usize, row: usize) -> Point {
+        let angle = (self.angle_min + column as f64 * self.angle_step).to_radians();
+        let radius = self.radius_min + row as f64 * self.radius_step;
+        (self.origin.0 + radius * angle.sin(), self.origin.1 + radius * angle.cos())
+    }
+
+    fn check(&self) -> Result<()> {
+        positive("角度步进", self.angle_step)?;
+        positive("声程步进", self.radius_step)?;
+        if self.angle_min.is_nan() || self.angle_min < -90.0 || self.angle_max > 90.0 || self.angle_max < self.angle_min {
+            return Err(CalcError::OutOfRange("扇形角度范围"));
+        }
+        if self.radius_min.is_nan() || self.radius_min < 0.0 || self.radius_max <= self.radius_min {
+            return Err(CalcError::OutOfRange("扇形声程范围"));
+        }
+        if self.columns() * self.rows() > MAX_PIXELS {
+            return Err(CalcError::OutOfRange("像素数（请增大步进或缩小成像区域）"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImagingGrid {
+    Rectangular(Grid),
+    Polar(PolarGrid),
+    /// 矩形网格上只计算多边形内部的像素，顶点顺序不限
+    Polygon(Grid, Vec<Point>),
+}
+
+//射线法判断点是否在多边形内
+fn contains(polygon: &[Point], point: Point) -> bool {
+    let mut inside = false;
+    let mut previous = polygon[polygon.len() - 1];
+    for &vertex in polygon.iter() {
+        if (vertex.1 > point.1) != (previous.1 > point.1) {
+            let x = vertex.0 + (point.1 - vertex.1) / (previous.1 - vertex.1) * (previous.0 - vertex.0);
+            if point.0 < x {
+                inside = !inside;
+            }
+        }
+        previous = vertex;
+    }
+    inside
+}
+
+impl ImagingGrid {
+    pub fn columns(&self) -> usize {
+        match self {
+            ImagingGrid::Rectangular(grid) | ImagingGrid::Polygon(grid, _) => grid.columns(),
+            ImagingGrid::Polar(grid) => grid.columns(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        match self {
+            ImagingGrid::Rectangular(grid) | ImagingGrid::Polygon(grid, _) => grid.rows(),
+            ImagingGrid::Polar(grid) => grid.rows(),
+        }
+    }
+
+    /// 第`index`个像素的位置
+    pub fn point(&self, index: usize) -> Point {
+        let (column, row) = (index % self.columns(), index / self.columns());
+        match self {
+            ImagingGrid::Rectangular(grid) | ImagingGrid::Polygon(grid, _) => grid.point(column, row),
+            ImagingGrid::Polar(grid) => grid.point(column, row),
+        }
+    }
+
+    /// 需要计算的像素（序号, 位置）
+    pub fn pixels(&self) -> Vec<(usize, Point)> {
+        let all = (0..self.columns() * self.rows()).map(|index| (index, self.point(index)));
+        match self {
+            ImagingGrid::Polygon(_, polygon) => all.filter(|&(_, point)| contains(polygon, point)).collect(),
+            _ => all.collect(),
+        }
+    }
+
+    /// 成像区域的外接矩形(x_min, x_max, z_min, z_max)
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        match self {
+            ImagingGrid::Rectangular(grid) | ImagingGrid::Polygon(grid, _) => {
+                let (x, z) = grid.point(grid.columns() - 1, grid.rows() - 1);
+                (grid.x_min, x, grid.z_min, z)
+            }
+            ImagingGrid::Polar(grid) => {
+                //扇形边界由最小、最大声程两条圆弧上的像素确定
+                let (columns, rows) = (grid.columns(), grid.rows());
+                let mut points = Vec::new();
+                for &row in [0, rows - 1].iter() {
+                    for column in 0..columns {
+                        points.push(grid.point(column, row));
+                    }
+                }
+                points.iter().fold(
+                    (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY),
+                    |(x0, x1, z0, z1), p| (x0.min(p.0), x1.max(p.0), z0.min(p.1), z1.max(p.1)),
+                )
+            }
+        }
+    }
+
+    /// 相邻像素的最大间距，扇形网格取最大声程处的弧长和声程步进中的较大值
+    pub fn spacing(&self) -> f64 {
+        match self {
+            ImagingGrid::Rectangular(grid) | ImagingGrid::Polygon(grid, _) => grid.resolution,
+            ImagingGrid::Polar(grid) => grid.radius_step.max(grid.radius_max * grid.angle_step.to_radians()),
+        }
+    }
+
+    /// 网格间距不大于λ/5
+    pub fn fine_enough(&self, wavelength: f64) -> bool {
+        self.spacing() <= wavelength / 5.0
+    }
+
+    pub fn check(&self) -> Result<()> {
+        match self {
+            ImagingGrid::Rectangular(grid) => grid.check(),
+            ImagingGrid::Polar(grid) => grid.check(),
+            ImagingGrid::Polygon(grid, polygon) => {
+                grid.check()?;
+                if polygon.len() < 3 {
+                    return Err(CalcError::Invalid("多边形至少需要3个顶点".into()));
+                }
+                if self.pixels().is_empty() {
+                    return Err(CalcError::Invalid("多边形内没有像素，请检查顶点坐标".into()));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 解析多边形顶点，每个顶点为“x,z”，以分号或换行分隔
+pub fn parse_polygon(text: &str) -> Result<Vec<Point>> {
+    let mut polygon = Vec::new();
+    for item in text.split(&[';', '\n', '；'][..]) {
+        let item = item.trim();
+        if item.is_empty() {
+            continue;
+        }
+        let values: Vec<f64> = item
+            .split(&[',', '，'][..])
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| CalcError::Invalid(format!("顶点“{}”格式错误，应为x,z", item)))?;
+        match values.as_slice() {
+            [x, z] => polygon.push((*x, *z)),
+            _ => return Err(CalcError::Invalid(format!("顶点“{}”格式错误，应为x,z", item))),
+        }
+    }
+    Ok(polygon)
+}
+
+/// 显示用的矩形栅格，按行存放，成像区域以外为NaN
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raster {
+    pub x: (f64, f64),
+    pub z: (f64, f64),
+    pub columns: usize,
+    pub rows: usize,
+    pub values: Vec<f32>,
+}
+
+impl Raster {
+    /// 相对参考幅度按动态范围换算到0~1，NaN保留
+    pub fn levels(&self, peak: f32, range: f64) -> Vec<f64> {
+        self.values
+            .iter()
+            .map(|&value| {
+                if value.is_nan() {
+                    f64::NAN
+                } else if peak > 0.0 && value > 0.0 {
+                    1.0 + (20.0 * (value as f64 / peak as f64).log10()).max(-range) / range
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+}
+
+//扇形图像显示时的栅格边长（像素）
+const POLAR_RASTER: f64 = 300.0;
+
+/// 把按网格存放的幅度转换为矩形栅格，`active`为false的像素不显示；扇形网格按双线性插值
+pub fn rasterize(grid: &ImagingGrid, amplitude: &[f32], active: &[bool]) -> Raster {
+    let value = |index: usize| if active[index] { amplitude[index] } else { f32::NAN };
+    let (x_min, x_max, z_min, z_max) = grid.bounds();
+    match grid {
+        ImagingGrid::Rectangular(_) | ImagingGrid::Polygon(_, _) => Raster {
+            x: (x_min, x_max),
+            z: (z_min, z_max),
+            columns: grid.columns(),
+            rows: grid.rows(),
+            values: (0..amplitude.len()).map(value).collect(),
+        },
+        ImagingGrid::Polar(polar) => {
+            let step = (x_max - x_min).max(z_max - z_min) / POLAR_RASTER;
+            let columns = ((x_max - x_min) / step).ceil() as usize + 1;
+            let rows = ((z_max - z_min) / step).ceil() as usize + 1;
+            let (angles, radii) = (polar.columns(), polar.rows());
+            let mut values = Vec::with_capacity(columns * rows);
+            for row in 0..rows {
+                for column in 0..columns {
+                    let (dx, dz) = (x_min + column as f64 * step - polar.origin.0, z_min + row as f64 * step - polar.origin.1);
+                    let i = (dx.atan2(dz).to_degrees() - polar.angle_min) / polar.angle_step;
+                    let j = (dx.hypot(dz) - polar.radius_min) / polar.radius_step;
+                    if i < 0.0 || j < 0.0 || i > (angles - 1) as f64 || j > (radii - 1) as f64 {
+                        values.push(f32::NAN);
+                        continue;
+                    }
+                    let (i0, j0) = (i.floor() as usize, j.floor() as usize);
+                    let (i1, j1) = ((i0 + 1).min(angles - 1), (j0 + 1).min(radii - 1));
+                    let (wi, wj) = ((i - i0 as f64) as f32, (j - j0 as f64) as f32);
+                    let at = |a: usize, r: usize| value(r * angles + a);
+                    values.push(
+                        (at(i0, j0) * (1.0 - wi) + at(i1, j0) * wi) * (1.0 - wj)
+                            + (at(i0, j1) * (1.0 - wi) + at(i1, j1) * wi) * wj,
+                    );
+                }
+            }
+            Raster {
+                x: (x_min, x_min + (columns - 1) as f64 * step),
+                z: (z_min, z_min + (rows - 1) as f64 * step),
+                columns,
+                rows,
+                values,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sector() -> ImagingGrid {
+        ImagingGrid::Polar(PolarGrid {
+            origin: (0.0, 0.0),
+            angle_min: -30.0,
+            angle_max: 30.0,
+            angle_step: 1.0,
+            radius_min: 5.0,
+            radius_max: 45.0,
+            radius_step: 1.0,
+        })
+    }
+
+    #[test]
+    fn sector_pixels() {
+        let grid = sector();
+        assert_eq!((grid.columns(), grid.rows()), (61, 41));
+        assert_eq!(grid.pixels().len(), 61 * 41);
+        //第30列为0度，第15行声程20mm
+        let (x, z) = grid.point(15 * 61 + 30);
+        assert!(x.abs() < 1e-12 && (z - 20.0).abs() < 1e-12);
+        let (x_min, x_max, z_min, z_max) = grid.bounds();
+        assert!((x_max - 22.5).abs() < 1e-9 && (x_min + 22.5).abs() < 1e-9);
+        assert!((z_min - 5.0 * 30f64.to_radians().cos()).abs() < 1e-9 && (z_max - 45.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn polygon_mask() {
+        let square = parse_polygon("2.5,2.5；7.5，2.5\n7.5,7.5; 2.5,7.5").unwrap();
+        let grid = ImagingGrid::Polygon(
+            Grid { x_min: 0.0, x_max: 10.0, z_min: 0.0, z_max: 10.0, resolution: 1.0 },
+            square,
+        );
+        let pixels = grid.pixels();
+        assert_eq!(pixels.len(), 25);
+        assert!(pixels.iter().all(|&(_, (x, z))| (3.0..=7.0).contains(&x) && (3.0..=7.0).contains(&z)));
+        let active: Vec<bool> = (0..121).map(|index| pixels.iter().any(|&(i, _)| i == index)).collect();
+        let raster = rasterize(&grid, &[1.0; 121], &active);
+        assert_eq!((raster.columns, raster.rows), (11, 11));
+        assert_eq!(raster.values.iter().filter(|value| !value.is_nan()).count(), 25);
+        assert!(parse_polygon("1,2,3").is_err());
+    }
+
+    #[test]
+    fn sector_raster_inclusion() {
+        let grid = sector();
+        let count = grid.columns() * grid.rows();
+        let raster = rasterize(&grid, &vec![1.0; count], &vec![true; count]);
+        let at = |x: f64, z: f64| {
+            let step = (raster.x.1 - raster.x.0) / (raster.columns - 1) as f64;
+            let column = ((x - raster.x.0) / step).round() as usize;
+            let row = ((z - raster.z.0) / step).round() as usize;
+            raster.values[row * raster.columns + column]
+        };
+        assert!((at(0.0, 20.0) - 1.0).abs() < 1e-6);
+        assert!((at(10.0, 25.0) - 1.0).abs() < 1e-6);
+        //扇形以外：角度超过30度或声程小于5mm
+        assert!(at(-22.0, 5.0).is_nan());
+        assert!(at(0.0, 4.5).is_nan());
+    }
+}
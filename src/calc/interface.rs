@@ -0,0 +1,328 @@
+//! 界面上的声压/能量反射与透射系数
+//!
+//! 垂直入射只与两侧纵波声阻抗Z = ρc有关；斜入射时按界面两侧位移和应力连续求解各波型的振幅，
+//! 液体一侧只有纵波，且固体表面的切应力为0。
+//! 声速单位m/s，密度g/cm³，声阻抗MRayl，入射波为第一介质中的纵波。
+
+use super::signal::Complex;
+use super::{positive, CalcError, Result};
+
+/// 界面一侧的介质，横波声速为0时为液体
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Medium {
+    pub velocity_longitudinal: f64,
+    pub velocity_shear: f64,
+    pub density: f64,
+}
+
+impl Medium {
+    /// 纵波声阻抗，MRayl
+    pub fn impedance(&self) -> f64 {
+        self.density * self.velocity_longitudinal / 1000.0
+    }
+
+    fn solid(&self) -> bool {
+        self.velocity_shear > 0.0
+    }
+
+    fn check(&self) -> Result<()> {
+        positive("纵波声速", self.velocity_longitudinal)?;
+        positive("密度", self.density)?;
+        if self.velocity_shear.is_nan() || self.velocity_shear < 0.0 || self.velocity_shear >= self.velocity_longitudinal {
+            return Err(CalcError::OutOfRange("横波声速"));
+        }
+        Ok(())
+    }
+}
+
+/// 垂直入射的反射与透射系数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalIncidence {
+    pub impedance_incidence: f64,
+    pub impedance_medium: f64,
+    /// 声压反射率 (Z₂ − Z₁)/(Z₂ + Z₁)，负值表示反相
+    pub pressure_reflection: f64,
+    /// 声压透射率 2Z₂/(Z₁ + Z₂)
+    pub pressure_transmission: f64,
+    /// 声强反射率 R²
+    pub energy_reflection: f64,
+    /// 声强透射率 4Z₁Z₂/(Z₁ + Z₂)²
+    pub energy_transmission: f64,
+}
+
+pub fn normal_incidence(incidence: &Medium, medium: &Medium) -> Result<NormalIncidence> {
+    incidence.check()?;
+    medium.check()?;
+    let (z1, z2) = (incidence.impedance(), medium.impedance());
+    let reflection = (z2 - z1) / (z2 + z1);
+    Ok(NormalIncidence {
+        impedance_incidence: z1,
+        impedance_medium: z2,
+        pressure_reflection: reflection,
+        pressure_transmission: 2.0 * z2 / (z1 + z2),
+        energy_reflection: reflection * reflection,
+        energy_transmission: 4.0 * z1 * z2 / ((z1 + z2) * (z1 + z2)),
+    })
+}
+
+/// 某一入射角下各波型的能量系数，没有的波型（液体中的横波、超过临界角）为0，四项之和为1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObliqueCoefficients {
+    pub incidence: f64,
+    pub reflected_longitudinal: f64,
+    pub reflected_shear: f64,
+    pub transmitted_longitudinal: f64,
+    pub transmitted_shear: f64,
+}
+
+//界面上的一列平面波，`downward`为沿+z方向传播（入射波和透射波）
+struct Wave {
+    velocity: f64,
+    medium: Medium,
+    shear: bool,
+    downward: bool,
+}
+
+impl Wave {
+    //竖直方向慢度，超过临界角时为虚数（沿传播方向衰减）
+    fn vertical(&self, p: f64) -> Complex {
+        let q2 = 1.0 / (self.velocity * self.velocity) - p * p;
+        let q = if q2 >= 0.0 { Complex::new(q2.sqrt(), 0.0) } else { Complex::new(0.0, (-q2).sqrt()) };
+        if self.downward {
+            q
+        } else {
+            q.scale(-1.0)
+        }
+    }
+
+    //单位振幅时界面上的位移(ux, uz)和应力(σzz, σxz)，省去共同的iω因子
+    fn fields(&self, p: f64) -> [Complex; 4] {
+        let q = self.vertical(p);
+        let c = self.velocity;
+        let px = Complex::new(p, 0.0);
+        //纵波沿传播方向振动，横波垂直于传播方向
+        let (ux, uz) = if self.shear { (q.scale(c), px.scale(-c)) } else { (px.scale(c), q.scale(c)) };
+        let medium = &self.medium;
+        let mu = medium.density * medium.velocity_shear * medium.velocity_shear;
+        let lambda = medium.density * medium.velocity_longitudinal * medium.velocity_longitudinal - 2.0 * mu;
+        let divergence = px * ux + q * uz;
+        let szz = divergence.scale(lambda) + (q * uz).scale(2.0 * mu);
+        let sxz = (q * ux + px * uz).scale(mu);
+        [ux, uz, szz, sxz]
+    }
+
+    //单位振幅时沿z方向的能流，与入射波的能流相比即能量系数
+    fn flux(&self, p: f64) -> f64 {
+        self.medium.density * self.velocity * self.velocity * self.vertical(p).re.abs()
+    }
+}
+
+//带主元选取的复数高斯消元
+fn solve(mut matrix: Vec<Vec<Complex>>, mut rhs: Vec<Complex>) -> Option<Vec<Complex>> {
+    let n = rhs.len();
+    for column in 0..n {
+        let pivot = (column..n).max_by(|&a, &b| {
+            matrix[a][column]
+                .norm()
+                .partial_cmp(&matrix[b][column].norm())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })?;
+        if matrix[pivot][column].norm() < 1e-12 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+        let pivot_row = matrix[column].clone();
+        for row in column + 1..n {
+            let factor = matrix[row][column] / pivot_row[column];
+            for (value, &pivot_value) in matrix[row].iter_mut().zip(pivot_row.iter()).skip(column) {
+                *value = *value - factor * pivot_value;
+            }
+            rhs[row] = rhs[row] - factor * rhs[column];
+        }
+    }
+    let mut solution = vec![Complex::default(); n];
+    for row in (0..n).rev() {
+        let mut sum = rhs[row];
+        for k in row + 1..n {
+            sum = sum - matrix[row][k] * solution[k];
+        }
+        solution[row] = sum / matrix[row][row];
+    }
+    Some(solution)
+}
+
+/// 第一介质中的纵波以入射角`angle`（度）斜入射时的能量反射与透射系数
+pub fn oblique(incidence: &Medium, medium: &Medium, angle: f64) -> Result<ObliqueCoefficients> {
+    incidence.check()?;
+    medium.check()?;
+    if angle.is_nan() || !(0.0..90.0).contains(&angle) {
+        return Err(CalcError::OutOfRange("入射角"));
+    }
+    //声速换算为mm/µs，各项数量级接近，消元更稳定
+    let scaled = |medium: &Medium| Medium {
+        velocity_longitudinal: medium.velocity_longitudinal / 1000.0,
+        velocity_shear: medium.velocity_shear / 1000.0,
+        density: medium.density,
+    };
+    let (m1, m2) = (scaled(incidence), scaled(medium));
+    let wave = |medium: Medium, shear: bool, downward: bool| Wave {
+        velocity: if shear { medium.velocity_shear } else { medium.velocity_longitudinal },
+        medium,
+        shear,
+        downward,
+    };
+    let p = angle.to_radians().sin() / m1.velocity_longitudinal;
+    let incident = wave(m1, false, true);
+
+    //未知振幅依次为：反射纵波、反射横波、透射纵波、透射横波（液体中没有横波）
+    let mut unknowns = vec![(wave(m1, false, false), false)];
+    if m1.solid() {
+        unknowns.push((wave(m1, true, false), false));
+    }
+    unknowns.push((wave(m2, false, true), true));
+    if m2.solid() {
+        unknowns.push((wave(m2, true, true), true));
+    }
+    //边界条件：法向位移和法向应力总是连续；两侧都是固体时切向位移和切应力连续，
+    //只有一侧是固体时该侧切应力为0
+    let conditions: Vec<(usize, Option<bool>)> = match (m1.solid(), m2.solid()) {
+        (true, true) => vec![(1, None), (2, None), (0, None), (3, None)],
+        (true, false) => vec![(1, None), (2, None), (3, Some(false))],
+        (false, true) => vec![(1, None), (2, None), (3, Some(true))],
+        (false, false) => vec![(1, None), (2, None)],
+    };
+    let incident_fields = incident.fields(p);
+    let fields: Vec<[Complex; 4]> = unknowns.iter().map(|(wave, _)| wave.fields(p)).collect();
+    let mut matrix = Vec::new();
+    let mut rhs = Vec::new();
+    for &(field, side) in conditions.iter() {
+        //连续条件写成 第一介质各波之和 − 第二介质各波之和 = 0；单侧条件只取该侧的波
+        let row = unknowns
+            .iter()
+            .zip(fields.iter())
+            .map(|(&(_, transmitted), values)| match side {
+                None if transmitted => values[field].scale(-1.0),
+                Some(side) if side != transmitted => Complex::default(),
+                _ => values[field],
+            })
+            .collect();
+        matrix.push(row);
+        rhs.push(match side {
+            Some(true) => Complex::default(),
+            _ => incident_fields[field].scale(-1.0),
+        });
+    }
+    let amplitudes = solve(matrix, rhs).ok_or_else(|| CalcError::Invalid(format!("入射角{}度时边界条件方程无解", angle)))?;
+
+    let incident_flux = incident.flux(p);
+    let mut energy = [0.0; 4];
+    for ((wave, transmitted), amplitude) in unknowns.iter().zip(amplitudes.iter()) {
+        let index = match (transmitted, wave.shear) {
+            (false, false) => 0,
+            (false, true) => 1,
+            (true, false) => 2,
+            (true, true) => 3,
+        };
+        let norm = amplitude.norm();
+        energy[index] = wave.flux(p) * norm * norm / incident_flux;
+    }
+    Ok(ObliqueCoefficients {
+        incidence: angle,
+        reflected_longitudinal: energy[0],
+        reflected_shear: energy[1],
+        transmitted_longitudinal: energy[2],
+        transmitted_shear: energy[3],
+    })
+}
+
+/// 入射角0～89度每隔`step`度计算一次
+pub fn sweep(incidence: &Medium, medium: &Medium, step: f64) -> Result<Vec<ObliqueCoefficients>> {
+    positive("角度间隔", step)?;
+    let count = (89.0 / step).floor() as usize;
+    (0..=count).map(|i| oblique(incidence, medium, i as f64 * step)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WATER: Medium = Medium {
+        velocity_longitudinal: 1480.0,
+        velocity_shear: 0.0,
+        density: 1.0,
+    };
+    const ACRYLIC: Medium = Medium {
+        velocity_longitudinal: 2730.0,
+        velocity_shear: 1430.0,
+        density: 1.18,
+    };
+    const STEEL: Medium = Medium {
+        velocity_longitudinal: 5920.0,
+        velocity_shear: 3240.0,
+        density: 7.85,
+    };
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    fn sum(coefficients: &ObliqueCoefficients) -> f64 {
+        coefficients.reflected_longitudinal
+            + coefficients.reflected_shear
+            + coefficients.transmitted_longitudinal
+            + coefficients.transmitted_shear
+    }
+
+    #[test]
+    fn normal_incidence_water_steel() {
+        let output = normal_incidence(&WATER, &STEEL).unwrap();
+        let (z1, z2) = (1.48, 46.472);
+        assert!(close(output.pressure_reflection, (z2 - z1) / (z2 + z1)));
+        assert!(close(output.pressure_transmission, 1.0 + output.pressure_reflection));
+        assert!(close(output.energy_reflection + output.energy_transmission, 1.0));
+        //反方向入射时声压反射率反相
+        let back = normal_incidence(&STEEL, &WATER).unwrap();
+        assert!(close(back.pressure_reflection, -output.pressure_reflection));
+    }
+
+    #[test]
+    fn oblique_at_zero_matches_normal_incidence() {
+        let normal = normal_incidence(&ACRYLIC, &STEEL).unwrap();
+        let oblique = oblique(&ACRYLIC, &STEEL, 0.0).unwrap();
+        assert!((oblique.reflected_longitudinal - normal.energy_reflection).abs() < 1e-9);
+        assert!(oblique.reflected_shear.abs() < 1e-12);
+        assert!(oblique.transmitted_shear.abs() < 1e-12);
+    }
+
+    #[test]
+    fn energy_is_conserved() {
+        let pairs = [(WATER, STEEL), (ACRYLIC, STEEL), (STEEL, WATER), (STEEL, ACRYLIC), (WATER, ACRYLIC)];
+        for (incidence, medium) in pairs.iter() {
+            for row in sweep(incidence, medium, 5.0).unwrap().iter() {
+                assert!((sum(row) - 1.0).abs() < 1e-9, "{:?}", row);
+            }
+        }
+    }
+
+    #[test]
+    fn total_reflection_past_second_critical_angle() {
+        //水到钢第二临界角约27.2度
+        let output = oblique(&WATER, &STEEL, 35.0).unwrap();
+        assert!((output.reflected_longitudinal - 1.0).abs() < 1e-9);
+        assert!(output.transmitted_longitudinal.abs() < 1e-12);
+        assert!(output.transmitted_shear.abs() < 1e-12);
+    }
+
+    #[test]
+    fn error_paths() {
+        let invalid = Medium {
+            velocity_shear: 6000.0,
+            ..STEEL
+        };
+        assert_eq!(normal_incidence(&WATER, &invalid), Err(CalcError::OutOfRange("横波声速")));
+        let empty = Medium { density: 0.0, ..WATER };
+        assert_eq!(normal_incidence(&empty, &STEEL), Err(CalcError::NonPositive("密度")));
+        assert_eq!(oblique(&WATER, &STEEL, 90.0), Err(CalcError::OutOfRange("入射角")));
+    }
+}
@@ -0,0 +1,64 @@
+//! 超声检测计算公式
+//!
+//! 本模块不依赖Yew和浏览器，`Model`只负责收集输入和显示结果，
+//! 所有公式都在这里实现，可以直接在其它程序中复用或用`cargo test`测试。
+
+use std::fmt;
+
+pub mod aim;
+pub mod ascan;
+pub mod dac;
+pub mod delay_law;
+pub mod fmc;
+pub mod fmc_file;
+pub mod grid;
+pub mod grating;
+pub mod interface;
+pub mod multimode;
+pub mod pipe;
+pub mod probe;
+pub mod pwi;
+pub mod ray;
+pub mod reflector;
+pub mod refraction;
+pub mod signal;
+pub mod skip;
+pub mod tfm;
+pub mod tofd;
+pub mod throughput;
+pub mod wedge;
+pub mod weld;
+
+/// 计算参数错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalcError {
+    /// 参数必须大于0
+    NonPositive(&'static str),
+    /// 参数超出允许范围
+    OutOfRange(&'static str),
+    /// 输入内容无效，附带说明
+    Invalid(String),
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CalcError::NonPositive(name) => write!(f, "{}必须大于0，请检查", name),
+            CalcError::OutOfRange(name) => write!(f, "{}超出允许范围，请检查", name),
+            CalcError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for CalcError {}
+
+pub type Result<T> = std::result::Result<T, CalcError>;
+
+/// 检查参数大于0（同时排除NaN）
+pub(crate) fn positive(name: &'static str, value: f64) -> Result<f64> {
+    if value > 0.0 {
+        Ok(value)
+    } else {
+        Err(CalcError::NonPositive(name))
+    }
+}
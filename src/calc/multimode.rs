@@ -0,0 +1,511 @@
+//! 楔块上的多模式TFM（直接、半跳、全跳及波型转换）
+//!
+//! 阵元排列与`delay_law`相同，第一阵元水平位置为`probe_offset`；工件为平板，底面深度为`thickness`。
+//! 波型组合写作“发射声程-接收声程”，每个声程为一个或两个波型字母：
+//! 一个字母表示由阵元直接到像素，两个字母表示先以第一个波型下行到底面，再以第二个波型反射到像素。
+//! 例如TT-T为横波经底面反射到达像素、横波直接返回阵元；TL-T中底面反射时横波转换为纵波。
+
+use std::fmt;
+
+use super::fmc::{add_echo, pulse_sigma, FmcData, Scatterer, MAX_ELEMENTS, MAX_SAMPLES};
+use super::ray::{self, Point};
+use super::grid::ImagingGrid;
+use super::tfm::{delay_and_sum, TfmImage};
+use super::{positive, CalcError, Result};
+
+/// 波型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wave {
+    L,
+    T,
+}
+
+/// 阵元与像素之间的一段声程
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Leg {
+    Direct(Wave),
+    /// 经底面反射，(下行波型, 反射后波型)
+    Skip(Wave, Wave),
+}
+
+/// 成像模式，发射声程和接收声程
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TfmMode {
+    pub transmit: Leg,
+    pub receive: Leg,
+}
+
+/// 裂纹检测常用的成像模式
+pub const COMMON_MODES: [&str; 5] = ["L-L", "T-T", "TT-T", "TT-TT", "TL-T"];
+
+//仿真时考虑的全部声程
+const LEGS: [Leg; 6] = [
+    Leg::Direct(Wave::L),
+    Leg::Direct(Wave::T),
+    Leg::Skip(Wave::L, Wave::L),
+    Leg::Skip(Wave::L, Wave::T),
+    Leg::Skip(Wave::T, Wave::L),
+    Leg::Skip(Wave::T, Wave::T),
+];
+
+impl fmt::Display for Wave {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Wave::L => write!(f, "L"),
+            Wave::T => write!(f, "T"),
+        }
+    }
+}
+
+impl fmt::Display for Leg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Leg::Direct(wave) => write!(f, "{}", wave),
+            Leg::Skip(down, up) => write!(f, "{}{}", down, up),
+        }
+    }
+}
+
+impl fmt::Display for TfmMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.transmit, self.receive)
+    }
+}
+
+fn parse_wave(c: char) -> Option<Wave> {
+    match c {
+        'L' | 'l' => Some(Wave::L),
+        'T' | 't' | 'S' | 's' => Some(Wave::T),
+        _ => None,
+    }
+}
+
+fn parse_leg(text: &str) -> Option<Leg> {
+    let waves: Vec<Wave> = text.trim().chars().map(parse_wave).collect::<Option<_>>()?;
+    match waves.as_slice() {
+        [wave] => Some(Leg::Direct(*wave)),
+        [down, up] => Some(Leg::Skip(*down, *up)),
+        _ => None,
+    }
+}
+
+impl Leg {
+    /// 到达像素时的波型
+    pub fn arriving(&self) -> Wave {
+        match *self {
+            Leg::Direct(wave) => wave,
+            Leg::Skip(_, up) => up,
+        }
+    }
+}
+
+impl TfmMode {
+    /// 解析“TT-T”形式的模式名称，横波也可以写作S
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut legs = text.split('-');
+        match (legs.next().and_then(parse_leg), legs.next().and_then(parse_leg), legs.next()) {
+            (Some(transmit), Some(receive), None) => Ok(TfmMode { transmit, receive }),
+            _ => Err(CalcError::Invalid(format!("成像模式“{}”格式错误，应为L-L、TT-T等", text))),
+        }
+    }
+}
+
+/// 楔块上的线阵探头和平板工件，长度单位mm，声速单位m/s，角度单位度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WedgeArray {
+    pub elements: usize,
+    pub pitch: f64,
+    pub wedge_angle: f64,
+    pub wedge_velocity: f64,
+    pub first_element_height: f64,
+    /// 第一阵元的水平位置
+    pub probe_offset: f64,
+    pub velocity_longitudinal: f64,
+    pub velocity_shear: f64,
+    pub thickness: f64,
+}
+
+impl WedgeArray {
+    pub fn positions(&self) -> Vec<Point> {
+        let wedge = self.wedge_angle.to_radians();
+        (0..self.elements)
+            .map(|i| {
+                let offset = i as f64 * self.pitch;
+                (
+                    self.probe_offset + offset * wedge.cos(),
+                    -(self.first_element_height + offset * wedge.sin()),
+                )
+            })
+            .collect()
+    }
+
+    pub fn velocity(&self, wave: Wave) -> f64 {
+        match wave {
+            Wave::L => self.velocity_longitudinal,
+            Wave::T => self.velocity_shear,
+        }
+    }
+
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.elements == 0 {
+            return Err(CalcError::NonPositive("阵元数"));
+        }
+        if self.elements > MAX_ELEMENTS {
+            return Err(CalcError::OutOfRange("阵元数"));
+        }
+        positive("阵元间距", self.pitch)?;
+        positive("楔块声速", self.wedge_velocity)?;
+        positive("纵波声速", self.velocity_longitudinal)?;
+        positive("横波声速", self.velocity_shear)?;
+        positive("板厚", self.thickness)?;
+        if self.first_element_height < 0.0 {
+            return Err(CalcError::OutOfRange("第一阵元高度"));
+        }
+        if self.wedge_angle.is_nan() || self.wedge_angle < 0.0 || self.wedge_angle >= 90.0 {
+            return Err(CalcError::OutOfRange("楔块角度"));
+        }
+        Ok(())
+    }
+}
+
+//底面声时表的采样间距，mm
+const TABLE_STEP: f64 = 0.1;
+const GOLDEN_ITERATIONS: usize = 40;
+
+/// 从一个阵元经楔块界面到底面各点的声时，用于波型转换的跳跃声程
+struct BackwallTable {
+    x_min: f64,
+    times: Vec<f64>,
+}
+
+impl BackwallTable {
+    fn new(array: &WedgeArray, source: Point, wave: Wave, x_range: (f64, f64)) -> Self {
+        let count = ((x_range.1 - x_range.0) / TABLE_STEP).ceil() as usize + 1;
+        let times = (0..count)
+            .map(|i| {
+                let x = x_range.0 + i as f64 * TABLE_STEP;
+                ray::refracted(source, array.wedge_velocity, (x, array.thickness), array.velocity(wave), 0.0).0
+            })
+            .collect();
+        BackwallTable { x_min: x_range.0, times }
+    }
+
+    fn x_max(&self) -> f64 {
+        self.x_min + (self.times.len() - 1) as f64 * TABLE_STEP
+    }
+
+    fn time(&self, x: f64) -> f64 {
+        let t = ((x - self.x_min) / TABLE_STEP).max(0.0);
+        let i = (t as usize).min(self.times.len().saturating_sub(2));
+        let w = (t - i as f64).min(1.0);
+        self.times[i] * (1.0 - w) + self.times[(i + 1).min(self.times.len() - 1)] * w
+    }
+}
+
+//凸函数在区间内的最小值（黄金分割法），返回(极小点, 最小值)
+fn minimize<F: Fn(f64) -> f64>(f: F, mut low: f64, mut high: f64) -> (f64, f64) {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut a = high - ratio * (high - low);
+    let mut b = low + ratio * (high - low);
+    let (mut fa, mut fb) = (f(a), f(b));
+    for _ in 0..GOLDEN_ITERATIONS {
+        if fa < fb {
+            high = b;
+            b = a;
+            fb = fa;
+            a = high - ratio * (high - low);
+            fa = f(a);
+        } else {
+            low = a;
+            a = b;
+            fa = fb;
+            b = low + ratio * (high - low);
+            fb = f(b);
+        }
+    }
+    if fa < fb {
+        (a, fa)
+    } else {
+        (b, fb)
+    }
+}
+
+/// 一个阵元按某种声程到工件内各点的声时（µs）
+pub(crate) struct LegSolver<'a> {
+    array: &'a WedgeArray,
+    leg: Leg,
+    source: Point,
+    table: Option<BackwallTable>,
+}
+
+impl<'a> LegSolver<'a> {
+    pub(crate) fn new(array: &'a WedgeArray, leg: Leg, source: Point, x_range: (f64, f64)) -> Self {
+        //只有底面波型转换需要预先计算底面声时表，同波型反射按底面镜像处理
+        let table = match leg {
+            Leg::Skip(down, up) if down != up => Some(BackwallTable::new(array, source, down, x_range)),
+            _ => None,
+        };
+        LegSolver { array, leg, source, table }
+    }
+
+    fn time(&self, target: Point) -> f64 {
+        self.arrival(target).0
+    }
+
+    /// 声时及最后一段声线的起点（界面入射点或底面反射点），用于求到达方向
+    pub(crate) fn arrival(&self, target: Point) -> (f64, Point) {
+        let array = self.array;
+        match (self.leg, self.table.as_ref()) {
+            (Leg::Direct(wave), _) => {
+                let (time, x) = ray::refracted(self.source, array.wedge_velocity, target, array.velocity(wave), 0.0);
+                (time, (x, 0.0))
+            }
+            (Leg::Skip(down, _), None) => {
+                let mirror = (target.0, 2.0 * array.thickness - target.1);
+                let (time, x) = ray::refracted(self.source, array.wedge_velocity, mirror, array.velocity(down), 0.0);
+                //镜像声线与底面的交点即反射点
+                let bounce = x + (mirror.0 - x) * array.thickness / mirror.1;
+                (time, (bounce, array.thickness))
+            }
+            (Leg::Skip(_, up), Some(table)) => {
+                //底面反射点位于阵元与目标点的水平位置之间
+                let low = self.source.0.min(target.0).max(table.x_min);
+                let high = self.source.0.max(target.0).min(table.x_max());
+                let time = |x: f64| table.time(x) + ray::direct((x, array.thickness), target, array.velocity(up));
+                let (x, time) = if high > low { minimize(time, low, high) } else { (low, time(low)) };
+                (time, (x, array.thickness))
+            }
+        }
+    }
+}
+
+//覆盖阵列和目标区域的底面水平范围
+pub(crate) fn backwall_range(positions: &[Point], x_min: f64, x_max: f64) -> (f64, f64) {
+    positions
+        .iter()
+        .fold((x_min, x_max), |(low, high), p| (low.min(p.0), high.max(p.0)))
+}
+
+/// 成像区域内位于工件中（不超过板厚）的像素
+pub(crate) fn plate_pixels(grid: &ImagingGrid, thickness: f64) -> Result<Vec<(usize, Point)>> {
+    grid.check()?;
+    let pixels: Vec<(usize, Point)> = grid
+        .pixels()
+        .into_iter()
+        .filter(|&(_, point)| point.1 > 0.0 && point.1 <= thickness)
+        .collect();
+    if pixels.is_empty() {
+        return Err(CalcError::OutOfRange("成像区域（全部在工件以外）"));
+    }
+    Ok(pixels)
+}
+
+/// 每个阵元按指定声程到各像素的声时表，单位为采样点
+fn leg_tables(array: &WedgeArray, leg: Leg, points: &[Point], x_range: (f64, f64), sample_rate: f64) -> Vec<Vec<f32>> {
+    array
+        .positions()
+        .iter()
+        .map(|&source| {
+            let solver = LegSolver::new(array, leg, source, x_range);
+            points.iter().map(|&point| (solver.time(point) * sample_rate) as f32).collect()
+        })
+        .collect()
+}
+
+/// 按指定模式做延时叠加成像，成像区域超过板厚的部分不计算
+pub fn reconstruct(fmc: &FmcData, array: &WedgeArray, mode: TfmMode, grid: &ImagingGrid) -> Result<TfmImage> {
+    array.check()?;
+    if fmc.elements != array.elements || fmc.samples < 2 {
+        return Err(CalcError::Invalid("FMC数据与阵元数不符".into()));
+    }
+    let pixels = plate_pixels(grid, array.thickness)?;
+    let points: Vec<Point> = pixels.iter().map(|&(_, point)| point).collect();
+    let (x_min, x_max, _, _) = grid.bounds();
+    let range = backwall_range(&array.positions(), x_min, x_max);
+
+    let transmit = leg_tables(array, mode.transmit, &points, range, fmc.sample_rate);
+    let values = if mode.transmit == mode.receive {
+        delay_and_sum(fmc, &transmit, &transmit, points.len(), true)
+    } else {
+        let receive = leg_tables(array, mode.receive, &points, range, fmc.sample_rate);
+        delay_and_sum(fmc, &transmit, &receive, points.len(), false)
+    };
+    Ok(TfmImage::from_pixels(grid.clone(), &pixels, &values))
+}
+
+/// 楔块FMC仿真参数，频率MHz，带宽为−6dB相对带宽（%），采样频率MHz
+#[derive(Debug, Clone, PartialEq)]
+pub struct WedgeSimulationInput {
+    pub array: WedgeArray,
+    pub frequency: f64,
+    pub bandwidth: f64,
+    pub sample_rate: f64,
+    pub scatterers: Vec<Scatterer>,
+}
+
+//各声程的相对幅度：横波楔块中横波较强，底面波型转换损失较大
+pub(crate) fn leg_weight(leg: Leg) -> f64 {
+    let wave = |wave: Wave| match wave {
+        Wave::L => 0.5,
+        Wave::T => 1.0,
+    };
+    match leg {
+        Leg::Direct(w) => wave(w),
+        Leg::Skip(down, up) if down == up => 0.8 * wave(down),
+        Leg::Skip(down, up) => 0.3 * wave(down) * wave(up),
+    }
+}
+
+/// 合成楔块上的FMC数据：每个散射体包含全部发射、接收声程组合，另加纵波和横波底面回波
+pub fn simulate(input: &WedgeSimulationInput) -> Result<FmcData> {
+    let array = &input.array;
+    array.check()?;
+    positive("频率", input.frequency)?;
+    positive("带宽", input.bandwidth)?;
+    positive("采样频率", input.sample_rate)?;
+    if input.sample_rate < 2.0 * input.frequency * (1.0 + input.bandwidth / 200.0) {
+        return Err(CalcError::OutOfRange("采样频率（低于奈奎斯特频率）"));
+    }
+    if input.scatterers.iter().any(|s| s.z >= array.thickness) {
+        return Err(CalcError::Invalid("散射体深度必须小于板厚".into()));
+    }
+
+    let positions = array.positions();
+    let (x_min, x_max) = input
+        .scatterers
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), s| (low.min(s.x), high.max(s.x)));
+    let range = backwall_range(&positions, x_min.min(positions[0].0), x_max.max(positions[0].0));
+
+    //times[leg][element][scatterer]
+    let times: Vec<Vec<Vec<f64>>> = LEGS
+        .iter()
+        .map(|&leg| {
+            positions
+                .iter()
+                .map(|&source| {
+                    let solver = LegSolver::new(array, leg, source, range);
+                    input.scatterers.iter().map(|s| solver.time((s.x, s.z))).collect()
+                })
+                .collect()
+        })
+        .collect();
+    //底面回波：发射、接收阵元到底面声时之和的最小值
+    let backwall: Vec<Vec<BackwallTable>> = [Wave::L, Wave::T]
+        .iter()
+        .map(|&wave| {
+            positions
+                .iter()
+                .map(|&source| BackwallTable::new(array, source, wave, range))
+                .collect()
+        })
+        .collect();
+
+    let mut echoes = Vec::new();
+    for tx in 0..array.elements {
+        for rx in 0..array.elements {
+            for (s, scatterer) in input.scatterers.iter().enumerate() {
+                for (a, &transmit) in LEGS.iter().enumerate() {
+                    for (b, &receive) in LEGS.iter().enumerate() {
+                        let (t1, t2) = (times[a][tx][s], times[b][rx][s]);
+                        let amplitude = scatterer.amplitude * leg_weight(transmit) * leg_weight(receive) / (t1 * t2).sqrt();
+                        echoes.push((tx, rx, t1 + t2, amplitude));
+                    }
+                }
+            }
+            for (w, &wave) in [Wave::L, Wave::T].iter().enumerate() {
+                let (first, second) = (&backwall[w][tx], &backwall[w][rx]);
+                let (_, time) = minimize(|x| first.time(x) + second.time(x), range.0, range.1);
+                echoes.push((tx, rx, time, leg_weight(Leg::Direct(wave)) / time));
+            }
+        }
+    }
+
+    let sigma = pulse_sigma(input.frequency, input.bandwidth);
+    let latest = echoes.iter().map(|echo| echo.2).fold(0.0, f64::max);
+    let samples = ((latest + 8.0 * sigma) * input.sample_rate).ceil() as usize;
+    if samples > MAX_SAMPLES {
+        return Err(CalcError::OutOfRange("采样长度（请减小板厚或采样频率）"));
+    }
+    let mut fmc = FmcData::new(array.elements, samples, input.sample_rate, 0.0);
+    for &(tx, rx, time, amplitude) in echoes.iter() {
+        let start = (tx * array.elements + rx) * samples;
+        add_echo(&mut fmc.data[start..start + samples], input.sample_rate, 0.0, time, amplitude, input.frequency, sigma);
+    }
+    let peak = fmc.data.iter().fold(0f32, |peak, v| peak.max(v.abs()));
+    if peak > 0.0 {
+        for v in fmc.data.iter_mut() {
+            *v /= peak;
+        }
+    }
+    Ok(fmc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn array() -> WedgeArray {
+        WedgeArray {
+            elements: 16,
+            pitch: 0.6,
+            wedge_angle: 36.0,
+            wedge_velocity: 2337.0,
+            first_element_height: 6.0,
+            probe_offset: -35.0,
+            velocity_longitudinal: 5920.0,
+            velocity_shear: 3240.0,
+            thickness: 20.0,
+        }
+    }
+
+    #[test]
+    fn mode_names_round_trip() {
+        for name in COMMON_MODES.iter() {
+            assert_eq!(TfmMode::parse(name).unwrap().to_string(), *name);
+        }
+        assert_eq!(TfmMode::parse("ss-t").unwrap().to_string(), "TT-T");
+        assert!(TfmMode::parse("L").is_err());
+        assert!(TfmMode::parse("LLL-T").is_err());
+        assert!(TfmMode::parse("L-L-L").is_err());
+    }
+
+    #[test]
+    fn skip_time_is_mirrored_direct_time() {
+        let array = array();
+        let source = array.positions()[8];
+        let target = (-5.0, 12.0);
+        let range = backwall_range(&array.positions(), -20.0, 10.0);
+        let skip = LegSolver::new(&array, Leg::Skip(Wave::T, Wave::T), source, range);
+        let mirror = (target.0, 2.0 * array.thickness - target.1);
+        let (time, bounce) = skip.arrival(target);
+        assert_eq!(time, ray::refracted(source, 2337.0, mirror, 3240.0, 0.0).0);
+        assert_eq!(bounce.1, array.thickness);
+        //按底面声时表逐点搜索反射点，结果与镜像法一致
+        let table = LegSolver {
+            table: Some(BackwallTable::new(&array, source, Wave::T, range)),
+            ..LegSolver::new(&array, Leg::Skip(Wave::T, Wave::T), source, range)
+        };
+        let (table_time, table_bounce) = table.arrival(target);
+        assert!((table_time - time).abs() < 1e-3);
+        assert!((table_bounce.0 - bounce.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn golden_section_minimum() {
+        let (x, value) = minimize(|x| (x - 1.3).powi(2) + 2.0, -5.0, 5.0);
+        assert!((x - 1.3).abs() < 1e-6);
+        assert!((value - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn mode_conversion_is_faster_than_shear_skip() {
+        let array = array();
+        let source = array.positions()[0];
+        let range = backwall_range(&array.positions(), -20.0, 10.0);
+        let target = (0.0, 10.0);
+        let converted = LegSolver::new(&array, Leg::Skip(Wave::T, Wave::L), source, range).time(target);
+        let shear = LegSolver::new(&array, Leg::Skip(Wave::T, Wave::T), source, range).time(target);
+        assert!(converted < shear);
+    }
+}
@@ -0,0 +1,262 @@
+//! 管道外壁检测的曲面修正
+//!
+//! 周向扫查时声束在管道横截面内传播，内外壁都是同心圆：折射角β是声束与外壁法线的夹角，
+//! 到达内壁时与内壁法线的夹角为 sinθ = R·sinβ / r，比平板模型大，且R·sinβ ≥ r时声束到不了内壁。
+//! 轴向扫查时声束平面内内外壁是平行直线，深度和距离与平板相同，只有楔块接触面受曲率影响。
+//! 长度单位mm，角度单位度。
+
+use super::skip::{locate as flat_locate, SkipInput};
+use super::{positive, CalcError, Result};
+
+/// 周向扫查时声线在内外壁之间最多跟踪的次波数
+pub const MAX_LEGS: usize = 10;
+
+/// 声束相对管道轴线的扫查方向
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanDirection {
+    /// 声束沿轴线
+    Axial,
+    /// 声束沿圆周
+    Circumferential,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipeInput {
+    pub outer_diameter: f64,
+    pub thickness: f64,
+    /// 外壁入射点处的折射角
+    pub angle: f64,
+    pub direction: ScanDirection,
+}
+
+/// 一次波的几何关系
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipeGeometry {
+    /// 声束在内壁的实际入射角，声束到不了内壁时为`None`
+    pub inner_incidence: Option<f64>,
+    /// 能到达内壁的最大折射角
+    pub max_angle: f64,
+    /// 一次波末端（到达内壁，或到不了内壁时回到外壁）的声程
+    pub leg_path: f64,
+    /// 一次波末端在外壁上对应的弧长
+    pub leg_arc: f64,
+    /// 声束最深处离外壁的深度
+    pub max_depth: f64,
+}
+
+/// 由声程换算的反射体位置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipeReflector {
+    /// 所在次波，每经内壁或外壁反射一次加1
+    pub leg: usize,
+    /// 离外壁的径向深度
+    pub depth: f64,
+    /// 入射点到反射体在外壁上投影点的弧长，轴向扫查时为轴向距离
+    pub arc_distance: f64,
+    /// 按平板模型计算的深度和水平距离，用于比较
+    pub flat_depth: f64,
+    pub flat_distance: f64,
+}
+
+fn check(input: &PipeInput) -> Result<(f64, f64, f64)> {
+    let outer = positive("管道外径", input.outer_diameter)? / 2.0;
+    let thickness = positive("壁厚", input.thickness)?;
+    if thickness >= outer {
+        return Err(CalcError::Invalid(format!("壁厚{}mm不小于外半径{}mm", thickness, outer)));
+    }
+    if input.angle.is_nan() || input.angle <= 0.0 || input.angle >= 90.0 {
+        return Err(CalcError::OutOfRange("折射角"));
+    }
+    Ok((outer, outer - thickness, input.angle.to_radians()))
+}
+
+pub fn geometry(input: &PipeInput) -> Result<PipeGeometry> {
+    let (outer, inner, angle) = check(input)?;
+    if input.direction == ScanDirection::Axial {
+        return Ok(PipeGeometry {
+            inner_incidence: Some(input.angle),
+            max_angle: 90.0,
+            leg_path: input.thickness / angle.cos(),
+            leg_arc: input.thickness * angle.tan(),
+            max_depth: input.thickness,
+        });
+    }
+    //声线到圆心的距离
+    let offset = outer * angle.sin();
+    let max_angle = (inner / outer).asin().to_degrees();
+    Ok(if offset < inner {
+        let incidence = (offset / inner).asin();
+        PipeGeometry {
+            inner_incidence: Some(incidence.to_degrees()),
+            max_angle,
+            leg_path: outer * angle.cos() - (inner * inner - offset * offset).sqrt(),
+            //三角形内角和：圆心角 = θ − β
+            leg_arc: outer * (incidence - angle),
+            max_depth: input.thickness,
+        }
+    } else {
+        PipeGeometry {
+            inner_incidence: None,
+            max_angle,
+            leg_path: 2.0 * outer * angle.cos(),
+            leg_arc: outer * (std::f64::consts::PI - 2.0 * angle),
+            max_depth: outer - offset,
+        }
+    })
+}
+
+/// 按声程读数计算反射体位置，周向扫查时沿声线在内外壁之间逐次反射
+pub fn locate(input: &PipeInput, sound_path: f64) -> Result<PipeReflector> {
+    let (outer, inner, angle) = check(input)?;
+    let sound_path = positive("声程", sound_path)?;
+    let flat = flat_locate(
+        &SkipInput {
+            thickness: input.thickness,
+            angle: input.angle,
+        },
+        sound_path,
+    )?;
+    if input.direction == ScanDirection::Axial {
+        return Ok(PipeReflector {
+            leg: flat.leg,
+            depth: flat.depth,
+            arc_distance: flat.surface_distance,
+            flat_depth: flat.depth,
+            flat_distance: flat.surface_distance,
+        });
+    }
+    //圆心为原点，入射点在(0, R)，声线向圆心一侧偏转β
+    let mut position = (0.0, outer);
+    let mut direction = (angle.sin(), -angle.cos());
+    let mut remaining = sound_path;
+    let mut leg = 1;
+    let mut arc = 0.0;
+    loop {
+        let radius2 = position.0 * position.0 + position.1 * position.1;
+        let b = position.0 * direction.0 + position.1 * direction.1;
+        //向内传播且能碰到内壁时先到内壁，否则到外壁
+        let inner_disc = b * b - (radius2 - inner * inner);
+        let step = if b < 0.0 && inner_disc >= 0.0 {
+            -b - inner_disc.sqrt()
+        } else {
+            -b + (b * b - (radius2 - outer * outer)).sqrt()
+        };
+        let length = step.min(remaining);
+        let next = (position.0 + length * direction.0, position.1 + length * direction.1);
+        let cross = position.0 * next.1 - position.1 * next.0;
+        let dot = position.0 * next.0 + position.1 * next.1;
+        arc += cross.atan2(dot).abs() * outer;
+        position = next;
+        if remaining <= step {
+            break;
+        }
+        //角度接近90度时每次波很短，声程读数过大会反射很多次
+        if leg >= MAX_LEGS {
+            return Err(CalcError::OutOfRange("声程"));
+        }
+        remaining -= step;
+        leg += 1;
+        //在反射点按法线镜面反射
+        let radius = position.0.hypot(position.1);
+        let normal = (position.0 / radius, position.1 / radius);
+        let along = direction.0 * normal.0 + direction.1 * normal.1;
+        direction = (direction.0 - 2.0 * along * normal.0, direction.1 - 2.0 * along * normal.1);
+    }
+    Ok(PipeReflector {
+        leg,
+        depth: outer - position.0.hypot(position.1),
+        arc_distance: arc,
+        flat_depth: flat.depth,
+        flat_distance: flat.surface_distance,
+    })
+}
+
+/// 平底楔块在管道外壁上的最大间隙 g = a² / (4D)，`size`为楔块沿曲率方向的尺寸
+///
+/// 周向扫查时取楔块长度，轴向扫查时取楔块宽度。
+pub fn wedge_gap(outer_diameter: f64, size: f64) -> Result<f64> {
+    let diameter = positive("管道外径", outer_diameter)?;
+    let size = positive("楔块尺寸", size)?;
+    Ok(size * size / (4.0 * diameter))
+}
+
+/// 间隙达到`limit`时的管道外径，小于该外径需要用曲面楔块
+pub fn min_flat_diameter(size: f64, limit: f64) -> Result<f64> {
+    let size = positive("楔块尺寸", size)?;
+    let limit = positive("间隙限值", limit)?;
+    Ok(size * size / (4.0 * limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    fn pipe(angle: f64, direction: ScanDirection) -> PipeInput {
+        PipeInput {
+            outer_diameter: 100.0,
+            thickness: 10.0,
+            angle,
+            direction,
+        }
+    }
+
+    #[test]
+    fn inner_wall_incidence() {
+        //R = 50，r = 40：sinθ = 50·sin45 / 40
+        let geometry = geometry(&pipe(45.0, ScanDirection::Circumferential)).unwrap();
+        let offset = 50.0 * 45f64.to_radians().sin();
+        assert!(close(geometry.inner_incidence.unwrap(), (offset / 40.0).asin().to_degrees()));
+        assert!(close(geometry.leg_path, 50.0 * 45f64.to_radians().cos() - (1600.0 - offset * offset).sqrt()));
+        assert!(close(geometry.max_angle, 0.8f64.asin().to_degrees()));
+    }
+
+    #[test]
+    fn locate_reaches_inner_wall_at_leg_end() {
+        let input = pipe(45.0, ScanDirection::Circumferential);
+        let geometry = geometry(&input).unwrap();
+        let reflector = locate(&input, geometry.leg_path).unwrap();
+        assert_eq!(reflector.leg, 1);
+        assert!((reflector.depth - 10.0).abs() < 1e-9);
+        assert!((reflector.arc_distance - geometry.leg_arc).abs() < 1e-9);
+        //二次波回到外壁
+        let back = locate(&input, 2.0 * geometry.leg_path - 1e-9).unwrap();
+        assert_eq!(back.leg, 2);
+        assert!(back.depth.abs() < 1e-6);
+    }
+
+    #[test]
+    fn beam_missing_inner_wall() {
+        let input = pipe(60.0, ScanDirection::Circumferential);
+        let geometry = geometry(&input).unwrap();
+        assert_eq!(geometry.inner_incidence, None);
+        assert!(close(geometry.max_depth, 50.0 - 50.0 * 60f64.to_radians().sin()));
+        let deepest = locate(&input, geometry.leg_path / 2.0).unwrap();
+        assert!((deepest.depth - geometry.max_depth).abs() < 1e-9);
+    }
+
+    #[test]
+    fn axial_scan_is_flat() {
+        let input = pipe(45.0, ScanDirection::Axial);
+        let reflector = locate(&input, 10.0 * 2f64.sqrt()).unwrap();
+        assert!(close(reflector.depth, reflector.flat_depth));
+        assert!(close(reflector.arc_distance, reflector.flat_distance));
+        assert_eq!(geometry(&input).unwrap().inner_incidence, Some(45.0));
+    }
+
+    #[test]
+    fn long_sound_path_is_rejected() {
+        let input = pipe(89.0, ScanDirection::Circumferential);
+        assert_eq!(locate(&input, 10000.0), Err(CalcError::OutOfRange("声程")));
+    }
+
+    #[test]
+    fn wedge_gap_limit() {
+        assert!(close(wedge_gap(100.0, 30.0).unwrap(), 2.25));
+        assert!(close(min_flat_diameter(30.0, 0.5).unwrap(), 450.0));
+        assert_eq!(wedge_gap(0.0, 30.0), Err(CalcError::NonPositive("管道外径")));
+    }
+}
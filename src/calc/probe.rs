@@ -0,0 +1,166 @@
+//! 探头波长、阵元间距、近场长度与声束扩散
+
+use super::{positive, CalcError, Result};
+
+/// 探头参数，频率单位MHz，声速单位m/s
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProbeInput {
+    pub frequency: f64,
+    pub velocity: f64,
+}
+
+/// 计算结果，单位mm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProbeOutput {
+    pub lambda: f64,
+    /// 避免栅瓣的最小阵元间距（λ/2）
+    pub pitch: f64,
+}
+
+/// 波长 λ = v/f，声速m/s，频率MHz，结果mm
+pub fn wavelength(velocity: f64, frequency: f64) -> Result<f64> {
+    let velocity = positive("声速", velocity)?;
+    let frequency = positive("频率", frequency)?;
+    Ok(velocity / 1000.0 / frequency)
+}
+
+pub fn lambda_pitch(input: &ProbeInput) -> Result<ProbeOutput> {
+    let lambda = wavelength(input.velocity, input.frequency)?;
+    Ok(ProbeOutput {
+        lambda,
+        pitch: lambda / 2.0,
+    })
+}
+
+/// 声束参数，`element_size`为晶片（孔径）尺寸D，单位mm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BeamInput {
+    pub frequency: f64,
+    pub velocity: f64,
+    pub element_size: f64,
+    /// 聚焦深度，`None`时不计算聚焦
+    pub focal_depth: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BeamOutput {
+    /// 近场长度 N = D²f/4v，单位mm
+    pub near_field: f64,
+    /// −6dB扩散半角（度），孔径小于0.51λ时无定义
+    pub spread_6db: Option<f64>,
+    /// −20dB扩散半角（度），孔径小于0.87λ时无定义
+    pub spread_20db: Option<f64>,
+    pub focus: Option<FocusOutput>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FocusOutput {
+    /// 归一化焦距 S = F/N
+    pub normalized: f64,
+    /// 焦点处−6dB声束宽度，单位mm
+    pub spot_width: f64,
+    /// −6dB焦区长度（景深），单位mm
+    pub depth_of_field: f64,
+}
+
+/// 圆晶片扩散角系数 sinθ = kλ/D
+const SPREAD_6DB: f64 = 0.51;
+pub(crate) const SPREAD_12DB: f64 = 0.70;
+const SPREAD_20DB: f64 = 0.87;
+
+pub(crate) fn spread(k: f64, lambda: f64, element_size: f64) -> Option<f64> {
+    let sin = k * lambda / element_size;
+    if sin > 1.0 {
+        None
+    } else {
+        Some(sin.asin().to_degrees())
+    }
+}
+
+pub fn beam(input: &BeamInput) -> Result<BeamOutput> {
+    let lambda = wavelength(input.velocity, input.frequency)?;
+    let element_size = positive("晶片尺寸", input.element_size)?;
+    let near_field = element_size * element_size / (4.0 * lambda);
+
+    let focus = match input.focal_depth {
+        Some(focal_depth) => {
+            let focal_depth = positive("聚焦深度", focal_depth)?;
+            //近场以外无法聚焦
+            if focal_depth >= near_field {
+                return Err(CalcError::OutOfRange("聚焦深度（须小于近场长度）"));
+            }
+            let normalized = focal_depth / near_field;
+            Some(FocusOutput {
+                normalized,
+                spot_width: 1.02 * focal_depth * lambda / element_size,
+                depth_of_field: near_field * normalized * normalized * 2.0 / (1.0 + 0.5 * normalized),
+            })
+        }
+        None => None,
+    };
+
+    Ok(BeamOutput {
+        near_field,
+        spread_6db: spread(SPREAD_6DB, lambda, element_size),
+        spread_20db: spread(SPREAD_20DB, lambda, element_size),
+        focus,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn wavelength_in_steel() {
+        assert!(close(wavelength(5920.0, 5.0).unwrap(), 1.184));
+    }
+
+    #[test]
+    fn pitch_is_half_wavelength() {
+        let output = lambda_pitch(&ProbeInput {
+            frequency: 5.0,
+            velocity: 5920.0,
+        })
+        .unwrap();
+        assert!(close(output.lambda, 1.184));
+        assert!(close(output.pitch, output.lambda / 2.0));
+    }
+
+    #[test]
+    fn rejects_non_positive_input() {
+        assert_eq!(wavelength(0.0, 5.0), Err(CalcError::NonPositive("声速")));
+        assert_eq!(wavelength(5920.0, -1.0), Err(CalcError::NonPositive("频率")));
+        assert_eq!(wavelength(f64::NAN, 5.0), Err(CalcError::NonPositive("声速")));
+    }
+
+    #[test]
+    fn near_field_and_spread() {
+        //10mm晶片，λ = 1.184mm：N = D²/4λ，sinθ = 0.51λ/D
+        let output = beam(&BeamInput {
+            frequency: 5.0,
+            velocity: 5920.0,
+            element_size: 10.0,
+            focal_depth: None,
+        })
+        .unwrap();
+        assert!(close(output.near_field, 100.0 / (4.0 * 1.184)));
+        assert!(close(output.spread_6db.unwrap(), (0.51 * 1.184 / 10.0f64).asin().to_degrees()));
+        assert!(output.focus.is_none());
+    }
+
+    #[test]
+    fn focus_beyond_near_field() {
+        let input = BeamInput {
+            frequency: 5.0,
+            velocity: 5920.0,
+            element_size: 10.0,
+            focal_depth: Some(50.0),
+        };
+        assert_eq!(beam(&input), Err(CalcError::OutOfRange("聚焦深度（须小于近场长度）")));
+    }
+}
@@ -0,0 +1,200 @@
+//! 平面波成像（PWI）
+//!
+//! 各阵元按偏转角依次延时激发形成平面波，全部阵元接收；每个角度单独做延时叠加，
+//! 再把各角度的解析信号图像直接相加（相干复合）。
+//! 仿真数据由FMC数据按发射延时线性叠加得到，因此与FMC-TFM对比时散射体完全相同。
+
+use super::fmc::{element_positions, FmcData};
+use super::signal::analytic;
+use super::ray::Point;
+use super::tfm::{accumulate, delay_table, TfmImage, TfmInput};
+use super::{positive, CalcError, Result};
+
+/// 平面波角度数上限
+pub const MAX_ANGLES: usize = 64;
+
+/// PWI数据，按[角度][接收阵元][采样点]顺序存放
+#[derive(Debug, Clone, PartialEq)]
+pub struct PwiData {
+    /// 平面波角度，度
+    pub angles: Vec<f64>,
+    pub elements: usize,
+    pub samples: usize,
+    /// 采样频率，MHz
+    pub sample_rate: f64,
+    /// 第一个采样点对应的时间，µs
+    pub time_offset: f64,
+    pub data: Vec<f32>,
+}
+
+impl PwiData {
+    pub fn ascan(&self, angle: usize, rx: usize) -> &[f32] {
+        let start = (angle * self.elements + rx) * self.samples;
+        &self.data[start..start + self.samples]
+    }
+}
+
+/// 由起始角、终止角和步进生成角度列表，起始角等于终止角时只有一个角度
+pub fn angles(start: f64, end: f64, step: f64) -> Result<Vec<f64>> {
+    if start.abs() >= 90.0 || end.abs() >= 90.0 || start > end {
+        return Err(CalcError::OutOfRange("平面波角度"));
+    }
+    if start == end {
+        return Ok(vec![start]);
+    }
+    positive("角度步进", step)?;
+    let count = ((end - start) / step + 1e-9).floor() as usize + 1;
+    if count > MAX_ANGLES {
+        return Err(CalcError::OutOfRange("平面波角度数"));
+    }
+    Ok((0..count).map(|i| start + i as f64 * step).collect())
+}
+
+//最先激发阵元的x·sinθ，作为发射时间零点
+fn first_firing(positions: &[f64], sin: f64) -> f64 {
+    positions.iter().map(|x| x * sin).fold(f64::INFINITY, f64::min)
+}
+
+/// 各阵元的发射延时（µs），最先激发的阵元为0
+pub fn transmit_delays(positions: &[f64], angle: f64, velocity: f64) -> Vec<f64> {
+    let sin = angle.to_radians().sin();
+    let first = first_firing(positions, sin);
+    positions.iter().map(|x| (x * sin - first) / (velocity / 1000.0)).collect()
+}
+
+/// 按平面波发射延时叠加FMC数据，得到每个角度的全阵元接收信号
+pub fn synthesize(fmc: &FmcData, pitch: f64, velocity: f64, angles: &[f64]) -> Result<PwiData> {
+    positive("阵元间距", pitch)?;
+    positive("声速", velocity)?;
+    if angles.is_empty() {
+        return Err(CalcError::Invalid("请至少输入一个平面波角度".into()));
+    }
+    let positions = element_positions(fmc.elements, pitch);
+    let delays: Vec<Vec<f64>> = angles
+        .iter()
+        .map(|&angle| transmit_delays(&positions, angle, velocity))
+        .collect();
+    //延时后的信号整体后移，加长采样以免截断
+    let longest = delays.iter().flatten().cloned().fold(0.0, f64::max);
+    let samples = fmc.samples + (longest * fmc.sample_rate).ceil() as usize;
+
+    let mut data = vec![0f32; angles.len() * fmc.elements * samples];
+    for (a, delays) in delays.iter().enumerate() {
+        for rx in 0..fmc.elements {
+            let start = (a * fmc.elements + rx) * samples;
+            let trace = &mut data[start..start + samples];
+            for (tx, delay) in delays.iter().enumerate() {
+                let shift = delay * fmc.sample_rate;
+                let whole = shift.floor() as usize;
+                let w = (shift - shift.floor()) as f32;
+                //分数采样点延时用线性插值
+                let source = fmc.ascan(tx, rx);
+                for (i, &value) in source.iter().enumerate() {
+                    trace[i + whole] += value * (1.0 - w);
+                    if i + whole + 1 < samples {
+                        trace[i + whole + 1] += value * w;
+                    }
+                }
+            }
+        }
+    }
+    Ok(PwiData {
+        angles: angles.to_vec(),
+        elements: fmc.elements,
+        samples,
+        sample_rate: fmc.sample_rate,
+        time_offset: fmc.time_offset,
+        data,
+    })
+}
+
+/// 平面波到达像素的时间，单位为采样点，与`transmit_delays`的零点一致
+fn plane_wave_table(positions: &[f64], angle: f64, points: &[Point], velocity: f64, sample_rate: f64) -> Vec<f32> {
+    let (sin, cos) = (angle.to_radians().sin(), angle.to_radians().cos());
+    let first = first_firing(positions, sin);
+    let scale = sample_rate / (velocity / 1000.0);
+    points
+        .iter()
+        .map(|&(x, z)| ((x * sin + z * cos - first) * scale) as f32)
+        .collect()
+}
+
+/// 各角度分别延时叠加后相干复合
+pub fn reconstruct(pwi: &PwiData, input: &TfmInput) -> Result<TfmImage> {
+    positive("阵元间距", input.pitch)?;
+    positive("声速", input.velocity)?;
+    input.grid.check()?;
+    if pwi.elements == 0 || pwi.samples < 2 || pwi.angles.is_empty() {
+        return Err(CalcError::Invalid("PWI数据为空".into()));
+    }
+
+    let positions = element_positions(pwi.elements, input.pitch);
+    let pixels = input.grid.pixels();
+    let points: Vec<Point> = pixels.iter().map(|&(_, point)| point).collect();
+    let receive = delay_table(&positions, &points, input.velocity, pwi.sample_rate);
+    let offset = (pwi.time_offset * pwi.sample_rate) as f32;
+
+    let mut sum = vec![(0f32, 0f32); points.len()];
+    let mut trace = Vec::with_capacity(pwi.samples);
+    for (a, &angle) in pwi.angles.iter().enumerate() {
+        let transmit = plane_wave_table(&positions, angle, &points, input.velocity, pwi.sample_rate);
+        for (rx, receive) in receive.iter().enumerate() {
+            trace.clear();
+            trace.extend(analytic(pwi.ascan(a, rx)).iter().map(|v| (v.re as f32, v.im as f32)));
+            accumulate(&mut sum, &trace, &transmit, receive, offset);
+        }
+    }
+    let values: Vec<f32> = sum.iter().map(|value| value.0.hypot(value.1)).collect();
+    Ok(TfmImage::from_pixels(input.grid.clone(), &pixels, &values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fmc::{simulate, Scatterer, SimulationInput};
+    use super::super::grid::{Grid, ImagingGrid};
+    use super::super::tfm;
+    use super::*;
+
+    #[test]
+    fn zero_degree_plane_wave_has_no_delay() {
+        let positions = element_positions(16, 0.6);
+        assert!(transmit_delays(&positions, 0.0, 5920.0).iter().all(|&delay| delay == 0.0));
+        //偏转时相邻阵元延时差p·sinθ/v
+        let delays = transmit_delays(&positions, 20.0, 5920.0);
+        let step = 0.6 * 20f64.to_radians().sin() / 5.92;
+        assert!(delays[0].abs() < 1e-12);
+        assert!(delays.windows(2).all(|pair| (pair[1] - pair[0] - step).abs() < 1e-12));
+    }
+
+    #[test]
+    fn compound_peak_matches_tfm() {
+        let fmc = simulate(&SimulationInput {
+            elements: 16,
+            pitch: 0.6,
+            frequency: 5.0,
+            velocity: 5920.0,
+            bandwidth: 60.0,
+            sample_rate: 100.0,
+            scatterers: vec![Scatterer { x: -1.5, z: 12.0, amplitude: 1.0 }],
+            backwall: None,
+        })
+        .unwrap();
+        let input = TfmInput {
+            pitch: 0.6,
+            velocity: 5920.0,
+            grid: ImagingGrid::Rectangular(Grid { x_min: -5.0, x_max: 5.0, z_min: 8.0, z_max: 16.0, resolution: 0.1 }),
+        };
+        let pwi = synthesize(&fmc, 0.6, 5920.0, &angles(-10.0, 10.0, 5.0).unwrap()).unwrap();
+        let (_, compound) = reconstruct(&pwi, &input).unwrap().peak();
+        let (_, full) = tfm::reconstruct(&fmc, &input).unwrap().peak();
+        assert!((compound.0 - full.0).abs() < 1e-9 && (compound.1 - full.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn angle_list() {
+        assert_eq!(angles(-10.0, 10.0, 5.0), Ok(vec![-10.0, -5.0, 0.0, 5.0, 10.0]));
+        assert_eq!(angles(5.0, 5.0, 0.0), Ok(vec![5.0]));
+        assert_eq!(angles(10.0, -10.0, 5.0), Err(CalcError::OutOfRange("平面波角度")));
+        assert_eq!(angles(-40.0, 40.0, 1.0), Err(CalcError::OutOfRange("平面波角度数")));
+    }
+}
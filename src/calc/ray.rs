@@ -0,0 +1,73 @@
+//! 平界面折射声线（费马原理）
+//!
+//! 坐标约定：x沿工件表面，z向下为正，界面为z = 0，
+//! 楔块中的点z < 0，工件中的点z > 0。
+
+/// 二维点 (x, z)，单位mm
+pub type Point = (f64, f64);
+
+fn distance(a: Point, b: Point) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+const ITERATIONS: usize = 60;
+//入射点位置收敛精度，mm
+const TOLERANCE: f64 = 1e-9;
+
+/// 从`source`经界面z = `interface`到`target`的最短传播时间
+///
+/// 声速单位m/s，返回(时间µs, 界面入射点x)。时间对入射点x是凸函数，
+/// 其导数单调，求导数零点即满足斯涅尔定律的入射点：
+/// 用牛顿法迭代，步长越出当前区间时改用二分法。
+pub fn refracted(source: Point, velocity_source: f64, target: Point, velocity_target: f64, interface: f64) -> (f64, f64) {
+    //换算为mm/µs
+    let v1 = velocity_source / 1000.0;
+    let v2 = velocity_target / 1000.0;
+    let time = |x: f64| distance(source, (x, interface)) / v1 + distance((x, interface), target) / v2;
+    let (z1, z2) = ((source.1 - interface).powi(2), (target.1 - interface).powi(2));
+    let mut low = source.0.min(target.0);
+    let mut high = source.0.max(target.0);
+    let mut x = 0.5 * (low + high);
+    for _ in 0..ITERATIONS {
+        let r1 = distance(source, (x, interface)).max(1e-12);
+        let r2 = distance((x, interface), target).max(1e-12);
+        let slope = (x - source.0) / (v1 * r1) - (target.0 - x) / (v2 * r2);
+        if slope > 0.0 {
+            high = x;
+        } else {
+            low = x;
+        }
+        let curvature = z1 / (v1 * r1.powi(3)) + z2 / (v2 * r2.powi(3));
+        let next = x - slope / curvature;
+        let next = if next > low && next < high { next } else { 0.5 * (low + high) };
+        if (next - x).abs() < TOLERANCE || high - low < TOLERANCE {
+            x = next;
+            break;
+        }
+        x = next;
+    }
+    (time(x), x)
+}
+
+/// 同一介质中的直线传播时间，声速m/s，返回µs
+pub fn direct(source: Point, target: Point, velocity: f64) -> f64 {
+    distance(source, target) / (velocity / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refracted_obeys_snell() {
+        let (source, target) = ((-10.0, -5.0), (8.0, 15.0));
+        let (time, x) = refracted(source, 2337.0, target, 3240.0, 0.0);
+        let sin1 = (x - source.0) / distance(source, (x, 0.0));
+        let sin2 = (target.0 - x) / distance((x, 0.0), target);
+        assert!((sin1 / 2337.0 - sin2 / 3240.0).abs() < 1e-12);
+        assert!((time - direct(source, (x, 0.0), 2337.0) - direct((x, 0.0), target, 3240.0)).abs() < 1e-12);
+        //同一声速时走直线
+        let (_, x) = refracted(source, 5920.0, target, 5920.0, 0.0);
+        assert!((x - (-10.0 + 18.0 * 5.0 / 20.0)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,183 @@
+//! 校准反射体之间的理论增益差（远场公式）与传输修正
+//!
+//! 声压都以探头声压P₀与晶片面积F_s的乘积为单位，距离x单位mm，适用于x ≥ 3N的远场。
+//! - 大平底：P_B = P₀F_s / (2λx)
+//! - 平底孔：P_f = P₀F_s·πD²/4 / (λ²x²)
+//! - 长横孔：P_f = P₀F_s / (2λx) · √(D/(2x))
+
+use super::{positive, CalcError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReflectorKind {
+    /// 大平底
+    Backwall,
+    /// 平底孔
+    Fbh,
+    /// 长横孔
+    Sdh,
+}
+
+/// 反射体，直径和声程单位mm，大平底的直径不使用
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reflector {
+    pub kind: ReflectorKind,
+    pub diameter: f64,
+    pub distance: f64,
+}
+
+impl Reflector {
+    pub fn name(&self) -> String {
+        match self.kind {
+            ReflectorKind::Backwall => "大平底".into(),
+            ReflectorKind::Fbh => format!("Φ{}平底孔", self.diameter),
+            ReflectorKind::Sdh => format!("Φ{}长横孔", self.diameter),
+        }
+    }
+
+    /// 回波声压公式（Markdown）
+    pub fn formula(&self) -> &'static str {
+        match self.kind {
+            ReflectorKind::Backwall => "P = P₀F_s / (2λx)",
+            ReflectorKind::Fbh => "P = P₀F_s · πD² / (4λ²x²)",
+            ReflectorKind::Sdh => "P = P₀F_s / (2λx) · √(D / 2x)",
+        }
+    }
+}
+
+/// 回波声压与P₀F_s之比
+pub fn pressure(reflector: &Reflector, lambda: f64) -> Result<f64> {
+    let x = positive("声程", reflector.distance)?;
+    if reflector.kind != ReflectorKind::Backwall {
+        positive("反射体直径", reflector.diameter)?;
+    }
+    let d = reflector.diameter;
+    Ok(match reflector.kind {
+        ReflectorKind::Backwall => 1.0 / (2.0 * lambda * x),
+        ReflectorKind::Fbh => std::f64::consts::PI * d * d / (4.0 * lambda * lambda * x * x),
+        ReflectorKind::Sdh => 1.0 / (2.0 * lambda * x) * (d / (2.0 * x)).sqrt(),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GainInput {
+    /// 波长，mm
+    pub lambda: f64,
+    /// 参考反射体（已调到基准波高）
+    pub reference: Reflector,
+    pub target: Reflector,
+    /// 材料衰减系数，dB/mm（单程），0为不计衰减
+    pub attenuation: f64,
+    /// 近场长度，mm，用于检查远场条件，0为不检查
+    pub near_field: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GainOutput {
+    /// 20lg(P_参考 / P_目标)
+    pub difference: f64,
+    /// 往返衰减差2α(x_目标 − x_参考)
+    pub attenuation: f64,
+    /// 目标反射体达到基准波高需要增加的增益
+    pub total: f64,
+    /// 声程小于3N的反射体
+    pub near_field_warning: bool,
+}
+
+pub fn gain_difference(input: &GainInput) -> Result<GainOutput> {
+    let lambda = positive("波长", input.lambda)?;
+    if input.attenuation.is_nan() || input.attenuation < 0.0 {
+        return Err(CalcError::OutOfRange("衰减系数"));
+    }
+    let difference = 20.0 * (pressure(&input.reference, lambda)? / pressure(&input.target, lambda)?).log10();
+    let attenuation = 2.0 * input.attenuation * (input.target.distance - input.reference.distance);
+    let near_field_warning = input.near_field > 0.0
+        && (input.reference.distance < 3.0 * input.near_field || input.target.distance < 3.0 * input.near_field);
+    Ok(GainOutput {
+        difference,
+        attenuation,
+        total: difference + attenuation,
+        near_field_warning,
+    })
+}
+
+/// 传输修正：试块和工件上分别测得大平底回波达到基准波高所需的增益（dB）及其声程（mm）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransferInput {
+    pub block_gain: f64,
+    pub block_distance: f64,
+    pub part_gain: f64,
+    pub part_distance: f64,
+}
+
+/// 传输损失 = (G_工件 − G_试块) − 20lg(x_工件 / x_试块)，即扣除大平底声程差以后的增益差
+pub fn transfer_correction(input: &TransferInput) -> Result<f64> {
+    let block = positive("试块声程", input.block_distance)?;
+    let part = positive("工件声程", input.part_distance)?;
+    Ok(input.part_gain - input.block_gain - 20.0 * (part / block).log10())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reflector(kind: ReflectorKind, diameter: f64, distance: f64) -> Reflector {
+        Reflector { kind, diameter, distance }
+    }
+
+    fn gain(reference: Reflector, target: Reflector) -> GainOutput {
+        gain_difference(&GainInput {
+            lambda: 1.184,
+            reference,
+            target,
+            attenuation: 0.0,
+            near_field: 0.0,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn rule_of_thumb_gains() {
+        //平底孔直径减半+12dB，大平底声程加倍+6dB，长横孔声程加倍+9dB
+        let fbh = gain(reflector(ReflectorKind::Fbh, 4.0, 100.0), reflector(ReflectorKind::Fbh, 2.0, 100.0));
+        assert!((fbh.difference - 40.0 * 2f64.log10()).abs() < 1e-9);
+        let backwall = gain(reflector(ReflectorKind::Backwall, 0.0, 50.0), reflector(ReflectorKind::Backwall, 0.0, 100.0));
+        assert!((backwall.difference - 20.0 * 2f64.log10()).abs() < 1e-9);
+        let sdh = gain(reflector(ReflectorKind::Sdh, 2.0, 50.0), reflector(ReflectorKind::Sdh, 2.0, 100.0));
+        assert!((sdh.difference - 30.0 * 2f64.log10()).abs() < 1e-9);
+        assert!((sdh.total - sdh.difference).abs() < 1e-12);
+    }
+
+    #[test]
+    fn near_field_boundary() {
+        let check = |distance: f64| {
+            gain_difference(&GainInput {
+                lambda: 1.184,
+                reference: reflector(ReflectorKind::Backwall, 0.0, 100.0),
+                target: reflector(ReflectorKind::Fbh, 2.0, distance),
+                attenuation: 0.01,
+                near_field: 20.0,
+            })
+            .unwrap()
+        };
+        assert!(!check(60.0).near_field_warning);
+        assert!(check(59.9).near_field_warning);
+        assert!((check(150.0).attenuation - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn transfer_cancels_distance_term() {
+        //工件声程是试块的两倍，增益多6dB时没有传输损失
+        let loss = transfer_correction(&TransferInput {
+            block_gain: 30.0,
+            block_distance: 50.0,
+            part_gain: 30.0 + 20.0 * 2f64.log10(),
+            part_distance: 100.0,
+        })
+        .unwrap();
+        assert!(loss.abs() < 1e-12);
+        assert_eq!(
+            pressure(&reflector(ReflectorKind::Sdh, 0.0, 50.0), 1.184),
+            Err(CalcError::NonPositive("反射体直径"))
+        );
+    }
+}
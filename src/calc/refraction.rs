@@ -0,0 +1,223 @@
+//! 斯涅尔定律折射角、波型转换与临界角
+
+use super::{positive, CalcError, Result};
+
+/// 按斯涅尔定律计算折射角（度），超过临界角时无透射波，返回`None`
+pub fn snell(incidence: f64, velocity_incidence: f64, velocity_refraction: f64) -> Result<Option<f64>> {
+    if incidence.is_nan() || incidence.abs() >= 90.0 {
+        return Err(CalcError::OutOfRange("入射角"));
+    }
+    let velocity_incidence = positive("入射声速", velocity_incidence)?;
+    let velocity_refraction = positive("折射声速", velocity_refraction)?;
+    let sin = incidence.to_radians().sin() * velocity_refraction / velocity_incidence;
+    if sin.abs() > 1.0 {
+        Ok(None)
+    } else {
+        Ok(Some(sin.asin().to_degrees()))
+    }
+}
+
+/// 临界角（折射角为90度时的入射角），折射声速不大于入射声速时不存在
+pub fn critical_angle(velocity_incidence: f64, velocity_refraction: f64) -> Result<Option<f64>> {
+    let velocity_incidence = positive("入射声速", velocity_incidence)?;
+    let velocity_refraction = positive("折射声速", velocity_refraction)?;
+    if velocity_refraction > velocity_incidence {
+        Ok(Some((velocity_incidence / velocity_refraction).asin().to_degrees()))
+    } else {
+        Ok(None)
+    }
+}
+
+/// 折射角计算参数，声速单位m/s，角度单位度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RefractionInput {
+    pub incidence_min: f64,
+    pub incidence_max: f64,
+    pub velocity_incidence: f64,
+    /// 折射介质纵波声速
+    pub velocity_longitudinal: f64,
+    /// 折射介质横波声速，`None`时只计算纵波
+    pub velocity_shear: Option<f64>,
+    /// 第三层介质（钢）声速，`None`时不计算
+    pub velocity_steel: Option<f64>,
+}
+
+/// 某一入射角下的两种波型折射角
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModeAngles {
+    pub incidence: f64,
+    pub longitudinal: Option<f64>,
+    pub shear: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefractionOutput {
+    pub min: ModeAngles,
+    pub max: ModeAngles,
+    pub critical_first: Option<f64>,
+    pub critical_second: Option<f64>,
+    /// 纵波从折射介质再进入钢中的折射角
+    pub steel_min: Option<f64>,
+    pub steel_max: Option<f64>,
+    /// 入射角范围内均匀取点的两种波型折射角
+    pub sweep: Vec<ModeAngles>,
+}
+
+/// 材料库声速对应的温度，°C
+pub const REFERENCE_TEMPERATURE: f64 = 20.0;
+
+/// 声速温度系数，m/s/°C，声速随温度近似线性变化
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TemperatureCoefficients {
+    pub incidence: f64,
+    pub longitudinal: f64,
+    pub shear: f64,
+    pub steel: f64,
+}
+
+/// 把参考温度下的声速换算到`temperature`，未输入（为0）的声速不换算
+pub fn compensate(velocity: f64, coefficient: f64, temperature: f64) -> Result<f64> {
+    if velocity <= 0.0 {
+        return Ok(velocity);
+    }
+    positive("温度补偿后的声速", velocity + coefficient * (temperature - REFERENCE_TEMPERATURE))
+}
+
+/// 各介质声速都换算到`temperature`以后的折射角计算参数
+pub fn at_temperature(
+    input: &RefractionInput,
+    coefficients: &TemperatureCoefficients,
+    temperature: f64,
+) -> Result<RefractionInput> {
+    if !(-100.0..=500.0).contains(&temperature) {
+        return Err(CalcError::OutOfRange("温度"));
+    }
+    let optional = |velocity: Option<f64>, coefficient: f64| -> Result<Option<f64>> {
+        velocity.map(|velocity| compensate(velocity, coefficient, temperature)).transpose()
+    };
+    Ok(RefractionInput {
+        velocity_incidence: compensate(input.velocity_incidence, coefficients.incidence, temperature)?,
+        velocity_longitudinal: compensate(input.velocity_longitudinal, coefficients.longitudinal, temperature)?,
+        velocity_shear: optional(input.velocity_shear, coefficients.shear)?,
+        velocity_steel: optional(input.velocity_steel, coefficients.steel)?,
+        ..*input
+    })
+}
+
+/// 入射角范围的取点数
+const SWEEP_STEPS: usize = 10;
+
+pub fn mode_angles(input: &RefractionInput, incidence: f64) -> Result<ModeAngles> {
+    Ok(ModeAngles {
+        incidence,
+        longitudinal: snell(incidence, input.velocity_incidence, input.velocity_longitudinal)?,
+        shear: match input.velocity_shear {
+            Some(velocity) => snell(incidence, input.velocity_incidence, velocity)?,
+            None => None,
+        },
+    })
+}
+
+pub fn refraction(input: &RefractionInput) -> Result<RefractionOutput> {
+    let min = mode_angles(input, input.incidence_min)?;
+    let max = mode_angles(input, input.incidence_max)?;
+    let critical_first = critical_angle(input.velocity_incidence, input.velocity_longitudinal)?;
+    let critical_second = match input.velocity_shear {
+        Some(velocity) => critical_angle(input.velocity_incidence, velocity)?,
+        None => None,
+    };
+
+    let steel = |angle: Option<f64>| -> Result<Option<f64>> {
+        match (angle, input.velocity_steel) {
+            (Some(angle), Some(velocity)) => snell(angle, input.velocity_longitudinal, velocity),
+            _ => Ok(None),
+        }
+    };
+    let steel_min = steel(min.longitudinal)?;
+    let steel_max = steel(max.longitudinal)?;
+
+    let bottom = input.incidence_min.min(input.incidence_max);
+    let top = input.incidence_min.max(input.incidence_max);
+    let steps = if top > bottom { SWEEP_STEPS } else { 0 };
+    let mut sweep = Vec::with_capacity(steps + 1);
+    for i in 0..=steps {
+        let incidence = if steps == 0 {
+            bottom
+        } else {
+            bottom + (top - bottom) * i as f64 / steps as f64
+        };
+        sweep.push(mode_angles(input, incidence)?);
+    }
+
+    Ok(RefractionOutput {
+        min,
+        max,
+        critical_first,
+        critical_second,
+        steel_min,
+        steel_max,
+        sweep,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Rexolite楔块到钢
+    const REXOLITE: f64 = 2337.0;
+    const STEEL_L: f64 = 5920.0;
+    const STEEL_S: f64 = 3240.0;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn snell_into_steel() {
+        assert!(close(snell(20.0, REXOLITE, STEEL_L).unwrap().unwrap(), 60.042091733));
+        assert!(close(snell(20.0, REXOLITE, STEEL_S).unwrap().unwrap(), 28.305600310));
+        assert!(close(snell(36.0, REXOLITE, STEEL_S).unwrap().unwrap(), 54.577598722));
+        assert_eq!(snell(0.0, REXOLITE, STEEL_L), Ok(Some(0.0)));
+    }
+
+    #[test]
+    fn critical_angles() {
+        assert!(close(critical_angle(REXOLITE, STEEL_L).unwrap().unwrap(), 23.251225823));
+        assert!(close(critical_angle(REXOLITE, STEEL_S).unwrap().unwrap(), 46.161609023));
+        //折射声速更低时不存在临界角
+        assert_eq!(critical_angle(STEEL_L, REXOLITE), Ok(None));
+    }
+
+    #[test]
+    fn no_refraction_past_critical_angle() {
+        assert_eq!(snell(30.0, REXOLITE, STEEL_L), Ok(None));
+        assert_eq!(snell(50.0, REXOLITE, STEEL_S), Ok(None));
+    }
+
+    #[test]
+    fn error_paths() {
+        assert_eq!(snell(90.0, REXOLITE, STEEL_L), Err(CalcError::OutOfRange("入射角")));
+        assert_eq!(snell(f64::NAN, REXOLITE, STEEL_L), Err(CalcError::OutOfRange("入射角")));
+        assert_eq!(snell(20.0, 0.0, STEEL_L), Err(CalcError::NonPositive("入射声速")));
+        assert_eq!(snell(20.0, REXOLITE, -1.0), Err(CalcError::NonPositive("折射声速")));
+        assert_eq!(critical_angle(REXOLITE, 0.0), Err(CalcError::NonPositive("折射声速")));
+    }
+
+    #[test]
+    fn refraction_range() {
+        let input = RefractionInput {
+            incidence_min: 30.0,
+            incidence_max: 40.0,
+            velocity_incidence: REXOLITE,
+            velocity_longitudinal: STEEL_L,
+            velocity_shear: Some(STEEL_S),
+            velocity_steel: None,
+        };
+        let output = refraction(&input).unwrap();
+        assert_eq!(output.min.longitudinal, None);
+        assert!(close(output.max.shear.unwrap(), snell(40.0, REXOLITE, STEEL_S).unwrap().unwrap()));
+        assert_eq!(output.sweep.len(), SWEEP_STEPS + 1);
+        assert!(close(output.critical_second.unwrap(), 46.161609023));
+    }
+}
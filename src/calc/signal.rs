@@ -0,0 +1,186 @@
+//! 信号处理：FFT和希尔伯特变换
+
+use std::f64::consts::PI;
+use std::ops::{Add, Div, Mul, Sub};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    pub fn norm(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn scale(self, k: f64) -> Self {
+        Complex::new(self.re * k, self.im * k)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+    fn div(self, other: Complex) -> Complex {
+        let denominator = other.re * other.re + other.im * other.im;
+        Complex::new(
+            (self.re * other.re + self.im * other.im) / denominator,
+            (self.im * other.re - self.re * other.im) / denominator,
+        )
+    }
+}
+
+/// 原位基2 FFT，长度必须是2的幂；逆变换已除以N
+pub fn fft(data: &mut [Complex], inverse: bool) {
+    let n = data.len();
+    assert!(n.is_power_of_two(), "FFT长度必须是2的幂");
+    //位反转重排
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let twiddles: Vec<Complex> = (0..n / 2)
+        .map(|k| {
+            let angle = sign * 2.0 * PI * k as f64 / n as f64;
+            Complex::new(angle.cos(), angle.sin())
+        })
+        .collect();
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let stride = n / len;
+        for start in (0..n).step_by(len) {
+            for k in 0..half {
+                let a = data[start + k];
+                let b = data[start + k + half] * twiddles[k * stride];
+                data[start + k] = a + b;
+                data[start + k + half] = a - b;
+            }
+        }
+        len <<= 1;
+    }
+    if inverse {
+        for value in data.iter_mut() {
+            *value = value.scale(1.0 / n as f64);
+        }
+    }
+}
+
+/// 解析信号（实部为原信号，虚部为希尔伯特变换），长度与输入相同
+pub fn analytic(signal: &[f32]) -> Vec<Complex> {
+    if signal.is_empty() {
+        return Vec::new();
+    }
+    let n = signal.len().next_power_of_two();
+    let mut spectrum = vec![Complex::default(); n];
+    for (value, &sample) in spectrum.iter_mut().zip(signal.iter()) {
+        value.re = sample as f64;
+    }
+    fft(&mut spectrum, false);
+    //正频率加倍，负频率置零
+    for value in spectrum.iter_mut().take(n / 2).skip(1) {
+        *value = value.scale(2.0);
+    }
+    for value in spectrum.iter_mut().skip(n / 2 + 1) {
+        *value = Complex::default();
+    }
+    fft(&mut spectrum, true);
+    spectrum.truncate(signal.len());
+    spectrum
+}
+
+/// 希尔伯特包络
+pub fn envelope(signal: &[f32]) -> Vec<f64> {
+    analytic(signal).iter().map(|value| value.norm()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fmc::{pulse, pulse_sigma};
+    use super::*;
+
+    //直接按定义计算的DFT
+    fn dft(data: &[Complex]) -> Vec<Complex> {
+        let n = data.len();
+        (0..n)
+            .map(|k| {
+                data.iter().enumerate().fold(Complex::default(), |sum, (i, &value)| {
+                    let angle = -2.0 * PI * (k * i) as f64 / n as f64;
+                    sum + value * Complex::new(angle.cos(), angle.sin())
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fft_matches_dft() {
+        let data: Vec<Complex> = (0..16)
+            .map(|i| Complex::new((i as f64 * 0.7).sin() + 0.3, (i as f64 * 1.3).cos()))
+            .collect();
+        let mut spectrum = data.clone();
+        fft(&mut spectrum, false);
+        for (a, b) in spectrum.iter().zip(dft(&data).iter()) {
+            assert!((*a - *b).norm() < 1e-9);
+        }
+        //逆变换还原
+        fft(&mut spectrum, true);
+        for (a, b) in spectrum.iter().zip(data.iter()) {
+            assert!((*a - *b).norm() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn envelope_of_gaussian_pulse() {
+        //5MHz、60%带宽的脉冲，采样100MHz，中心在5µs
+        let (frequency, rate, centre) = (5.0, 100.0, 5.0);
+        let sigma = pulse_sigma(frequency, 60.0);
+        let signal: Vec<f32> = (0..1000)
+            .map(|i| pulse(i as f64 / rate - centre, frequency, sigma) as f32)
+            .collect();
+        let envelope = envelope(&signal);
+        assert_eq!(envelope.len(), signal.len());
+        for (i, &value) in envelope.iter().enumerate().skip(400).take(200) {
+            let t = i as f64 / rate - centre;
+            let expected = (-t * t / (2.0 * sigma * sigma)).exp();
+            assert!((value - expected).abs() < 1e-3, "t = {}µs: {} vs {}", t, value, expected);
+        }
+        assert!(analytic(&[]).is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+//! 焊缝检测跳距与声程
+
+use super::{positive, CalcError, Result};
+
+/// 板厚单位mm，折射角单位度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkipInput {
+    pub thickness: f64,
+    pub angle: f64,
+}
+
+/// 第`leg`次波末端（底面或表面反射点）的位置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Leg {
+    pub leg: usize,
+    /// 出射点到该次波末端的水平距离
+    pub surface_distance: f64,
+    /// 累计声程
+    pub sound_path: f64,
+}
+
+/// 由声程换算的反射体位置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reflector {
+    /// 所在次波，1为一次波（直射），2为二次波（经底面反射）……
+    pub leg: usize,
+    pub depth: f64,
+    pub surface_distance: f64,
+}
+
+fn check(input: &SkipInput) -> Result<f64> {
+    positive("板厚", input.thickness)?;
+    if input.angle.is_nan() || input.angle <= 0.0 || input.angle >= 90.0 {
+        return Err(CalcError::OutOfRange("折射角"));
+    }
+    Ok(input.angle.to_radians())
+}
+
+/// 前`count`次波的跳距和声程，第1次波末端即半跳距，第2次波末端即全跳距
+pub fn legs(input: &SkipInput, count: usize) -> Result<Vec<Leg>> {
+    let angle = check(input)?;
+    let skip = input.thickness * angle.tan();
+    let path = input.thickness / angle.cos();
+    Ok((1..=count)
+        .map(|leg| Leg {
+            leg,
+            surface_distance: skip * leg as f64,
+            sound_path: path * leg as f64,
+        })
+        .collect())
+}
+
+/// 按声程读数计算反射体深度和水平距离，超过一次波时按底面/表面反射折回
+pub fn locate(input: &SkipInput, sound_path: f64) -> Result<Reflector> {
+    let angle = check(input)?;
+    let sound_path = positive("声程", sound_path)?;
+    let vertical = sound_path * angle.cos();
+    let leg = (vertical / input.thickness).floor() as usize + 1;
+    let within = vertical - (leg - 1) as f64 * input.thickness;
+    //奇数次波向下传播，偶数次波向上传播
+    let depth = if leg % 2 == 1 { within } else { input.thickness - within };
+    Ok(Reflector {
+        leg,
+        depth,
+        surface_distance: sound_path * angle.sin(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    //45度时跳距等于板厚的整数倍
+    const INPUT: SkipInput = SkipInput {
+        thickness: 20.0,
+        angle: 45.0,
+    };
+
+    #[test]
+    fn half_and_full_skip() {
+        let legs = legs(&INPUT, 3).unwrap();
+        assert_eq!(legs.len(), 3);
+        assert!(close(legs[0].surface_distance, 20.0));
+        assert!(close(legs[1].surface_distance, 40.0));
+        assert!(close(legs[1].sound_path, 40.0 * 2f64.sqrt()));
+    }
+
+    #[test]
+    fn locate_folds_at_backwall() {
+        let direct = locate(&INPUT, 10.0 * 2f64.sqrt()).unwrap();
+        assert_eq!(direct.leg, 1);
+        assert!(close(direct.depth, 10.0));
+        //二次波向上传播，深度从底面算回来
+        let second = locate(&INPUT, 30.0 * 2f64.sqrt()).unwrap();
+        assert_eq!(second.leg, 2);
+        assert!(close(second.depth, 10.0));
+        assert!(close(second.surface_distance, 30.0));
+    }
+
+    #[test]
+    fn error_paths() {
+        let flat = SkipInput { angle: 90.0, ..INPUT };
+        assert_eq!(legs(&flat, 1), Err(CalcError::OutOfRange("折射角")));
+        let empty = SkipInput { thickness: 0.0, ..INPUT };
+        assert_eq!(legs(&empty, 1), Err(CalcError::NonPositive("板厚")));
+        assert_eq!(locate(&INPUT, 0.0), Err(CalcError::NonPositive("声程")));
+    }
+}
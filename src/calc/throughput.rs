@@ -0,0 +1,108 @@
+//! FMC与PWI采集速率和数据量估算
+
+use super::{positive, CalcError, Result};
+
+/// 采集参数：采样频率MHz，采集时间窗µs，PRF单位Hz，扫查步进mm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThroughputInput {
+    pub elements: usize,
+    pub sample_rate: f64,
+    /// 每个采样点的位数
+    pub bit_depth: u32,
+    pub range_gate: f64,
+    pub prf: f64,
+    /// 平面波角度数
+    pub angles: usize,
+    /// 每次发射的平均次数
+    pub averaging: usize,
+    pub scan_step: f64,
+    /// 仪器数据传输上限，MB/s，`None`时不限制
+    pub transfer_rate: Option<f64>,
+}
+
+/// 一种采集方式每帧的数据量和速率
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModeThroughput {
+    /// 每帧发射次数（含平均）
+    pub firings: usize,
+    /// 每帧A扫描条数
+    pub ascans: usize,
+    /// 每帧数据量，字节
+    pub frame_bytes: f64,
+    /// 帧率，Hz
+    pub frame_rate: f64,
+    /// 帧率受数据传输上限而非PRF限制
+    pub transfer_limited: bool,
+    /// 数据率，字节/秒
+    pub data_rate: f64,
+    /// 按每帧一个扫查步进计算的最大扫查速度，mm/s
+    pub scan_speed: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThroughputOutput {
+    /// 每条A扫描采样点数
+    pub samples: usize,
+    /// 时间窗允许的最大PRF，Hz
+    pub max_prf: f64,
+    pub fmc: ModeThroughput,
+    pub pwi: ModeThroughput,
+}
+
+fn mode(input: &ThroughputInput, transmissions: usize, ascan_bytes: f64) -> ModeThroughput {
+    let firings = transmissions * input.averaging;
+    let ascans = transmissions * input.elements;
+    let frame_bytes = ascans as f64 * ascan_bytes;
+    let prf_rate = input.prf / firings as f64;
+    let transfer_rate = input
+        .transfer_rate
+        .map(|rate| rate * 1e6 / frame_bytes)
+        .unwrap_or(f64::INFINITY);
+    let frame_rate = prf_rate.min(transfer_rate);
+    ModeThroughput {
+        firings,
+        ascans,
+        frame_bytes,
+        frame_rate,
+        transfer_limited: transfer_rate < prf_rate,
+        data_rate: frame_bytes * frame_rate,
+        scan_speed: frame_rate * input.scan_step,
+    }
+}
+
+/// FMC每帧发射N次、记录N×N条A扫描；PWI每帧发射M次、记录M×N条A扫描
+pub fn throughput(input: &ThroughputInput) -> Result<ThroughputOutput> {
+    if input.elements == 0 {
+        return Err(CalcError::NonPositive("阵元数"));
+    }
+    if input.angles == 0 {
+        return Err(CalcError::NonPositive("平面波角度数"));
+    }
+    if input.averaging == 0 {
+        return Err(CalcError::NonPositive("平均次数"));
+    }
+    if input.bit_depth == 0 || input.bit_depth > 32 {
+        return Err(CalcError::OutOfRange("采样位数"));
+    }
+    positive("采样频率", input.sample_rate)?;
+    positive("时间窗", input.range_gate)?;
+    positive("PRF", input.prf)?;
+    positive("扫查步进", input.scan_step)?;
+    if let Some(rate) = input.transfer_rate {
+        positive("传输速率", rate)?;
+    }
+    //下一次发射前必须采集完当前时间窗
+    let max_prf = 1e6 / input.range_gate;
+    if input.prf > max_prf {
+        return Err(CalcError::OutOfRange("PRF（超过时间窗允许的最大值）"));
+    }
+
+    let samples = (input.range_gate * input.sample_rate).ceil() as usize;
+    let ascan_bytes = samples as f64 * input.bit_depth as f64 / 8.0;
+    Ok(ThroughputOutput {
+        samples,
+        max_prf,
+        fmc: mode(input, input.elements, ascan_bytes),
+        pwi: mode(input, input.angles, ascan_bytes),
+    })
+}
@@ -0,0 +1,194 @@
+//! TOFD（衍射时差法）探头布置
+//!
+//! 一对探头对称放在焊缝两侧，中心间距PCS = 2S，声束轴线在目标深度d处相交：S = d·tanθ。
+//! 深度d处的衍射波声程为2√(S² + d²)，时间都不含楔块延时。
+//! 声速单位m/s，长度mm，时间µs，频率MHz。
+
+use super::probe::{spread, wavelength, SPREAD_12DB};
+use super::{positive, CalcError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TofdInput {
+    pub frequency: f64,
+    pub velocity: f64,
+    /// 晶片尺寸，为0时不计算声束覆盖
+    pub element_size: f64,
+    /// 折射角，度
+    pub angle: f64,
+    pub thickness: f64,
+    /// 两声束轴线交点的深度，一般取板厚的2/3
+    pub target_depth: f64,
+    /// 脉冲持续的周期数，决定直通波和底面波之后的盲区
+    pub pulse_cycles: f64,
+}
+
+/// 某一深度的深度分辨力
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Resolution {
+    pub depth: f64,
+    pub time: f64,
+    pub resolution: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TofdOutput {
+    pub pcs: f64,
+    pub lateral_time: f64,
+    pub backwall_time: f64,
+    pub target_time: f64,
+    pub pulse_duration: f64,
+    /// 直通波之后的近表面盲区深度
+    pub dead_zone: f64,
+    /// 底面波之前无法分辨的高度
+    pub backwall_dead_zone: f64,
+    /// 按板厚的10%、25%、50%、75%、100%和目标深度列出
+    pub resolution: Vec<Resolution>,
+    /// −12dB声束扩散半角
+    pub spread: Option<f64>,
+    /// 两声束−12dB范围在中心线上的重叠深度（上限, 下限），已限制在板厚内
+    pub coverage: Option<(f64, f64)>,
+}
+
+//mm/µs
+fn speed(velocity: f64) -> f64 {
+    velocity / 1000.0
+}
+
+/// 深度`depth`处的衍射波到达时间
+pub fn tip_time(velocity: f64, pcs: f64, depth: f64) -> f64 {
+    let half = pcs / 2.0;
+    2.0 * half.hypot(depth) / speed(velocity)
+}
+
+/// 由衍射波相对直通波的时间差换算深度，时间差与楔块延时无关
+pub fn tip_depth(velocity: f64, pcs: f64, delay: f64) -> Result<f64> {
+    let velocity = positive("声速", velocity)?;
+    let pcs = positive("探头中心间距", pcs)?;
+    if delay.is_nan() || delay < 0.0 {
+        return Err(CalcError::OutOfRange("衍射波时间差"));
+    }
+    let half = pcs / 2.0;
+    let path = (pcs / speed(velocity) + delay) * speed(velocity) / 2.0;
+    Ok((path * path - half * half).max(0.0).sqrt())
+}
+
+pub fn setup(input: &TofdInput) -> Result<TofdOutput> {
+    let lambda = wavelength(input.velocity, input.frequency)?;
+    let thickness = positive("板厚", input.thickness)?;
+    let target_depth = positive("目标深度", input.target_depth)?;
+    if target_depth > thickness {
+        return Err(CalcError::Invalid(format!("目标深度{}mm超过板厚{}mm", target_depth, thickness)));
+    }
+    if input.angle.is_nan() || input.angle <= 0.0 || input.angle >= 90.0 {
+        return Err(CalcError::OutOfRange("折射角"));
+    }
+    let pulse_duration = positive("脉冲周期数", input.pulse_cycles)? / input.frequency;
+    let angle = input.angle.to_radians();
+    let velocity = speed(input.velocity);
+    let half = target_depth * angle.tan();
+    let pcs = 2.0 * half;
+
+    //脉冲在时间上占据的声程
+    let pulse_path = velocity * pulse_duration;
+    let dead_zone = (pulse_path * half + pulse_path * pulse_path / 4.0).sqrt();
+    let backwall_path = half.hypot(thickness) - pulse_path / 2.0;
+    let backwall_dead_zone = thickness - (backwall_path * backwall_path - half * half).max(0.0).sqrt();
+
+    //Δd = v·t_p·√(S² + d²) / (2d)
+    let resolution_at = |depth: f64| Resolution {
+        depth,
+        time: tip_time(input.velocity, pcs, depth),
+        resolution: pulse_path * half.hypot(depth) / (2.0 * depth),
+    };
+    let mut resolution: Vec<Resolution> =
+        [0.1, 0.25, 0.5, 0.75, 1.0].iter().map(|ratio| resolution_at(thickness * ratio)).collect();
+    resolution.push(resolution_at(target_depth));
+    resolution.sort_by(|a, b| a.depth.partial_cmp(&b.depth).unwrap_or(std::cmp::Ordering::Equal));
+    resolution.dedup_by(|a, b| (a.depth - b.depth).abs() < 1e-9);
+
+    //声束上边缘（折射角+γ）与中心线交点最浅，下边缘（折射角−γ）最深
+    let spread = if input.element_size > 0.0 { spread(SPREAD_12DB, lambda, input.element_size) } else { None };
+    let coverage = spread.map(|spread| {
+        let upper = (input.angle + spread).min(89.9).to_radians();
+        let top = (half / upper.tan()).min(thickness);
+        let lower = input.angle - spread;
+        let bottom = if lower <= 0.0 { thickness } else { (half / lower.to_radians().tan()).min(thickness) };
+        (top, bottom)
+    });
+
+    Ok(TofdOutput {
+        pcs,
+        lateral_time: pcs / velocity,
+        backwall_time: tip_time(input.velocity, pcs, thickness),
+        target_time: tip_time(input.velocity, pcs, target_depth),
+        pulse_duration,
+        dead_zone,
+        backwall_dead_zone,
+        resolution,
+        spread,
+        coverage,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    fn input() -> TofdInput {
+        TofdInput {
+            frequency: 5.0,
+            velocity: 5920.0,
+            element_size: 6.0,
+            angle: 60.0,
+            thickness: 20.0,
+            target_depth: 15.0,
+            pulse_cycles: 2.0,
+        }
+    }
+
+    #[test]
+    fn pcs_and_arrival_times() {
+        let output = setup(&input()).unwrap();
+        let half = 15.0 * 60f64.to_radians().tan();
+        assert!(close(output.pcs, 2.0 * half));
+        assert!(close(output.lateral_time, 2.0 * half / 5.92));
+        assert!(close(output.backwall_time, 2.0 * half.hypot(20.0) / 5.92));
+        assert!(close(output.pulse_duration, 0.4));
+        //直通波之后一个脉冲宽度到达的衍射波深度即盲区
+        assert!(close(tip_time(5920.0, output.pcs, output.dead_zone) - output.lateral_time, output.pulse_duration));
+    }
+
+    #[test]
+    fn tip_depth_inverts_tip_time() {
+        let pcs = 50.0;
+        let lateral = tip_time(5920.0, pcs, 0.0);
+        for &depth in [0.5, 5.0, 12.0, 30.0].iter() {
+            let delay = tip_time(5920.0, pcs, depth) - lateral;
+            assert!((tip_depth(5920.0, pcs, delay).unwrap() - depth).abs() < 1e-9);
+        }
+        assert_eq!(tip_depth(5920.0, pcs, 0.0), Ok(0.0));
+    }
+
+    #[test]
+    fn coverage_brackets_target_depth() {
+        let output = setup(&input()).unwrap();
+        let (top, bottom) = output.coverage.unwrap();
+        assert!(top < 15.0 && 15.0 <= bottom);
+        //分辨力随深度增加而变好
+        assert!(output.resolution.windows(2).all(|pair| pair[0].resolution > pair[1].resolution));
+    }
+
+    #[test]
+    fn error_paths() {
+        let deep = TofdInput { target_depth: 25.0, ..input() };
+        assert!(setup(&deep).is_err());
+        let flat = TofdInput { angle: 90.0, ..input() };
+        assert_eq!(setup(&flat), Err(CalcError::OutOfRange("折射角")));
+        assert_eq!(tip_depth(5920.0, 0.0, 1.0), Err(CalcError::NonPositive("探头中心间距")));
+        assert_eq!(tip_depth(5920.0, 50.0, -1.0), Err(CalcError::OutOfRange("衍射波时间差")));
+    }
+}
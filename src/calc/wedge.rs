@@ -0,0 +1,143 @@
+//! 楔块出射点与前沿
+//!
+//! 坐标约定同`delay_law`：第一阵元中心位于x = 0，声束沿+x方向前进，
+//! 楔块前端位于x = `primary_offset`处。阵元沿楔块斜面逐个升高，
+//! 出射点按从第一阵元开始的激发孔径中心计算。
+
+use super::{positive, CalcError, Result};
+
+/// 楔块参数，长度单位mm，声速单位m/s，角度单位度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WedgeInput {
+    pub wedge_angle: f64,
+    pub wedge_velocity: f64,
+    pub first_element_height: f64,
+    pub pitch: f64,
+    /// 激发孔径阵元数，从第一阵元开始，为0或1时按第一阵元计算
+    pub aperture: usize,
+    /// 第一阵元中心到楔块前端的水平距离
+    pub primary_offset: f64,
+    /// 楔块标称前沿（出射点到楔块前端）
+    pub front_length: f64,
+    pub material_velocity: f64,
+    pub refraction_min: f64,
+    pub refraction_max: f64,
+    pub refraction_step: f64,
+}
+
+/// 某一折射角的出射点
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExitPoint {
+    pub refraction: f64,
+    /// 楔块中的入射角
+    pub incidence: f64,
+    /// 出射点相对第一阵元中心的水平偏移
+    pub index_offset: f64,
+    /// 出射点到楔块前端的距离，负值表示出射点在楔块之外
+    pub front: f64,
+    /// 计算前沿与标称前沿之差
+    pub front_deviation: f64,
+    /// 楔块中的声程
+    pub wedge_path: f64,
+}
+
+const MAX_STEPS: usize = 181;
+
+pub fn exit_point(input: &WedgeInput, refraction: f64) -> Result<ExitPoint> {
+    if refraction.is_nan() || refraction.abs() >= 90.0 {
+        return Err(CalcError::OutOfRange("折射角"));
+    }
+    let sin = refraction.to_radians().sin() * input.wedge_velocity / input.material_velocity;
+    if sin.abs() >= 1.0 {
+        return Err(CalcError::OutOfRange("折射角"));
+    }
+    let incidence = sin.asin();
+    //孔径中心沿斜面离第一阵元的距离
+    let wedge = input.wedge_angle.to_radians();
+    let centre = input.aperture.saturating_sub(1) as f64 * input.pitch / 2.0;
+    let height = input.first_element_height + centre * wedge.sin();
+    let index_offset = centre * wedge.cos() + height * incidence.tan();
+    let front = input.primary_offset - index_offset;
+    Ok(ExitPoint {
+        refraction,
+        incidence: incidence.to_degrees(),
+        index_offset,
+        front,
+        front_deviation: front - input.front_length,
+        wedge_path: height / incidence.cos(),
+    })
+}
+
+pub fn exit_points(input: &WedgeInput) -> Result<Vec<ExitPoint>> {
+    positive("楔块声速", input.wedge_velocity)?;
+    positive("工件声速", input.material_velocity)?;
+    positive("第一阵元高度", input.first_element_height)?;
+    if input.wedge_angle.is_nan() || input.wedge_angle < 0.0 || input.wedge_angle >= 90.0 {
+        return Err(CalcError::OutOfRange("楔块角度"));
+    }
+    if input.aperture > 1 {
+        positive("阵元间距", input.pitch)?;
+    }
+
+    let span = input.refraction_max - input.refraction_min;
+    let count = if span == 0.0 {
+        1
+    } else {
+        let step = positive("角度步进", input.refraction_step)?;
+        (span.abs() / step + 1e-9).floor() as usize + 1
+    };
+    if count > MAX_STEPS {
+        return Err(CalcError::OutOfRange("折射角数量"));
+    }
+    (0..count)
+        .map(|i| exit_point(input, input.refraction_min + span.signum() * input.refraction_step * i as f64))
+        .collect()
+}
+
+/// 楔块角度对应的自然折射角（不偏转时的声束角度），超过临界角时为`None`
+pub fn natural_refraction(input: &WedgeInput) -> Result<Option<f64>> {
+    super::refraction::snell(input.wedge_angle, input.wedge_velocity, input.material_velocity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(aperture: usize) -> WedgeInput {
+        WedgeInput {
+            wedge_angle: 36.0,
+            wedge_velocity: 2337.0,
+            first_element_height: 5.0,
+            pitch: 0.6,
+            aperture,
+            primary_offset: 30.0,
+            front_length: 10.0,
+            material_velocity: 3240.0,
+            refraction_min: 45.0,
+            refraction_max: 70.0,
+            refraction_step: 5.0,
+        }
+    }
+
+    #[test]
+    fn first_element_exit_point() {
+        let point = exit_point(&input(1), 45.0).unwrap();
+        let incidence = point.incidence.to_radians();
+        assert!((point.index_offset - 5.0 * incidence.tan()).abs() < 1e-9);
+        assert!((point.wedge_path - 5.0 / incidence.cos()).abs() < 1e-9);
+        assert_eq!(exit_point(&input(0), 45.0), Ok(point));
+    }
+
+    #[test]
+    fn aperture_centre_moves_up_the_wedge() {
+        //16阵元孔径中心沿斜面离第一阵元4.5mm
+        let point = exit_point(&input(16), 45.0).unwrap();
+        let (wedge, incidence) = (36f64.to_radians(), point.incidence.to_radians());
+        let height = 5.0 + 4.5 * wedge.sin();
+        assert!((point.index_offset - (4.5 * wedge.cos() + height * incidence.tan())).abs() < 1e-9);
+        assert!((point.wedge_path - height / incidence.cos()).abs() < 1e-9);
+        assert!(point.front < exit_point(&input(1), 45.0).unwrap().front);
+        let no_pitch = WedgeInput { pitch: 0.0, ..input(16) };
+        assert_eq!(exit_points(&no_pitch), Err(CalcError::NonPositive("阵元间距")));
+    }
+}
@@ -0,0 +1,352 @@
+//! 焊缝坡口几何与扇扫声线覆盖
+//!
+//! 坐标约定：焊缝中心线x = 0，x沿工件表面，z向下为深度（0为上表面，
+//! 板厚处为底面）。探头位于焊缝左侧（x < 0），声束沿+x方向前进。
+
+use super::ray::Point;
+use super::{positive, CalcError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bevel {
+    /// 单V型坡口
+    SingleV,
+    /// 双V型（X型）坡口，钝边位于板厚中部
+    DoubleV,
+    /// J型坡口，两侧对称（即U型坡口）
+    J,
+}
+
+/// 坡口参数，长度单位mm，坡口角为单侧坡口面与竖直方向的夹角（度）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeldInput {
+    pub bevel: Bevel,
+    pub thickness: f64,
+    pub bevel_angle: f64,
+    pub root_gap: f64,
+    /// 钝边高度
+    pub land: f64,
+    /// 盖面宽度，小于坡口上口宽度时按上口宽度绘制
+    pub cap_width: f64,
+    /// J型坡口根部圆弧半径
+    pub root_radius: f64,
+}
+
+/// 熔合面上的一段
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaceSegment {
+    pub name: String,
+    pub start: Point,
+    pub end: Point,
+}
+
+/// 扇扫参数，`probe_offset`为出射点到焊缝中心线的距离
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoverageInput {
+    pub probe_offset: f64,
+    pub angle_min: f64,
+    pub angle_max: f64,
+    pub angle_step: f64,
+}
+
+/// 声线与熔合面的交点
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    /// `Coverage::faces`中的序号
+    pub segment: usize,
+    pub point: Point,
+    /// 1为一次波，2为二次波
+    pub leg: usize,
+    /// 声线与熔合面法线的夹角（度），越小越接近垂直入射
+    pub incidence: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Beam {
+    pub angle: f64,
+    /// 出射点、底面反射点、二次波末端
+    pub path: Vec<Point>,
+    pub hits: Vec<Hit>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coverage {
+    pub faces: Vec<FaceSegment>,
+    /// 盖面轮廓，仅用于绘图
+    pub cap: Vec<Point>,
+    pub beams: Vec<Beam>,
+}
+
+const MAX_BEAMS: usize = 181;
+/// 坡口面等分段数，用于报告覆盖位置
+const BEVEL_PARTS: usize = 3;
+/// J型根部圆弧的折线段数
+const ARC_PARTS: usize = 6;
+
+fn part_name(i: usize) -> &'static str {
+    match i {
+        0 => "下部",
+        1 => "中部",
+        _ => "上部",
+    }
+}
+
+//把一条直线等分为若干段
+fn split(name: &str, start: Point, end: Point, parts: usize, faces: &mut Vec<FaceSegment>) {
+    for i in 0..parts {
+        let a = i as f64 / parts as f64;
+        let b = (i + 1) as f64 / parts as f64;
+        faces.push(FaceSegment {
+            name: if parts == 1 {
+                name.to_string()
+            } else {
+                format!("{}{}", name, part_name(i))
+            },
+            start: (start.0 + (end.0 - start.0) * a, start.1 + (end.1 - start.1) * a),
+            end: (start.0 + (end.0 - start.0) * b, start.1 + (end.1 - start.1) * b),
+        });
+    }
+}
+
+//左侧（近侧）熔合面，自下而上
+fn near_face(input: &WeldInput) -> Vec<FaceSegment> {
+    let t = input.thickness;
+    let half_gap = input.root_gap / 2.0;
+    let tan = input.bevel_angle.to_radians().tan();
+    let mut faces = Vec::new();
+    match input.bevel {
+        Bevel::SingleV => {
+            let land_top = (-half_gap, t - input.land);
+            if input.land > 0.0 {
+                split("钝边", (-half_gap, t), land_top, 1, &mut faces);
+            }
+            let top = (-half_gap - land_top.1 * tan, 0.0);
+            split("坡口面", land_top, top, BEVEL_PARTS, &mut faces);
+        }
+        Bevel::DoubleV => {
+            let land_bottom = (-half_gap, (t + input.land) / 2.0);
+            let land_top = (-half_gap, (t - input.land) / 2.0);
+            let depth = (t - input.land) / 2.0;
+            split("下坡口面", (-half_gap - depth * tan, t), land_bottom, 1, &mut faces);
+            if input.land > 0.0 {
+                split("钝边", land_bottom, land_top, 1, &mut faces);
+            }
+            split("上坡口面", land_top, (-half_gap - depth * tan, 0.0), 1, &mut faces);
+        }
+        Bevel::J => {
+            let land_top = (-half_gap, t - input.land);
+            if input.land > 0.0 {
+                split("钝边", (-half_gap, t), land_top, 1, &mut faces);
+            }
+            //圆弧圆心在钝边顶点正上方，圆弧转到切线与坡口角一致
+            let radius = input.root_radius;
+            let centre = (land_top.0, land_top.1 - radius);
+            let sweep = (90.0 - input.bevel_angle).to_radians();
+            let mut previous = land_top;
+            for i in 1..=ARC_PARTS {
+                let phi = sweep * i as f64 / ARC_PARTS as f64;
+                let point = (centre.0 - radius * phi.sin(), centre.1 + radius * phi.cos());
+                faces.push(FaceSegment {
+                    name: "根部圆弧".into(),
+                    start: previous,
+                    end: point,
+                });
+                previous = point;
+            }
+            let top = (previous.0 - previous.1 * tan, 0.0);
+            split("坡口面", previous, top, BEVEL_PARTS, &mut faces);
+        }
+    }
+    faces
+}
+
+/// 两侧熔合面，近侧在前、远侧在后，名称分别加“近侧”“远侧”前缀
+pub fn fusion_faces(input: &WeldInput) -> Result<Vec<FaceSegment>> {
+    let thickness = positive("板厚", input.thickness)?;
+    if input.bevel_angle.is_nan() || input.bevel_angle < 0.0 || input.bevel_angle >= 90.0 {
+        return Err(CalcError::OutOfRange("坡口角"));
+    }
+    if input.root_gap < 0.0 {
+        return Err(CalcError::OutOfRange("根部间隙"));
+    }
+    if input.land < 0.0 || input.land >= thickness {
+        return Err(CalcError::OutOfRange("钝边高度"));
+    }
+    if input.bevel == Bevel::J {
+        positive("根部圆弧半径", input.root_radius)?;
+        if input.land + input.root_radius >= thickness {
+            return Err(CalcError::OutOfRange("根部圆弧半径"));
+        }
+    }
+    let near = near_face(input);
+    let far: Vec<FaceSegment> = near
+        .iter()
+        .map(|face| FaceSegment {
+            name: format!("远侧{}", face.name),
+            start: (-face.start.0, face.start.1),
+            end: (-face.end.0, face.end.1),
+        })
+        .collect();
+    let mut faces: Vec<FaceSegment> = near
+        .into_iter()
+        .map(|face| FaceSegment {
+            name: format!("近侧{}", face.name),
+            ..face
+        })
+        .collect();
+    faces.extend(far);
+    Ok(faces)
+}
+
+//线段相交，返回交点
+fn intersect(a: Point, b: Point, c: Point, d: Point) -> Option<Point> {
+    let r = (b.0 - a.0, b.1 - a.1);
+    let s = (d.0 - c.0, d.1 - c.1);
+    let denominator = r.0 * s.1 - r.1 * s.0;
+    if denominator.abs() < 1e-12 {
+        return None;
+    }
+    let q = (c.0 - a.0, c.1 - a.1);
+    let t = (q.0 * s.1 - q.1 * s.0) / denominator;
+    let u = (q.0 * r.1 - q.1 * r.0) / denominator;
+    if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+        Some((a.0 + t * r.0, a.1 + t * r.1))
+    } else {
+        None
+    }
+}
+
+//声线与熔合面法线的夹角
+fn incidence(ray: Point, face: &FaceSegment) -> f64 {
+    let tangent = (face.end.0 - face.start.0, face.end.1 - face.start.1);
+    let length = (tangent.0 * tangent.0 + tangent.1 * tangent.1).sqrt();
+    let ray_length = (ray.0 * ray.0 + ray.1 * ray.1).sqrt();
+    let sin = ((ray.0 * tangent.0 + ray.1 * tangent.1) / (length * ray_length)).abs();
+    sin.min(1.0).asin().to_degrees()
+}
+
+/// 一次波和二次波声线与熔合面的交点
+pub fn trace(weld: &WeldInput, input: &CoverageInput) -> Result<Coverage> {
+    let faces = fusion_faces(weld)?;
+    positive("探头前沿到焊缝中心距离", input.probe_offset)?;
+    for angle in [input.angle_min, input.angle_max].iter() {
+        if angle.is_nan() || *angle <= 0.0 || *angle >= 90.0 {
+            return Err(CalcError::OutOfRange("折射角"));
+        }
+    }
+    let span = input.angle_max - input.angle_min;
+    let count = if span == 0.0 {
+        1
+    } else {
+        let step = positive("角度步进", input.angle_step)?;
+        (span.abs() / step + 1e-9).floor() as usize + 1
+    };
+    if count > MAX_BEAMS {
+        return Err(CalcError::OutOfRange("声线数量"));
+    }
+
+    let t = weld.thickness;
+    let mut beams = Vec::with_capacity(count);
+    for i in 0..count {
+        let angle = input.angle_min + span.signum() * input.angle_step * i as f64;
+        let tan = angle.to_radians().tan();
+        let exit = (-input.probe_offset, 0.0);
+        let bottom = (exit.0 + t * tan, t);
+        let end = (bottom.0 + t * tan, 0.0);
+        let mut hits = Vec::new();
+        for (leg, (a, b)) in [(exit, bottom), (bottom, end)].iter().enumerate() {
+            let direction = (b.0 - a.0, b.1 - a.1);
+            for (segment, face) in faces.iter().enumerate() {
+                if let Some(point) = intersect(*a, *b, face.start, face.end) {
+                    hits.push(Hit {
+                        segment,
+                        point,
+                        leg: leg + 1,
+                        incidence: incidence(direction, face),
+                    });
+                }
+            }
+        }
+        beams.push(Beam {
+            angle,
+            path: vec![exit, bottom, end],
+            hits,
+        });
+    }
+
+    //盖面画成略高于表面的折线
+    let opening = faces
+        .iter()
+        .map(|face| face.start.0.abs().max(face.end.0.abs()))
+        .fold(0.0, f64::max);
+    let half_cap = (weld.cap_width / 2.0).max(opening);
+    let crown = (t * 0.1).min(3.0);
+    let cap = vec![(-half_cap, 0.0), (-half_cap * 0.6, -crown), (half_cap * 0.6, -crown), (half_cap, 0.0)];
+
+    Ok(Coverage { faces, cap, beams })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Point, b: Point) -> bool {
+        (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9
+    }
+
+    fn weld(bevel: Bevel) -> WeldInput {
+        WeldInput {
+            bevel,
+            thickness: 20.0,
+            bevel_angle: 30.0,
+            root_gap: 2.0,
+            land: 2.0,
+            cap_width: 0.0,
+            root_radius: 5.0,
+        }
+    }
+
+    #[test]
+    fn single_v_face_endpoints() {
+        let faces = fusion_faces(&weld(Bevel::SingleV)).unwrap();
+        assert_eq!(faces.len(), 8);
+        assert_eq!(faces[0].name, "近侧钝边");
+        assert!(close(faces[0].start, (-1.0, 20.0)) && close(faces[0].end, (-1.0, 18.0)));
+        assert_eq!(faces[3].name, "近侧坡口面上部");
+        assert!(close(faces[3].end, (-1.0 - 18.0 * 30f64.to_radians().tan(), 0.0)));
+        //远侧与近侧关于中心线对称
+        assert_eq!(faces[7].name, "远侧坡口面上部");
+        assert!(close(faces[7].end, (1.0 + 18.0 * 30f64.to_radians().tan(), 0.0)));
+    }
+
+    #[test]
+    fn j_arc_within_thickness() {
+        let faces = fusion_faces(&weld(Bevel::J)).unwrap();
+        let arc: Vec<&FaceSegment> = faces.iter().filter(|face| face.name == "近侧根部圆弧").collect();
+        assert_eq!(arc.len(), ARC_PARTS);
+        for face in faces.iter() {
+            for point in [face.start, face.end].iter() {
+                assert!(point.1 >= -1e-9 && point.1 <= 20.0 + 1e-9);
+            }
+        }
+        //圆弧从钝边顶点开始，各点到圆心距离为半径
+        assert!(close(arc[0].start, (-1.0, 18.0)));
+        assert!(arc.iter().all(|face| ((face.end.0 + 1.0).hypot(face.end.1 - 13.0) - 5.0).abs() < 1e-9));
+        let too_large = WeldInput { root_radius: 18.0, ..weld(Bevel::J) };
+        assert_eq!(fusion_faces(&too_large), Err(CalcError::OutOfRange("根部圆弧半径")));
+    }
+
+    #[test]
+    fn beam_hits_far_bevel_perpendicularly() {
+        //60度声线垂直于30度坡口面，瞄准远侧坡口面深度9mm处
+        let (tan30, tan60) = (30f64.to_radians().tan(), 60f64.to_radians().tan());
+        let offset = 9.0 * tan60 - (1.0 + 9.0 * tan30);
+        let input = CoverageInput { probe_offset: offset, angle_min: 60.0, angle_max: 60.0, angle_step: 0.0 };
+        let coverage = trace(&weld(Bevel::SingleV), &input).unwrap();
+        let hit = coverage.beams[0].hits[0];
+        assert_eq!((hit.segment, hit.leg), (6, 1));
+        assert_eq!(coverage.faces[hit.segment].name, "远侧坡口面中部");
+        assert!(close(hit.point, (1.0 + 9.0 * tan30, 9.0)));
+        assert!(hit.incidence.abs() < 1e-6);
+        assert_eq!(intersect((0.0, 0.0), (1.0, 1.0), (0.0, 1.0), (1.0, 2.0)), None);
+    }
+}
@@ -3,12 +3,40 @@
 #[macro_use]
 extern crate serde_derive;
 
+pub mod calc;
 mod markdown;
+mod material;
+mod plot;
 
+use calc::aim::{AimInput, AimMap};
+use calc::ascan::{Detection, Gate, Spectrum, Waveform};
+use calc::dac::{AmplitudeUnit, DacInput, DacOutput};
+use calc::delay_law::{DelayLawInput, FocalLaw, ScanType};
+use calc::fmc::{FmcData, SimulationInput};
+use calc::pwi::PwiData;
+use calc::grid::{Grid, ImagingGrid, PolarGrid};
+use calc::tfm::{TfmImage, TfmInput};
+use calc::tofd::{TofdInput, TofdOutput};
+use calc::throughput::{ModeThroughput, ThroughputInput, ThroughputOutput};
+use calc::grating::{GratingInput, GratingOutput};
+use calc::interface::{Medium, NormalIncidence, ObliqueCoefficients};
+use calc::multimode::{TfmMode, WedgeArray, WedgeSimulationInput};
+use calc::pipe::{PipeInput, ScanDirection};
+use calc::probe::{BeamInput, ProbeInput};
+use calc::reflector::{GainInput, Reflector, ReflectorKind, TransferInput};
+use calc::refraction::{RefractionInput, TemperatureCoefficients};
+use calc::skip::SkipInput;
+use calc::wedge::{ExitPoint, WedgeInput};
+use calc::weld::{Bevel, Coverage, CoverageInput, WeldInput};
 use yew::format::Json;
 use yew::services::storage::Area;
-use yew::services::{DialogService, StorageService};
-use yew::{html, Component, ComponentLink, Html, InputData, Renderable, ShouldRender};
+use yew::services::interval::IntervalTask;
+use yew::services::reader::{File, FileData, ReaderTask};
+use yew::services::{DialogService, IntervalService, ReaderService, StorageService};
+use material::Material;
+use plot::{Axis, Marker, Path, Series};
+use std::time::Duration;
+use yew::{html, ChangeData, Component, ComponentLink, Html, InputData, Renderable, ShouldRender};
 
 
 const KEY: &'static str = "yew.crm.database";
@@ -16,6 +44,11 @@ const KEY: &'static str = "yew.crm.database";
 #[derive(Serialize, Deserialize)]
 struct Database {
     probes: Vec<Probe>,
+    //自定义材料，内置材料见material::presets
+    #[serde(default)]
+    materials: Vec<Material>,
+    #[serde(default)]
+    wedges: Vec<Wedge>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -26,9 +59,32 @@ pub struct Probe {
     //存储输入数据的变量
     frequency: f64,
     velocity: f64,
+    //晶片（孔径）尺寸与聚焦深度，单位mm
+    #[serde(default)]
+    element_size: f64,
+    #[serde(default)]
+    focal_depth: f64,
+    //相控阵探头的阵元数和实际阵元间距
+    #[serde(default)]
+    elements: usize,
+    #[serde(default)]
+    element_pitch: f64,
     //存储结果的变量
     lambda: f64,
     pitch: f64,
+    #[serde(default)]
+    near_field: f64,
+    #[serde(default)]
+    spread_6db: Option<f64>,
+    #[serde(default)]
+    spread_20db: Option<f64>,
+    #[serde(default)]
+    spot_width: Option<f64>,
+    #[serde(default)]
+    depth_of_field: Option<f64>,
+    //用该探头制作的DAC曲线
+    #[serde(default)]
+    dac_curves: Vec<Dac>,
 }
 
 impl Probe {
@@ -37,8 +93,82 @@ impl Probe {
             description: "".into(),
             frequency: 0.0,
             velocity: 0.0,
+            element_size: 0.0,
+            focal_depth: 0.0,
+            elements: 0,
+            element_pitch: 0.0,
             lambda: 0.0,
             pitch: 0.0,
+            near_field: 0.0,
+            spread_6db: None,
+            spread_20db: None,
+            spot_width: None,
+            depth_of_field: None,
+            dac_curves: Vec::new(),
+        }
+    }
+
+    /// 由频率、声速和晶片参数重新计算波长、λ/2间距和声场参数，并生成说明
+    fn recalc(&mut self) {
+        let input = ProbeInput {
+            frequency: self.frequency,
+            velocity: self.velocity,
+        };
+        match calc::probe::lambda_pitch(&input) {
+            Ok(output) => {
+                self.lambda = output.lambda;
+                self.pitch = output.pitch;
+                self.description =
+                    format!("波长为{}mm\npitch最小值为{}mm", self.lambda, self.pitch);
+                if self.element_pitch > self.pitch {
+                    self.description += &format!(
+                        "\n实际阵元间距{}mm大于λ/2，偏转时可能出现栅瓣",
+                        self.element_pitch
+                    );
+                }
+            }
+            Err(err) => self.description = err.to_string(),
+        }
+        //先清掉上次的结果，未计算或计算出错时不保留旧值
+        self.near_field = 0.0;
+        self.spread_6db = None;
+        self.spread_20db = None;
+        self.spot_width = None;
+        self.depth_of_field = None;
+        //输入了晶片尺寸时计算近场和扩散角，聚焦深度为0时不聚焦
+        if self.element_size > 0.0 && self.lambda > 0.0 {
+            let input = BeamInput {
+                frequency: self.frequency,
+                velocity: self.velocity,
+                element_size: self.element_size,
+                focal_depth: if self.focal_depth > 0.0 {
+                    Some(self.focal_depth)
+                } else {
+                    None
+                },
+            };
+            match calc::probe::beam(&input) {
+                Ok(output) => {
+                    self.near_field = output.near_field;
+                    self.spread_6db = output.spread_6db;
+                    self.spread_20db = output.spread_20db;
+                    self.spot_width = output.focus.map(|focus| focus.spot_width);
+                    self.depth_of_field = output.focus.map(|focus| focus.depth_of_field);
+                    self.description += &format!(
+                        "\n近场长度为{:.2}mm\n-6dB扩散半角为{}\n-20dB扩散半角为{}",
+                        output.near_field,
+                        spread_text(output.spread_6db),
+                        spread_text(output.spread_20db)
+                    );
+                    if let Some(focus) = output.focus {
+                        self.description += &format!(
+                            "\n归一化焦距为{:.2}\n焦点声束宽度为{:.2}mm\n焦区长度为{:.2}mm",
+                            focus.normalized, focus.spot_width, focus.depth_of_field
+                        );
+                    }
+                }
+                Err(err) => self.description += &format!("\n{}", err),
+            }
         }
     }
 }
@@ -46,39 +176,317 @@ impl Probe {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BeamAngle {
     incidence_min: f64,
-    refraction_min: f64,
+    //超过临界角时无透射波，记为None
+    refraction_min: Option<f64>,
 
     incidence_max: f64,
-    refraction_max: f64,
+    refraction_max: Option<f64>,
+
+    //折射介质中的横波折射角
+    refraction_shear_min: Option<f64>,
+    refraction_shear_max: Option<f64>,
 
-    refraction_steel_min: f64,
-    refraction_steel_max: f64,
+    refraction_steel_min: Option<f64>,
+    refraction_steel_max: Option<f64>,
+
+    //第一/第二临界角，折射介质声速不大于入射声速时不存在
+    critical_first: Option<f64>,
+    critical_second: Option<f64>,
 
     velocity_steel: f64,
     velocity_incidence: f64,
+    //折射介质纵波声速
     velocity_medium: f64,
+    //折射介质横波声速
+    velocity_medium_shear: f64,
+    //入射介质横波声速，0为液体
+    #[serde(default)]
+    velocity_incidence_shear: f64,
+    //两侧介质密度，都输入后计算反射/透射系数
+    #[serde(default)]
+    density_incidence: f64,
+    #[serde(default)]
+    density_medium: f64,
+
+    //校准和检测时的温度，°C，输入的声速按材料库的20°C计
+    #[serde(default = "reference_temperature")]
+    temperature_calibration: f64,
+    #[serde(default = "reference_temperature")]
+    temperature_inspection: f64,
+    //声速温度系数，m/s/°C
+    #[serde(default)]
+    temperature_incidence: f64,
+    #[serde(default)]
+    temperature_medium: f64,
+    #[serde(default)]
+    temperature_medium_shear: f64,
+    #[serde(default)]
+    temperature_steel: f64,
 
     result: String,
-    //reflection: f64,
+    #[serde(skip)]
+    coefficients: Vec<ObliqueCoefficients>,
+}
+
+fn reference_temperature() -> f64 {
+    calc::refraction::REFERENCE_TEMPERATURE
 }
 
 impl BeamAngle {
     fn empty() -> Self {
         BeamAngle {
             incidence_min: 0.0,
-            refraction_min: 0.0,
+            refraction_min: None,
 
             incidence_max: 0.0,
-            refraction_max: 0.0,
+            refraction_max: None,
 
-            refraction_steel_min: 0.0,
-            refraction_steel_max: 0.0,
+            refraction_shear_min: None,
+            refraction_shear_max: None,
+
+            refraction_steel_min: None,
+            refraction_steel_max: None,
+
+            critical_first: None,
+            critical_second: None,
 
             velocity_steel: 0.0,
             velocity_incidence: 0.0,
             velocity_medium: 0.0,
-            //reflection: f64,
+            velocity_medium_shear: 0.0,
+            velocity_incidence_shear: 0.0,
+            density_incidence: 0.0,
+            density_medium: 0.0,
+            temperature_calibration: reference_temperature(),
+            temperature_inspection: reference_temperature(),
+            temperature_incidence: 0.0,
+            temperature_medium: 0.0,
+            temperature_medium_shear: 0.0,
+            temperature_steel: 0.0,
             result: "".into(),
+            coefficients: Vec::new(),
+        }
+    }
+
+    fn refraction_text(&self, input: &RefractionInput, output: &calc::refraction::RefractionOutput) -> String {
+        let mut text = format!(
+            "纵波折射角范围为{}～{}\n按入射声速{}m/s折射声速{}m/s计算",
+            angle_text(output.min.longitudinal),
+            angle_text(output.max.longitudinal),
+            input.velocity_incidence,
+            input.velocity_longitudinal
+        );
+        if let Some(velocity) = input.velocity_shear {
+            text += &format!(
+                "\n横波折射角范围为{}～{}\n按横波声速{}m/s计算",
+                angle_text(output.min.shear),
+                angle_text(output.max.shear),
+                velocity
+            );
+        }
+        //临界角提示
+        let top = self.incidence_min.max(self.incidence_max);
+        let bottom = self.incidence_min.min(self.incidence_max);
+        for (name, critical) in [
+            ("第一临界角", output.critical_first),
+            ("第二临界角", output.critical_second),
+        ]
+        .iter()
+        {
+            if let Some(critical) = critical {
+                let flag = if bottom > *critical {
+                    "入射角范围全部超过"
+                } else if top > *critical {
+                    "入射角范围跨过"
+                } else {
+                    "入射角范围未达到"
+                };
+                text += &format!("\n{}为{:.2}度，{}该角", name, critical, flag);
+            }
+        }
+        //按入射角范围列出两种波型
+        text += "\n\n| 入射角 | 纵波折射角 | 横波折射角 |\n|---|---|---|";
+        for row in output.sweep.iter() {
+            text += &format!(
+                "\n| {:.2}度 | {} | {} |",
+                row.incidence,
+                angle_text(row.longitudinal),
+                angle_text(row.shear)
+            );
+        }
+        if let Some(velocity) = input.velocity_steel {
+            text += &format!(
+                "\n\n钢中折射角范围为{}～{}\n按钢中声速{}m/s计算",
+                angle_text(output.steel_min),
+                angle_text(output.steel_max),
+                velocity
+            );
+        }
+        text
+    }
+
+    fn temperature_coefficients(&self) -> TemperatureCoefficients {
+        TemperatureCoefficients {
+            incidence: self.temperature_incidence,
+            longitudinal: self.temperature_medium,
+            shear: self.temperature_medium_shear,
+            steel: self.temperature_steel,
+        }
+    }
+
+    //按检测温度的声速计算折射角，温度不是20°C时列出校准与检测温度下的折射角偏移
+    fn calc_refraction(&mut self, input: &RefractionInput) -> calc::Result<()> {
+        //先清掉上次的结果，计算出错时不保留旧温度下的折射角
+        self.refraction_min = None;
+        self.refraction_max = None;
+        self.refraction_shear_min = None;
+        self.refraction_shear_max = None;
+        self.critical_first = None;
+        self.critical_second = None;
+        self.refraction_steel_min = None;
+        self.refraction_steel_max = None;
+        let coefficients = self.temperature_coefficients();
+        let calibration = calc::refraction::at_temperature(input, &coefficients, self.temperature_calibration)?;
+        let inspection = calc::refraction::at_temperature(input, &coefficients, self.temperature_inspection)?;
+        let calibration_output = calc::refraction::refraction(&calibration)?;
+        let output = calc::refraction::refraction(&inspection)?;
+        self.refraction_min = output.min.longitudinal;
+        self.refraction_max = output.max.longitudinal;
+        self.refraction_shear_min = output.min.shear;
+        self.refraction_shear_max = output.max.shear;
+        self.critical_first = output.critical_first;
+        self.critical_second = output.critical_second;
+        self.refraction_steel_min = output.steel_min;
+        self.refraction_steel_max = output.steel_max;
+        self.result = self.refraction_text(&inspection, &output);
+        let reference = reference_temperature();
+        if self.temperature_calibration != reference || self.temperature_inspection != reference {
+            let text = self.temperature_text(&calibration, &inspection, &calibration_output, &output);
+            self.result += &text;
+        }
+        Ok(())
+    }
+
+    fn temperature_text(
+        &self,
+        calibration: &RefractionInput,
+        inspection: &RefractionInput,
+        calibration_output: &calc::refraction::RefractionOutput,
+        output: &calc::refraction::RefractionOutput,
+    ) -> String {
+        let velocity = |velocity: Option<f64>| match velocity {
+            Some(velocity) => format!("{:.0}m/s", velocity),
+            None => "-".into(),
+        };
+        let mut text = format!(
+            "\n\n温度补偿（输入声速按{}°C计）\n\n| 声速 | 校准{}°C | 检测{}°C |\n|---|---|---|",
+            reference_temperature(),
+            self.temperature_calibration,
+            self.temperature_inspection
+        );
+        for (name, before, after) in [
+            ("入射", Some(calibration.velocity_incidence), Some(inspection.velocity_incidence)),
+            ("纵波", Some(calibration.velocity_longitudinal), Some(inspection.velocity_longitudinal)),
+            ("横波", calibration.velocity_shear, inspection.velocity_shear),
+            ("钢中", calibration.velocity_steel, inspection.velocity_steel),
+        ]
+        .iter()
+        {
+            if before.is_some() {
+                text += &format!("\n| {} | {} | {} |", name, velocity(*before), velocity(*after));
+            }
+        }
+        //偏移 = 检测温度下的折射角 − 校准温度下的折射角
+        let shift = |before: Option<f64>, after: Option<f64>| match (before, after) {
+            (Some(before), Some(after)) => format!("{}→{}（{:+.2}度）", angle_text(Some(before)), angle_text(Some(after)), after - before),
+            _ => format!("{}→{}", angle_text(before), angle_text(after)),
+        };
+        text += "\n\n| 入射角 | 纵波折射角 | 横波折射角 |\n|---|---|---|";
+        for (before, after) in calibration_output.sweep.iter().zip(output.sweep.iter()) {
+            text += &format!(
+                "\n| {:.2}度 | {} | {} |",
+                after.incidence,
+                shift(before.longitudinal, after.longitudinal),
+                shift(before.shear, after.shear)
+            );
+        }
+        text
+    }
+
+    fn incidence_medium(&self) -> Medium {
+        Medium {
+            velocity_longitudinal: self.velocity_incidence,
+            velocity_shear: self.velocity_incidence_shear,
+            density: self.density_incidence,
+        }
+    }
+
+    fn refraction_medium(&self) -> Medium {
+        Medium {
+            velocity_longitudinal: self.velocity_medium,
+            velocity_shear: self.velocity_medium_shear,
+            density: self.density_medium,
+        }
+    }
+
+    fn coefficient_text(&self, normal: &NormalIncidence) -> String {
+        let mut text = format!(
+            "\n\n垂直入射：Z₁ = {:.2}MRayl，Z₂ = {:.2}MRayl\n\n| | 反射 | 透射 |\n|---|---|---|\n| 声压 | {:.3} | {:.3} |\n| 能量 | {:.3} | {:.3} |",
+            normal.impedance_incidence,
+            normal.impedance_medium,
+            normal.pressure_reflection,
+            normal.pressure_transmission,
+            normal.energy_reflection,
+            normal.energy_transmission
+        );
+        //只列出输入的入射角范围内的斜入射系数
+        let top = self.incidence_min.max(self.incidence_max);
+        let bottom = self.incidence_min.min(self.incidence_max);
+        text += "\n\n斜入射能量系数\n\n| 入射角 | 反射纵波 | 反射横波 | 透射纵波 | 透射横波 |\n|---|---|---|---|---|";
+        for row in self.coefficients.iter().filter(|row| (bottom..=top).contains(&row.incidence)) {
+            text += &format!(
+                "\n| {}度 | {:.3} | {:.3} | {:.3} | {:.3} |",
+                row.incidence,
+                row.reflected_longitudinal,
+                row.reflected_shear,
+                row.transmitted_longitudinal,
+                row.transmitted_shear
+            );
+        }
+        text
+    }
+
+    fn view_coefficients(&self) -> Html {
+        if self.coefficients.is_empty() {
+            return html! {};
+        }
+        let series = |label: &str, color: &'static str, value: fn(&ObliqueCoefficients) -> f64| Series {
+            label: label.into(),
+            color,
+            points: self.coefficients.iter().map(|row| (row.incidence, value(row))).collect(),
+        };
+        plot::line_chart(
+            &Axis { label: "入射角（度）", min: 0.0, max: 90.0 },
+            &Axis { label: "能量系数", min: 0.0, max: 1.0 },
+            &[
+                series("反射纵波", "steelblue", |row| row.reflected_longitudinal),
+                series("反射横波", "darkorange", |row| row.reflected_shear),
+                series("透射纵波", "firebrick", |row| row.transmitted_longitudinal),
+                series("透射横波", "seagreen", |row| row.transmitted_shear),
+            ],
+        )
+    }
+
+    fn view_temperature_inputs(&self, link: &ComponentLink<Model>) -> Html {
+        html! {
+            <div class="beam-angle">
+                { view_number_input("beam-angle", "校准温度（°C）", self.temperature_calibration, link, Msg::UpdateCalibrationTemperature) }
+                { view_number_input("beam-angle", "检测温度（°C）", self.temperature_inspection, link, Msg::UpdateInspectionTemperature) }
+                { view_number_input("beam-angle", "入射声速温度系数（m/s/°C）", self.temperature_incidence, link, Msg::UpdateTemperatureIncidence) }
+                { view_number_input("beam-angle", "纵波声速温度系数（m/s/°C）", self.temperature_medium, link, Msg::UpdateTemperatureMedium) }
+                { view_number_input("beam-angle", "横波声速温度系数（m/s/°C）", self.temperature_medium_shear, link, Msg::UpdateTemperatureMediumShear) }
+                { view_number_input("beam-angle", "钢中声速温度系数（m/s/°C）", self.temperature_steel, link, Msg::UpdateTemperatureSteel) }
+            </div>
         }
     }
 
@@ -97,18 +505,16 @@ impl BeamAngle {
         }
     }
     fn velocity_incidence_input(&self, link: &ComponentLink<Model>) -> Html {
-        html! {
-        <input class="beam-angle"
-                   placeholder="介质声速（入射角）"
-                   oninput=link.callback(|e: InputData| Msg::UpdateVelocityIncidence(e.value.parse().unwrap())) />
-        }
+        view_number_input("beam-angle", "介质声速（入射角）", self.velocity_incidence, link, Msg::UpdateVelocityIncidence)
     }
     fn velocity_refraction_input(&self, link: &ComponentLink<Model>) -> Html {
-        html! {
-        <input class="beam-angle"
-                   placeholder="介质声速（折射角）"
-                   oninput=link.callback(|e: InputData| Msg::UpdateVelocityRefraction(e.value.parse().unwrap())) />
-        }
+        view_number_input("beam-angle", "介质纵波声速（折射角）", self.velocity_medium, link, Msg::UpdateVelocityRefraction)
+    }
+    fn velocity_shear_input(&self, link: &ComponentLink<Model>) -> Html {
+        view_number_input("beam-angle", "介质横波声速（折射角）", self.velocity_medium_shear, link, Msg::UpdateVelocityShear)
+    }
+    fn velocity_steel_input(&self, link: &ComponentLink<Model>) -> Html {
+        view_number_input("beam-angle", "介质声速（钢）", self.velocity_steel, link, Msg::UpdateVelocitySteel)
     }
     fn view_result(&self, link: &ComponentLink<Model>) -> Html {
         html! {
@@ -127,8 +533,14 @@ impl Renderable for BeamAngle {
             <div class="beam-angle">
                 <p>{ format!("Incidence Min: {}", self.incidence_min) }</p>
                 <p>{ format!("Incidence Max: {}", self.incidence_max) }</p>
-                <p>{ format!("Incidence in Steel Min: {}", self.refraction_steel_min) }</p>
-                <p>{ format!("Incidence in Steel Max: {}", self.refraction_steel_max) }</p>
+                <p>{ format!("Refraction L Min: {}", angle_text(self.refraction_min)) }</p>
+                <p>{ format!("Refraction L Max: {}", angle_text(self.refraction_max)) }</p>
+                <p>{ format!("Refraction S Min: {}", angle_text(self.refraction_shear_min)) }</p>
+                <p>{ format!("Refraction S Max: {}", angle_text(self.refraction_shear_max)) }</p>
+                <p>{ format!("1st Critical: {}", angle_text(self.critical_first)) }</p>
+                <p>{ format!("2nd Critical: {}", angle_text(self.critical_second)) }</p>
+                <p>{ format!("Refraction in Steel Min: {}", angle_text(self.refraction_steel_min)) }</p>
+                <p>{ format!("Refraction in Steel Max: {}", angle_text(self.refraction_steel_max)) }</p>
                 <p>{ "Result:" }</p>
                 { markdown::render_markdown(&self.result) }
             </div>
@@ -136,174 +548,3610 @@ impl Renderable for BeamAngle {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Wedge {
+    description: String,
+    //楔块参数，长度单位mm
+    angle: f64,
+    velocity: f64,
+    first_element_height: f64,
+    //出射点按从第一阵元开始的激发孔径中心计算
+    #[serde(default)]
+    pitch: f64,
+    #[serde(default)]
+    aperture: usize,
+    //第一阵元中心到楔块前端的水平距离
+    primary_offset: f64,
+    //标称前沿（出射点到楔块前端）
+    front_length: f64,
+    //计算出射点用的工件声速和折射角范围
+    material_velocity: f64,
+    refraction_min: f64,
+    refraction_max: f64,
+    refraction_step: f64,
+}
+
+impl Wedge {
+    fn empty() -> Self {
+        Wedge::with_range(0.0, 0.0, 0.0)
+    }
+
+    //折射角范围和工件声速取同一波型
+    fn with_range(refraction_min: f64, refraction_max: f64, material_velocity: f64) -> Self {
+        Wedge {
+            description: "".into(),
+            angle: 0.0,
+            velocity: 0.0,
+            first_element_height: 0.0,
+            pitch: 0.0,
+            aperture: 0,
+            primary_offset: 0.0,
+            front_length: 0.0,
+            material_velocity,
+            refraction_min,
+            refraction_max,
+            refraction_step: 0.0,
+        }
+    }
+
+    fn input(&self) -> WedgeInput {
+        WedgeInput {
+            wedge_angle: self.angle,
+            wedge_velocity: self.velocity,
+            first_element_height: self.first_element_height,
+            pitch: self.pitch,
+            aperture: self.aperture,
+            primary_offset: self.primary_offset,
+            front_length: self.front_length,
+            material_velocity: self.material_velocity,
+            refraction_min: self.refraction_min,
+            refraction_max: self.refraction_max,
+            refraction_step: self.refraction_step,
+        }
+    }
+
+    fn exit_points_text(&self, natural: Option<f64>, points: &[ExitPoint]) -> String {
+        let mut text = format!("楔块自然折射角为{}", angle_text(natural));
+        text += "\n\n| 折射角 | 入射角 | 出射点偏移 | 前沿 | 与标称前沿差 | 楔块声程 |\n|---|---|---|---|---|---|";
+        for point in points.iter() {
+            text += &format!(
+                "\n| {:.1}度 | {:.2}度 | {:.2}mm | {:.2}mm | {:+.2}mm | {:.2}mm |",
+                point.refraction,
+                point.incidence,
+                point.index_offset,
+                point.front,
+                point.front_deviation,
+                point.wedge_path
+            );
+        }
+        if points.iter().any(|point| point.front < 0.0) {
+            text += "\n\n**部分角度的出射点在楔块前端之外，请检查第一阵元偏移**";
+        }
+        text
+    }
+
+    fn view_inputs(&self, materials: &[Material], link: &ComponentLink<Model>) -> Html {
+        html! {
+            <div class="wedge">
+                { view_number_input("wedge", "楔块角度", self.angle, link, Msg::UpdateWedgeAngle) }
+                { view_number_input("wedge", "楔块声速", self.velocity, link, Msg::UpdateWedgeVelocity) }
+                { view_velocity_select(materials, link, Msg::UpdateWedgeVelocity) }
+                { view_number_input("wedge", "第一阵元高度", self.first_element_height, link, Msg::UpdateFirstElementHeight) }
+                { view_number_input("wedge", "阵元间距", self.pitch, link, Msg::UpdatePitch) }
+                { view_number_input("wedge", "孔径阵元数（0为第一阵元）", self.aperture as f64, link, |val| Msg::UpdateAperture(val as usize)) }
+                { view_number_input("wedge", "第一阵元到前端距离", self.primary_offset, link, Msg::UpdatePrimaryOffset) }
+                { view_number_input("wedge", "标称前沿", self.front_length, link, Msg::UpdateFrontLength) }
+                { view_number_input("wedge", "工件声速", self.material_velocity, link, Msg::UpdateMaterialVelocity) }
+                { view_velocity_select(materials, link, Msg::UpdateMaterialVelocity) }
+                { view_number_input("wedge", "起始折射角", self.refraction_min, link, Msg::UpdateAngleStart) }
+                { view_number_input("wedge", "终止折射角", self.refraction_max, link, Msg::UpdateAngleEnd) }
+                { view_number_input("wedge", "角度步进", self.refraction_step, link, Msg::UpdateAngleStep) }
+            </div>
+        }
+    }
+}
+
+impl Renderable for Wedge {
+    fn render(&self) -> Html {
+        html! {
+            <div class="wedge">
+                <p>{ format!("Wedge Angle: {}", self.angle) }</p>
+                <p>{ format!("Wedge Velocity: {}", self.velocity) }</p>
+                <p>{ format!("First Element Height: {}", self.first_element_height) }</p>
+                <p>{ format!("Primary Offset: {}", self.primary_offset) }</p>
+                <p>{ format!("Front Length: {}", self.front_length) }</p>
+                <p>{ "Description:" }</p>
+                { markdown::render_markdown(&self.description) }
+            </div>
+        }
+    }
+}
+
+//校准试块上的反射体
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum CalibrationReflector {
+    Sdh,
+    Fbh,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Dac {
+    description: String,
+    reflector: CalibrationReflector,
+    //反射体直径，mm
+    diameter: f64,
+    //参考波高，%满屏
+    reference: f64,
+    //校准点"深度,幅度"，分号或换行分隔
+    points: String,
+    //幅度以dB输入，否则为%满屏
+    decibel: bool,
+
+    #[serde(skip)]
+    output: Option<DacOutput>,
+    #[serde(skip)]
+    result: String,
+}
+
+impl Dac {
+    fn empty() -> Self {
+        Dac {
+            description: "".into(),
+            reflector: CalibrationReflector::Sdh,
+            diameter: 3.0,
+            reference: 80.0,
+            points: "10,80;20,62;30,45;40,33".into(),
+            decibel: false,
+            output: None,
+            result: "".into(),
+        }
+    }
+
+    fn input(&self) -> calc::Result<DacInput> {
+        Ok(DacInput {
+            points: calc::dac::parse_points(&self.points)?,
+            unit: if self.decibel { AmplitudeUnit::Decibel } else { AmplitudeUnit::Percent },
+            reference: self.reference,
+        })
+    }
+
+    fn calc(&mut self) {
+        match self.input().and_then(|input| calc::dac::build(&input)) {
+            Ok(output) => {
+                self.result = Dac::dac_text(&output);
+                self.output = Some(output);
+            }
+            Err(err) => {
+                self.result = err.to_string();
+                self.output = None;
+            }
+        }
+    }
+
+    fn reflector_name(&self) -> String {
+        match self.reflector {
+            CalibrationReflector::Sdh => format!("Φ{}横通孔", self.diameter),
+            CalibrationReflector::Fbh => format!("Φ{}平底孔", self.diameter),
+        }
+    }
+
+    fn dac_text(output: &DacOutput) -> String {
+        let mut text = "| 深度（mm） | DAC（%满屏） | −6dB | −12dB | TCG增益（dB） |\n|---|---|---|---|---|".to_string();
+        for point in output.points.iter() {
+            text += &format!(
+                "\n| {} | {:.1} | {:.1} | {:.1} | {:+.1} |",
+                point.depth,
+                point.amplitude,
+                output.amplitude(point.depth, -6.0),
+                output.amplitude(point.depth, -12.0),
+                point.gain
+            );
+        }
+        text += &format!("\n\nTCG开启后各深度的反射体回波均显示为{}%满屏", output.reference);
+        text
+    }
+
+    fn view_chart(&self) -> Html {
+        let output = match self.output {
+            Some(ref output) => output,
+            None => return html! {},
+        };
+        let (first, last) = (output.points[0].depth, output.points[output.points.len() - 1].depth);
+        let curve = |offset: f64| -> Vec<(f64, f64)> {
+            (0..=100)
+                .map(|i| {
+                    let depth = first + (last - first) * i as f64 / 100.0;
+                    (depth, output.amplitude(depth, offset))
+                })
+                .collect()
+        };
+        html! {
+            <div style="display: flex; flex-wrap: wrap">
+                { plot::line_chart(
+                    &Axis { label: "深度（mm）", min: 0.0, max: last * 1.1 },
+                    &Axis { label: "%满屏", min: 0.0, max: 100.0 },
+                    &[
+                        Series { label: format!("DAC（{}）", self.reflector_name()), color: "firebrick", points: curve(0.0) },
+                        Series { label: "−6dB".into(), color: "darkorange", points: curve(-6.0) },
+                        Series { label: "−12dB".into(), color: "seagreen", points: curve(-12.0) },
+                    ],
+                ) }
+                { plot::line_chart(
+                    &Axis { label: "深度（mm）", min: 0.0, max: last * 1.1 },
+                    &Axis { label: "dB", min: 0.0, max: output.points.iter().map(|point| point.gain).fold(6.0, f64::max).ceil() },
+                    &[Series {
+                        label: "TCG增益".into(),
+                        color: "steelblue",
+                        points: output.points.iter().map(|point| (point.depth, point.gain)).collect(),
+                    }],
+                ) }
+            </div>
+        }
+    }
+
+    //CSV以数据URI下载
+    fn view_export(&self) -> Html {
+        match self.output {
+            Some(ref output) => {
+                let uri = format!("data:text/csv;base64,{}", plot::base64(output.to_csv().as_bytes()));
+                html! {
+                    <a href=uri download="DAC.csv"><button>{ "导出CSV" }</button></a>
+                }
+            }
+            None => html! {},
+        }
+    }
+
+    fn view_save_buttons(probes: &[Probe], link: &ComponentLink<Model>) -> Html {
+        let button = |(i, probe): (usize, &Probe)| {
+            html! {
+                <button onclick=link.callback(move |_| Msg::SaveDac(i))>
+                    { format!("保存到探头{}（{}MHz）", i + 1, probe.frequency) }
+                </button>
+            }
+        };
+        html! {
+            <div class="probe-buttons">
+                { for probes.iter().enumerate().map(button) }
+            </div>
+        }
+    }
+
+    //已保存的DAC曲线，按探头列出
+    fn view_saved(probes: &[Probe], link: &ComponentLink<Model>) -> Html {
+        let mut buttons = Vec::new();
+        for (i, probe) in probes.iter().enumerate() {
+            for (j, dac) in probe.dac_curves.iter().enumerate() {
+                let label = format!("探头{} {} {}", i + 1, dac.reflector_name(), dac.description);
+                buttons.push(html! {
+                    <button onclick=link.callback(move |_| Msg::LoadDac(i, j))>{ label }</button>
+                });
+            }
+        }
+        html! {
+            <div class="dac-saved">
+                { for buttons.into_iter() }
+            </div>
+        }
+    }
+
+    fn view_inputs(&self, link: &ComponentLink<Model>) -> Html {
+        let placeholder = format!(
+            "校准点：深度（mm）,幅度（{}），分号分隔",
+            if self.decibel { "dB" } else { "%满屏" }
+        );
+        html! {
+            <div class="dac">
+                <input class="dac"
+                       placeholder="试块及说明"
+                       value=&self.description
+                       oninput=link.callback(|e: InputData| Msg::UpdateDescription(e.value)) />
+                <button onclick=link.callback(|_| Msg::UpdateCalibrationReflector(CalibrationReflector::Sdh))>{ "横通孔SDH" }</button>
+                <button onclick=link.callback(|_| Msg::UpdateCalibrationReflector(CalibrationReflector::Fbh))>{ "平底孔FBH" }</button>
+                { view_number_input("dac", "反射体直径", self.diameter, link, Msg::UpdateReflectorDiameter) }
+                { view_number_input("dac", "参考波高（%满屏）", self.reference, link, Msg::UpdateReferenceHeight) }
+                <button onclick=link.callback(|_| Msg::UpdateDacUnit(false))>{ "波高输入" }</button>
+                <button onclick=link.callback(|_| Msg::UpdateDacUnit(true))>{ "dB输入" }</button>
+                <textarea class="dac"
+                          placeholder=placeholder
+                          value=&self.points
+                          oninput=link.callback(|e: InputData| Msg::UpdateDacPoints(e.value)) />
+            </div>
+        }
+    }
+}
+
 #[derive(Debug)]
-pub enum Scene {
-    SceneList,
-    ProbeForm(Probe),
-    //BeamAngleForm(BeamAngle),
-    TFMPWIForm,
-    RefractionAngle(BeamAngle),
-    Settings,
+pub struct GainTransfer {
+    //波长和近场长度，mm，可从探头载入
+    lambda: f64,
+    near_field: f64,
+    //参考反射体已调到基准波高
+    reference: Reflector,
+    target: Reflector,
+    //单程衰减系数，dB/mm
+    attenuation: f64,
+    //传输修正：试块和工件上大平底回波达到基准波高的增益及声程
+    block_gain: f64,
+    block_distance: f64,
+    part_gain: f64,
+    part_distance: f64,
+
+    result: String,
 }
 
-pub struct Model {
-    link: ComponentLink<Self>,
-    storage: StorageService,
-    dialog: DialogService,
-    database: Database,
-    scene: Scene,
+impl GainTransfer {
+    fn empty() -> Self {
+        GainTransfer {
+            lambda: 0.0,
+            near_field: 0.0,
+            reference: Reflector {
+                kind: ReflectorKind::Sdh,
+                diameter: 3.0,
+                distance: 0.0,
+            },
+            target: Reflector {
+                kind: ReflectorKind::Fbh,
+                diameter: 2.0,
+                distance: 0.0,
+            },
+            attenuation: 0.0,
+            block_gain: 0.0,
+            block_distance: 0.0,
+            part_gain: 0.0,
+            part_distance: 0.0,
+            result: "".into(),
+        }
+    }
+
+    fn input(&self) -> GainInput {
+        GainInput {
+            lambda: self.lambda,
+            reference: self.reference,
+            target: self.target,
+            attenuation: self.attenuation,
+            near_field: self.near_field,
+        }
+    }
+
+    //把公式和代入的数值都写出来
+    fn calc(&self) -> calc::Result<String> {
+        let output = calc::reflector::gain_difference(&self.input())?;
+        let reference = calc::reflector::pressure(&self.reference, self.lambda)?;
+        let target = calc::reflector::pressure(&self.target, self.lambda)?;
+        let line = |title: &str, reflector: &Reflector, pressure: f64| {
+            format!(
+                "**{}**：{}，声程x = {}mm\n\n{} = {:.4e}·P₀F_s",
+                title,
+                reflector.name(),
+                reflector.distance,
+                reflector.formula(),
+                pressure
+            )
+        };
+        let mut text = format!("λ = {}mm\n\n", self.lambda);
+        text += &line("参考反射体", &self.reference, reference);
+        text += "\n\n";
+        text += &line("目标反射体", &self.target, target);
+        text += &format!(
+            "\n\nΔ = 20lg(P_参考 / P_目标) = 20lg({:.4e} / {:.4e}) = {:.1}dB",
+            reference, target, output.difference
+        );
+        let mut total = output.total;
+        if self.attenuation > 0.0 {
+            text += &format!(
+                "\n\n衰减修正 = 2α(x_目标 − x_参考) = 2 × {} × ({} − {}) = {:.1}dB",
+                self.attenuation, self.target.distance, self.reference.distance, output.attenuation
+            );
+        }
+        if self.block_distance > 0.0 || self.part_distance > 0.0 {
+            let transfer = TransferInput {
+                block_gain: self.block_gain,
+                block_distance: self.block_distance,
+                part_gain: self.part_gain,
+                part_distance: self.part_distance,
+            };
+            let correction = calc::reflector::transfer_correction(&transfer)?;
+            text += &format!(
+                "\n\n传输修正 = (G_工件 − G_试块) − 20lg(x_工件 / x_试块) = ({} − {}) − 20lg({} / {}) = {:.1}dB",
+                self.part_gain, self.block_gain, self.part_distance, self.block_distance, correction
+            );
+            total += correction;
+        }
+        text += &format!(
+            "\n\n**目标反射体达到基准波高需在参考灵敏度基础上{}{:.1}dB**",
+            if total >= 0.0 { "增加" } else { "降低" },
+            total.abs()
+        );
+        if output.near_field_warning {
+            text += &format!("\n\n注意：声程小于3N = {:.1}mm，远场公式误差较大", 3.0 * self.near_field);
+        }
+        Ok(text)
+    }
+
+    fn view_kind_buttons(link: &ComponentLink<Model>, to_msg: fn(ReflectorKind) -> Msg) -> Html {
+        html! {
+            <div class="reflector">
+                <button onclick=link.callback(move |_| to_msg(ReflectorKind::Backwall))>{ "大平底" }</button>
+                <button onclick=link.callback(move |_| to_msg(ReflectorKind::Fbh))>{ "平底孔" }</button>
+                <button onclick=link.callback(move |_| to_msg(ReflectorKind::Sdh))>{ "长横孔" }</button>
+            </div>
+        }
+    }
+
+    fn view_inputs(&self, link: &ComponentLink<Model>) -> Html {
+        html! {
+            <div class="gain-transfer">
+                { view_number_input("gain-transfer", "波长（mm）", self.lambda, link, Msg::UpdateLambda) }
+                { view_number_input("gain-transfer", "近场长度（mm）", self.near_field, link, Msg::UpdateNearField) }
+                <p>{ format!("参考反射体：{}", self.reference.name()) }</p>
+                { GainTransfer::view_kind_buttons(link, Msg::UpdateReferenceKind) }
+                { view_number_input("gain-transfer", "参考反射体直径", self.reference.diameter, link, Msg::UpdateReferenceDiameter) }
+                { view_number_input("gain-transfer", "参考反射体声程", self.reference.distance, link, Msg::UpdateReferenceDistance) }
+                <p>{ format!("目标反射体：{}", self.target.name()) }</p>
+                { GainTransfer::view_kind_buttons(link, Msg::UpdateTargetKind) }
+                { view_number_input("gain-transfer", "目标反射体直径", self.target.diameter, link, Msg::UpdateTargetDiameter) }
+                { view_number_input("gain-transfer", "目标反射体声程", self.target.distance, link, Msg::UpdateTargetDistance) }
+                { view_number_input("gain-transfer", "衰减系数（dB/mm，单程）", self.attenuation, link, Msg::UpdateAttenuation) }
+                { view_number_input("gain-transfer", "试块大平底增益（dB）", self.block_gain, link, Msg::UpdateBlockGain) }
+                { view_number_input("gain-transfer", "试块大平底声程", self.block_distance, link, Msg::UpdateBlockDistance) }
+                { view_number_input("gain-transfer", "工件大平底增益（dB）", self.part_gain, link, Msg::UpdatePartGain) }
+                { view_number_input("gain-transfer", "工件大平底声程", self.part_distance, link, Msg::UpdatePartDistance) }
+            </div>
+        }
+    }
+}
+
+//TOFD页面的两个标签
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TofdTab {
+    Setup,
+    TipDepth,
 }
 
 #[derive(Debug)]
-pub enum Msg {
-    SwitchTo(Scene),
-    AddNew,
-    //探头计算
-    //UpdateFirstName(String),
-    //UpdateLastName(String),
-    UpdateDescription(String),
-    UpdateFrequency(f64),
-    UpdateVelocity(f64),
-    CalcLP,
-    //折射角计算
-    UpdateIncidenceAngleMin(f64),
-    //u32?
-    UpdateIncidenceAngleMax(f64),
-    //u32?
-    UpdateVelocityIncidence(f64),
-    //u32?
-    UpdateVelocityRefraction(f64),
-    //u32?
-    UpdateVelocitySteel(f64),
-    //u32
-    CalcRefraction,
-    //
-    Clear,
+pub struct Tofd {
+    tab: TofdTab,
+    //频率、声速和晶片尺寸可从探头载入
+    frequency: f64,
+    velocity: f64,
+    element_size: f64,
+    angle: f64,
+    thickness: f64,
+    target_depth: f64,
+    pulse_cycles: f64,
+    //深度换算用的探头中心间距，计算布置后自动填入
+    pcs: f64,
+    //上/下端点衍射波相对直通波的时间差，µs，下端点为0时只换算一个深度
+    tip_upper: f64,
+    tip_lower: f64,
+
+    result: String,
+    depth_result: String,
 }
 
-impl Component for Model {
-    type Message = Msg;
-    type Properties = ();
+impl Tofd {
+    fn empty() -> Self {
+        Tofd {
+            tab: TofdTab::Setup,
+            frequency: 0.0,
+            velocity: 0.0,
+            element_size: 0.0,
+            angle: 60.0,
+            thickness: 0.0,
+            target_depth: 0.0,
+            pulse_cycles: 2.0,
+            pcs: 0.0,
+            tip_upper: 0.0,
+            tip_lower: 0.0,
+            result: "".into(),
+            depth_result: "".into(),
+        }
+    }
 
-    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let storage = StorageService::new(Area::Local).expect("存储功能处于关闭状态");
-        let Json(database) = storage.restore(KEY);
-        let database = database.unwrap_or_else(|_| Database { probes: Vec::new() });
-        Model {
-            link,
-            storage,
-            dialog: DialogService::new(),
-            database,
-            scene: Scene::SceneList,
+    fn input(&self) -> TofdInput {
+        TofdInput {
+            frequency: self.frequency,
+            velocity: self.velocity,
+            element_size: self.element_size,
+            angle: self.angle,
+            thickness: self.thickness,
+            //未输入目标深度时按板厚的2/3
+            target_depth: if self.target_depth > 0.0 { self.target_depth } else { self.thickness * 2.0 / 3.0 },
+            pulse_cycles: self.pulse_cycles,
         }
     }
 
-    fn update(&mut self, msg: Self::Message) -> ShouldRender {
-        let mut new_scene = None;
-        match self.scene {
-            Scene::SceneList => match msg {
-                Msg::SwitchTo(Scene::ProbeForm(probe)) => {
-                    new_scene = Some(Scene::ProbeForm(probe));
+    fn setup_text(input: &TofdInput, output: &TofdOutput) -> String {
+        let mut text = format!(
+            "声束交点深度{:.2}mm，折射角{}度\n\n**探头中心间距PCS = 2d·tanθ = {:.1}mm**\n\n| 信号 | 到达时间（不含楔块延时） |\n|---|---|\n| 直通波 | {:.3}µs |\n| 声束交点 | {:.3}µs |\n| 底面波 | {:.3}µs |",
+            input.target_depth, input.angle, output.pcs, output.lateral_time, output.target_time, output.backwall_time
+        );
+        text += &format!(
+            "\n\n脉冲宽度{}周期 = {:.3}µs\n\n近表面盲区{:.2}mm，底面盲区{:.2}mm",
+            input.pulse_cycles, output.pulse_duration, output.dead_zone, output.backwall_dead_zone
+        );
+        text += "\n\n| 深度 | 衍射波时间 | 深度分辨力 |\n|---|---|---|";
+        for row in output.resolution.iter() {
+            text += &format!("\n| {:.2}mm | {:.3}µs | {:.2}mm |", row.depth, row.time, row.resolution);
+        }
+        match (output.spread, output.coverage) {
+            (Some(spread), Some((top, bottom))) => {
+                text += &format!(
+                    "\n\n−12dB扩散半角{:.1}度，两声束在中心线上覆盖深度{:.1}～{:.1}mm",
+                    spread, top, bottom
+                );
+                if top > output.dead_zone || bottom < input.thickness {
+                    text += "\n\n**声束未覆盖全部板厚**，需要增加不同PCS或折射角的扫查";
+                }
+            }
+            _ if input.element_size > 0.0 => text += "\n\n晶片尺寸小于0.70λ，无法计算−12dB扩散角",
+            _ => text += "\n\n未输入晶片尺寸，不计算声束覆盖",
+        }
+        text
+    }
+
+    fn tip_depth_text(&self) -> calc::Result<String> {
+        let upper = calc::tofd::tip_depth(self.velocity, self.pcs, self.tip_upper)?;
+        let mut text = format!(
+            "PCS = {}mm，声速{}m/s\n\nd = √((v(t_直通 + Δt)/2)² − S²)\n\n上端点深度{:.2}mm",
+            self.pcs, self.velocity, upper
+        );
+        if self.tip_lower > 0.0 {
+            let lower = calc::tofd::tip_depth(self.velocity, self.pcs, self.tip_lower)?;
+            text += &format!("\n\n下端点深度{:.2}mm\n\n**缺陷自身高度{:.2}mm**", lower, (lower - upper).abs());
+        }
+        Ok(text)
+    }
+
+    //时间差-深度换算曲线，非线性在近表面最明显
+    fn view_depth_curve(&self) -> Html {
+        let thickness = self.thickness;
+        if self.velocity <= 0.0 || self.pcs <= 0.0 || thickness <= 0.0 {
+            return html! {};
+        }
+        let lateral = calc::tofd::tip_time(self.velocity, self.pcs, 0.0);
+        let points: Vec<(f64, f64)> = (0..=100)
+            .map(|i| {
+                let depth = thickness * i as f64 / 100.0;
+                (calc::tofd::tip_time(self.velocity, self.pcs, depth) - lateral, depth)
+            })
+            .collect();
+        let max = points[points.len() - 1].0;
+        plot::line_chart(
+            &Axis { label: "相对直通波的时间差（µs）", min: 0.0, max },
+            &Axis { label: "深度（mm）", min: 0.0, max: thickness },
+            &[Series {
+                label: format!("PCS {}mm", self.pcs),
+                color: "steelblue",
+                points,
+            }],
+        )
+    }
+
+    fn view_inputs(&self, link: &ComponentLink<Model>) -> Html {
+        let inputs = match self.tab {
+            TofdTab::Setup => html! {
+                <>
+                    { view_number_input("tofd", "频率（MHz）", self.frequency, link, Msg::UpdateFrequency) }
+                    { view_number_input("tofd", "声速（m/s）", self.velocity, link, Msg::UpdateVelocity) }
+                    { view_number_input("tofd", "晶片尺寸", self.element_size, link, Msg::UpdateElementSize) }
+                    { view_number_input("tofd", "折射角", self.angle, link, Msg::UpdateRefractionAngle) }
+                    { view_number_input("tofd", "板厚", self.thickness, link, Msg::UpdateThickness) }
+                    { view_number_input("tofd", "声束交点深度（默认2/3板厚）", self.target_depth, link, Msg::UpdateTargetDepth) }
+                    { view_number_input("tofd", "脉冲周期数", self.pulse_cycles, link, Msg::UpdatePulseCycles) }
+                </>
+            },
+            TofdTab::TipDepth => html! {
+                <>
+                    { view_number_input("tofd", "声速（m/s）", self.velocity, link, Msg::UpdateVelocity) }
+                    { view_number_input("tofd", "探头中心间距PCS", self.pcs, link, Msg::UpdatePcs) }
+                    { view_number_input("tofd", "板厚", self.thickness, link, Msg::UpdateThickness) }
+                    { view_number_input("tofd", "上端点相对直通波的时间差（µs）", self.tip_upper, link, Msg::UpdateTipUpper) }
+                    { view_number_input("tofd", "下端点相对直通波的时间差（µs）", self.tip_lower, link, Msg::UpdateTipLower) }
+                </>
+            },
+        };
+        html! {
+            <div class="tofd">
+                <button onclick=link.callback(|_| Msg::UpdateTofdTab(TofdTab::Setup))>{ "探头布置" }</button>
+                <button onclick=link.callback(|_| Msg::UpdateTofdTab(TofdTab::TipDepth))>{ "深度换算" }</button>
+                { inputs }
+            </div>
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SkipDistance {
+    thickness: f64,
+    angle: f64,
+    //声程读数，为0时只计算跳距
+    sound_path: f64,
+
+    result: String,
+}
+
+impl SkipDistance {
+    fn empty() -> Self {
+        SkipDistance::with_angle(0.0)
+    }
+
+    fn with_angle(angle: f64) -> Self {
+        SkipDistance {
+            thickness: 0.0,
+            angle,
+            sound_path: 0.0,
+            result: "".into(),
+        }
+    }
+
+    fn skip_text(&self) -> String {
+        let input = SkipInput {
+            thickness: self.thickness,
+            angle: self.angle,
+        };
+        let legs = match calc::skip::legs(&input, 3) {
+            Ok(legs) => legs,
+            Err(err) => return err.to_string(),
+        };
+        let mut text = format!("按板厚{}mm、折射角{}度计算", self.thickness, self.angle);
+        text += "\n\n| 次波 | 末端水平距离 | 累计声程 |\n|---|---|---|";
+        for leg in legs.iter() {
+            let name = match leg.leg {
+                1 => "一次波（半跳距）".to_string(),
+                2 => "二次波（全跳距）".to_string(),
+                n => format!("{}次波", n),
+            };
+            text += &format!("\n| {} | {:.2}mm | {:.2}mm |", name, leg.surface_distance, leg.sound_path);
+        }
+        if self.sound_path > 0.0 {
+            match calc::skip::locate(&input, self.sound_path) {
+                Ok(reflector) => {
+                    text += &format!(
+                        "\n\n声程{}mm：位于第{}次波，深度{:.2}mm，水平距离{:.2}mm",
+                        self.sound_path, reflector.leg, reflector.depth, reflector.surface_distance
+                    )
+                }
+                Err(err) => text += &format!("\n\n{}", err),
+            }
+        }
+        text
+    }
+
+    fn view_inputs(&self, link: &ComponentLink<Model>) -> Html {
+        html! {
+            <div class="skip-distance">
+                { view_number_input("skip-distance", "板厚", self.thickness, link, Msg::UpdateThickness) }
+                { view_number_input("skip-distance", "折射角", self.angle, link, Msg::UpdateRefractionAngle) }
+                { view_number_input("skip-distance", "声程读数", self.sound_path, link, Msg::UpdateSoundPath) }
+            </div>
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Pipe {
+    direction: ScanDirection,
+    outer_diameter: f64,
+    thickness: f64,
+    angle: f64,
+    //声程读数，为0时只计算一次波
+    sound_path: f64,
+    //楔块沿曲率方向的尺寸，为0时不检查间隙
+    wedge_size: f64,
+    gap_limit: f64,
+
+    result: String,
+}
+
+impl Pipe {
+    fn with_angle(angle: f64) -> Self {
+        Pipe {
+            direction: ScanDirection::Circumferential,
+            outer_diameter: 0.0,
+            thickness: 0.0,
+            angle,
+            sound_path: 0.0,
+            wedge_size: 0.0,
+            gap_limit: 0.5,
+            result: "".into(),
+        }
+    }
+
+    fn empty() -> Self {
+        Pipe::with_angle(0.0)
+    }
+
+    fn input(&self) -> PipeInput {
+        PipeInput {
+            outer_diameter: self.outer_diameter,
+            thickness: self.thickness,
+            angle: self.angle,
+            direction: self.direction,
+        }
+    }
+
+    fn calc(&self) -> calc::Result<String> {
+        let input = self.input();
+        let geometry = calc::pipe::geometry(&input)?;
+        let mut text = match self.direction {
+            ScanDirection::Axial => format!(
+                "轴向扫查：外径{}mm，壁厚{}mm，折射角{}度\n\n声束平面内内外壁平行，深度和距离与平板相同，内壁入射角{}度",
+                self.outer_diameter, self.thickness, self.angle, self.angle
+            ),
+            ScanDirection::Circumferential => format!(
+                "周向扫查：外径{}mm，壁厚{}mm，折射角{}度\n\n能到达内壁的最大折射角为{:.2}度",
+                self.outer_diameter, self.thickness, self.angle, geometry.max_angle
+            ),
+        };
+        if self.direction == ScanDirection::Circumferential {
+            text += &match geometry.inner_incidence {
+                Some(incidence) => format!(
+                    "\n\n内壁实际入射角{:.2}度（平板模型为{}度）\n\n一次波到内壁：声程{:.2}mm，外壁弧长{:.2}mm（平板模型为{:.2}mm和{:.2}mm）",
+                    incidence,
+                    self.angle,
+                    geometry.leg_path,
+                    geometry.leg_arc,
+                    self.thickness / self.angle.to_radians().cos(),
+                    self.thickness * self.angle.to_radians().tan()
+                ),
+                None => format!(
+                    "\n\n**折射角超过{:.2}度，声束到不了内壁**，最大深度{:.2}mm，声程{:.2}mm后回到外壁",
+                    geometry.max_angle, geometry.max_depth, geometry.leg_path
+                ),
+            };
+        }
+        if self.sound_path > 0.0 {
+            let reflector = calc::pipe::locate(&input, self.sound_path)?;
+            text += &format!(
+                "\n\n声程{}mm：位于第{}次波，深度{:.2}mm，{}{:.2}mm\n\n平板模型：深度{:.2}mm，水平距离{:.2}mm",
+                self.sound_path,
+                reflector.leg,
+                reflector.depth,
+                match self.direction {
+                    ScanDirection::Axial => "轴向距离",
+                    ScanDirection::Circumferential => "外壁弧长",
+                },
+                reflector.arc_distance,
+                reflector.flat_depth,
+                reflector.flat_distance
+            );
+        }
+        if self.wedge_size > 0.0 {
+            let gap = calc::pipe::wedge_gap(self.outer_diameter, self.wedge_size)?;
+            let min_diameter = calc::pipe::min_flat_diameter(self.wedge_size, self.gap_limit)?;
+            text += &format!(
+                "\n\n平底楔块最大间隙 g = a²/(4D) = {}²/(4×{}) = {:.2}mm，限值{}mm",
+                self.wedge_size, self.outer_diameter, gap, self.gap_limit
+            );
+            if gap > self.gap_limit {
+                text += &format!(
+                    "\n\n**间隙超过限值，需要使用曲面楔块**（该楔块尺寸下外径不小于{:.0}mm才可用平底楔块）",
+                    min_diameter
+                );
+            }
+        }
+        Ok(text)
+    }
+
+    fn view_inputs(&self, link: &ComponentLink<Model>) -> Html {
+        let size_name = match self.direction {
+            ScanDirection::Axial => "楔块宽度",
+            ScanDirection::Circumferential => "楔块长度",
+        };
+        html! {
+            <div class="pipe">
+                <button onclick=link.callback(|_| Msg::UpdateScanDirection(ScanDirection::Circumferential))>{ "周向扫查" }</button>
+                <button onclick=link.callback(|_| Msg::UpdateScanDirection(ScanDirection::Axial))>{ "轴向扫查" }</button>
+                { view_number_input("pipe", "管道外径", self.outer_diameter, link, Msg::UpdateOuterDiameter) }
+                { view_number_input("pipe", "壁厚", self.thickness, link, Msg::UpdateThickness) }
+                { view_number_input("pipe", "折射角", self.angle, link, Msg::UpdateRefractionAngle) }
+                { view_number_input("pipe", "声程读数", self.sound_path, link, Msg::UpdateSoundPath) }
+                { view_number_input("pipe", size_name, self.wedge_size, link, Msg::UpdateWedgeSize) }
+                { view_number_input("pipe", "间隙限值", self.gap_limit, link, Msg::UpdateGapLimit) }
+            </div>
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Throughput {
+    elements: usize,
+    sample_rate: f64,
+    bit_depth: u32,
+    //采集时间窗，µs
+    range_gate: f64,
+    prf: f64,
+    angles: usize,
+    averaging: usize,
+    scan_step: f64,
+    //仪器传输上限，MB/s，0表示不限制
+    transfer_rate: f64,
+
+    result: String,
+}
+
+impl Throughput {
+    fn empty() -> Self {
+        Throughput {
+            elements: 0,
+            sample_rate: 0.0,
+            bit_depth: 16,
+            range_gate: 0.0,
+            prf: 0.0,
+            angles: 0,
+            averaging: 1,
+            scan_step: 1.0,
+            transfer_rate: 0.0,
+            result: "".into(),
+        }
+    }
+
+    fn input(&self) -> ThroughputInput {
+        ThroughputInput {
+            elements: self.elements,
+            sample_rate: self.sample_rate,
+            bit_depth: self.bit_depth,
+            range_gate: self.range_gate,
+            prf: self.prf,
+            angles: self.angles,
+            averaging: self.averaging,
+            scan_step: self.scan_step,
+            transfer_rate: if self.transfer_rate > 0.0 { Some(self.transfer_rate) } else { None },
+        }
+    }
+
+    fn throughput_text(output: &ThroughputOutput) -> String {
+        let frame_rate = |mode: &ModeThroughput| {
+            if mode.transfer_limited {
+                format!("{:.1}（受传输速率限制）", mode.frame_rate)
+            } else {
+                format!("{:.1}", mode.frame_rate)
+            }
+        };
+        let (fmc, pwi) = (&output.fmc, &output.pwi);
+        let mut text = format!(
+            "每条A扫描{}个采样点，当前时间窗允许的最大PRF为{:.0}Hz",
+            output.samples, output.max_prf
+        );
+        text += "\n\n| | FMC | PWI |\n|---|---|---|";
+        text += &format!("\n| 每帧发射次数 | {} | {} |", fmc.firings, pwi.firings);
+        text += &format!("\n| 每帧A扫描条数 | {} | {} |", fmc.ascans, pwi.ascans);
+        text += &format!("\n| 每帧数据量（MB） | {:.2} | {:.2} |", fmc.frame_bytes / 1e6, pwi.frame_bytes / 1e6);
+        text += &format!("\n| 帧率（Hz） | {} | {} |", frame_rate(fmc), frame_rate(pwi));
+        text += &format!("\n| 数据率（MB/s） | {:.1} | {:.1} |", fmc.data_rate / 1e6, pwi.data_rate / 1e6);
+        text += &format!("\n| 最大扫查速度（mm/s） | {:.1} | {:.1} |", fmc.scan_speed, pwi.scan_speed);
+        text
+    }
+
+    fn view_inputs(&self, link: &ComponentLink<Model>) -> Html {
+        html! {
+            <div class="throughput">
+                { view_number_input("throughput", "阵元数", self.elements as f64, link, |val| Msg::UpdateElements(val as usize)) }
+                { view_number_input("throughput", "采样频率（MHz）", self.sample_rate, link, Msg::UpdateSampleRate) }
+                { view_number_input("throughput", "采样位数", self.bit_depth as f64, link, |val| Msg::UpdateBitDepth(val as u32)) }
+                { view_number_input("throughput", "时间窗（µs）", self.range_gate, link, Msg::UpdateRangeGate) }
+                { view_number_input("throughput", "PRF（Hz）", self.prf, link, Msg::UpdatePrf) }
+                { view_number_input("throughput", "平面波角度数", self.angles as f64, link, |val| Msg::UpdateAngleCount(val as usize)) }
+                { view_number_input("throughput", "平均次数", self.averaging as f64, link, |val| Msg::UpdateAveraging(val as usize)) }
+                { view_number_input("throughput", "扫查步进（mm）", self.scan_step, link, Msg::UpdateScanStep) }
+                { view_number_input("throughput", "传输速率上限（MB/s）", self.transfer_rate, link, Msg::UpdateTransferRate) }
+            </div>
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Ascan {
+    //CSV只有幅度列或.npy文件时使用的采样频率，MHz
+    sample_rate: f64,
+    //平均次数，0为全部平均
+    averaging: usize,
+    //带通滤波范围，MHz，上限为0时不滤波
+    filter_low: f64,
+    filter_high: f64,
+    detection: Detection,
+    //显示时间范围，µs，终点为0时显示全部
+    view_start: f64,
+    view_end: f64,
+    gate_a: Gate,
+    gate_b: Gate,
+
+    source: String,
+    waveform: Option<Waveform>,
+    //处理后的信号，幅度谱按滤波后、检波前的信号计算
+    signal: Vec<f32>,
+    spectrum: Option<Spectrum>,
+    result: String,
+}
+
+impl Ascan {
+    fn empty() -> Self {
+        Ascan {
+            sample_rate: 100.0,
+            averaging: 0,
+            filter_low: 0.0,
+            filter_high: 0.0,
+            detection: Detection::Rf,
+            view_start: 0.0,
+            view_end: 0.0,
+            gate_a: Gate {
+                start: 0.0,
+                width: 0.0,
+                threshold: 20.0,
+            },
+            gate_b: Gate {
+                start: 0.0,
+                width: 0.0,
+                threshold: 20.0,
+            },
+            source: "".into(),
+            waveform: None,
+            signal: Vec::new(),
+            spectrum: None,
+            result: "".into(),
+        }
+    }
+
+    //TFM演示中当前发射阵元的自发自收A扫描
+    fn from_fmc(fmc: &FmcData, element: usize) -> Self {
+        let mut ascan = Ascan::empty();
+        ascan.source = format!("FMC仿真，阵元{}自发自收", element + 1);
+        ascan.waveform = Some(calc::ascan::from_fmc(fmc, element, element));
+        ascan.process();
+        ascan
+    }
+
+    //CSV按文本读取，其余按.npy读取
+    fn load(&mut self, file: &FileData) {
+        let waveform = if file.name.to_lowercase().ends_with(".npy") {
+            calc::ascan::read_npy(&file.content, self.sample_rate)
+        } else {
+            calc::ascan::parse_csv(&String::from_utf8_lossy(&file.content), self.sample_rate)
+        };
+        match waveform {
+            Ok(waveform) => {
+                self.source = file.name.clone();
+                self.waveform = Some(waveform);
+                self.process();
+            }
+            Err(err) => self.result = err.to_string(),
+        }
+    }
+
+    //平均、滤波、求频谱，最后检波
+    fn process(&mut self) {
+        let waveform = match self.waveform {
+            Some(ref waveform) => waveform,
+            None => {
+                self.result = "请先载入A扫描".into();
+                return;
+            }
+        };
+        let averaged = calc::ascan::average(waveform, self.averaging);
+        let filtered = if self.filter_high > 0.0 {
+            calc::ascan::band_pass(&averaged, waveform.sample_rate, self.filter_low, self.filter_high)
+        } else {
+            Ok(averaged)
+        };
+        let processed = filtered.and_then(|signal| {
+            let spectrum = calc::ascan::spectrum(&signal, waveform.sample_rate)?;
+            Ok((signal, spectrum))
+        });
+        match processed {
+            Ok((signal, spectrum)) => {
+                let count = waveform.acquisitions.len();
+                let averaged = if self.averaging == 0 { count } else { self.averaging.min(count) };
+                self.result = format!(
+                    "{}：{}个采样点，采样频率{:.2}MHz，平均{}次\n\n| 峰值频率 | −6dB下限 | −6dB上限 | 中心频率 | −6dB相对带宽 |\n|---|---|---|---|---|\n| {:.2}MHz | {:.2}MHz | {:.2}MHz | **{:.2}MHz** | {:.1}% |",
+                    self.source,
+                    waveform.samples(),
+                    waveform.sample_rate,
+                    averaged,
+                    spectrum.peak_frequency,
+                    spectrum.lower,
+                    spectrum.upper,
+                    spectrum.centre,
+                    spectrum.bandwidth
+                );
+                self.signal = calc::ascan::detect(&signal, self.detection);
+                self.spectrum = Some(spectrum);
+            }
+            Err(err) => {
+                self.result = err.to_string();
+                self.signal.clear();
+                self.spectrum = None;
+            }
+        }
+    }
+
+    //满屏高度取整条信号的最大幅度
+    fn full_scale(&self) -> f64 {
+        self.signal.iter().fold(0f32, |peak, value| peak.max(value.abs())) as f64
+    }
+
+    fn gate_text(&self) -> String {
+        let waveform = match self.waveform {
+            Some(ref waveform) if !self.signal.is_empty() => waveform,
+            _ => return "".into(),
+        };
+        let read = |gate: &Gate| {
+            calc::ascan::gate(&self.signal, waveform.sample_rate, waveform.time_offset, gate, self.full_scale())
+        };
+        let (a, b) = (read(&self.gate_a), read(&self.gate_b));
+        let row = |name: &str, reading: Option<calc::ascan::GateReading>| match reading {
+            Some(reading) => format!(
+                "\n| {} | {:.3} | {:.1} | {} |",
+                name,
+                reading.peak_time,
+                reading.peak_amplitude,
+                reading.crossing_time.map_or("-".to_string(), |time| format!("{:.3}", time))
+            ),
+            None => "".into(),
+        };
+        if a.is_none() && b.is_none() {
+            return "".into();
+        }
+        let mut text = "| 闸门 | 峰值时间（µs） | 峰值幅度（%满屏） | 过阈值时间（µs） |\n|---|---|---|---|".to_string();
+        text += &row("A", a);
+        text += &row("B", b);
+        if let (Some(a), Some(b)) = (a, b) {
+            text += &format!("\n\nB−A峰值时间差：{:.3}µs", b.peak_time - a.peak_time);
+        }
+        text
+    }
+
+    fn view_chart(&self) -> Html {
+        let waveform = match self.waveform {
+            Some(ref waveform) if !self.signal.is_empty() => waveform,
+            _ => return html! {},
+        };
+        let time = |i: usize| waveform.time_offset + i as f64 / waveform.sample_rate;
+        let end = time(self.signal.len() - 1);
+        let (start, end) = if self.view_end > self.view_start {
+            (self.view_start, self.view_end.min(end))
+        } else {
+            (waveform.time_offset, end)
+        };
+        let full_scale = self.full_scale().max(f64::MIN_POSITIVE);
+        //每段只保留幅度绝对值最大的点，避免折线过密
+        let first = ((start - waveform.time_offset) * waveform.sample_rate).max(0.0) as usize;
+        let last = (((end - waveform.time_offset) * waveform.sample_rate) as usize + 1).min(self.signal.len());
+        let step = ((last.saturating_sub(first)) / 1000).max(1);
+        let points: Vec<(f64, f64)> = (first..last)
+            .step_by(step)
+            .map(|chunk| {
+                let i = (chunk..(chunk + step).min(last))
+                    .fold(chunk, |best, i| if self.signal[i].abs() > self.signal[best].abs() { i } else { best });
+                (time(i), self.signal[i] as f64 / full_scale * 100.0)
+            })
+            .collect();
+        let gate = |label: &str, gate: &Gate, color| Series {
+            label: label.into(),
+            color,
+            points: vec![(gate.start, gate.threshold), (gate.start + gate.width, gate.threshold)],
+        };
+        let mut series = vec![Series {
+            label: self.source.clone(),
+            color: "steelblue",
+            points,
+        }];
+        if self.gate_a.width > 0.0 {
+            series.push(gate("闸门A", &self.gate_a, "firebrick"));
+        }
+        if self.gate_b.width > 0.0 {
+            series.push(gate("闸门B", &self.gate_b, "seagreen"));
+        }
+        let min = if self.detection == Detection::Rf { -100.0 } else { 0.0 };
+        plot::line_chart(
+            &Axis { label: "时间（µs）", min: start, max: end },
+            &Axis { label: "%满屏", min, max: 100.0 },
+            &series,
+        )
+    }
+
+    fn view_spectrum(&self) -> Html {
+        let spectrum = match self.spectrum {
+            Some(ref spectrum) => spectrum,
+            None => return html! {},
+        };
+        let max = (spectrum.upper * 2.0).min((spectrum.magnitude.len() - 1) as f64 * spectrum.resolution);
+        let points = spectrum
+            .magnitude
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| (i as f64 * spectrum.resolution, value))
+            .take_while(|point| point.0 <= max)
+            .collect();
+        plot::line_chart(
+            &Axis { label: "频率（MHz）", min: 0.0, max },
+            &Axis { label: "幅度", min: 0.0, max: 1.0 },
+            &[
+                Series {
+                    label: "幅度谱".into(),
+                    color: "steelblue",
+                    points,
+                },
+                Series {
+                    label: "−6dB".into(),
+                    color: "firebrick",
+                    points: vec![(spectrum.lower, 0.5), (spectrum.upper, 0.5)],
+                },
+            ],
+        )
+    }
+
+    fn view_detection_buttons(link: &ComponentLink<Model>) -> Html {
+        let button = |&(detection, name): &(Detection, &str)| {
+            html! {
+                <button onclick=link.callback(move |_| Msg::UpdateDetection(detection))>{ name }</button>
+            }
+        };
+        let detections = [
+            (Detection::Rf, "射频"),
+            (Detection::Full, "全波检波"),
+            (Detection::Positive, "正半波"),
+            (Detection::Negative, "负半波"),
+            (Detection::Envelope, "希尔伯特包络"),
+        ];
+        html! {
+            <div class="detection">
+                { for detections.iter().map(button) }
+            </div>
+        }
+    }
+
+    //测得的中心频率写回已保存的探头
+    fn view_save_buttons(&self, probes: &[Probe], link: &ComponentLink<Model>) -> Html {
+        if self.spectrum.is_none() {
+            return html! {};
+        }
+        let button = |(i, probe): (usize, &Probe)| {
+            html! {
+                <button onclick=link.callback(move |_| Msg::SaveProbeFrequency(i))>
+                    { format!("写入探头{}频率（原{}MHz）", i + 1, probe.frequency) }
+                </button>
+            }
+        };
+        html! {
+            <div class="probe-buttons">
+                { for probes.iter().enumerate().map(button) }
+            </div>
+        }
+    }
+
+    fn view_inputs(&self, link: &ComponentLink<Model>) -> Html {
+        html! {
+            <div class="ascan">
+                <label>{ "载入A扫描（CSV或.npy）" }
+                    <input type="file" accept=".csv,.txt,.npy"
+                           onchange=link.callback(|value| match value {
+                               ChangeData::Files(files) => match files.iter().next() {
+                                   Some(file) => Msg::LoadAscanFile(file),
+                                   None => Msg::Ignore,
+                               },
+                               _ => Msg::Ignore,
+                           }) />
+                </label>
+                { view_number_input("ascan", "采样频率（MHz，CSV单列或.npy）", self.sample_rate, link, Msg::UpdateSampleRate) }
+                { view_number_input("ascan", "平均次数（0为全部）", self.averaging as f64, link, |val| Msg::UpdateAveraging(val as usize)) }
+                { view_number_input("ascan", "滤波下限（MHz）", self.filter_low, link, Msg::UpdateFilterLow) }
+                { view_number_input("ascan", "滤波上限（MHz，0为不滤波）", self.filter_high, link, Msg::UpdateFilterHigh) }
+                { Ascan::view_detection_buttons(link) }
+                { view_number_input("ascan", "显示起点（µs）", self.view_start, link, Msg::UpdateViewStart) }
+                { view_number_input("ascan", "显示终点（µs）", self.view_end, link, Msg::UpdateViewEnd) }
+                { view_number_input("ascan", "闸门A起点（µs）", self.gate_a.start, link, Msg::UpdateGateAStart) }
+                { view_number_input("ascan", "闸门A宽度（µs）", self.gate_a.width, link, Msg::UpdateGateAWidth) }
+                { view_number_input("ascan", "闸门A阈值（%）", self.gate_a.threshold, link, Msg::UpdateGateAThreshold) }
+                { view_number_input("ascan", "闸门B起点（µs）", self.gate_b.start, link, Msg::UpdateGateBStart) }
+                { view_number_input("ascan", "闸门B宽度（µs）", self.gate_b.width, link, Msg::UpdateGateBWidth) }
+                { view_number_input("ascan", "闸门B阈值（%）", self.gate_b.threshold, link, Msg::UpdateGateBThreshold) }
+            </div>
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct WeldCoverage {
+    bevel: Bevel,
+    thickness: f64,
+    bevel_angle: f64,
+    root_gap: f64,
+    land: f64,
+    cap_width: f64,
+    root_radius: f64,
+    //出射点到焊缝中心线的距离
+    probe_offset: f64,
+    angle_min: f64,
+    angle_max: f64,
+    angle_step: f64,
+
+    coverage: Option<Coverage>,
+    result: String,
+}
+
+impl WeldCoverage {
+    fn empty() -> Self {
+        WeldCoverage::with_angles(0.0, 0.0)
+    }
+
+    fn with_angles(angle_min: f64, angle_max: f64) -> Self {
+        WeldCoverage {
+            bevel: Bevel::SingleV,
+            thickness: 0.0,
+            bevel_angle: 0.0,
+            root_gap: 0.0,
+            land: 0.0,
+            cap_width: 0.0,
+            root_radius: 0.0,
+            probe_offset: 0.0,
+            angle_min,
+            angle_max,
+            angle_step: 0.0,
+            coverage: None,
+            result: "".into(),
+        }
+    }
+
+    fn inputs(&self) -> (WeldInput, CoverageInput) {
+        (
+            WeldInput {
+                bevel: self.bevel,
+                thickness: self.thickness,
+                bevel_angle: self.bevel_angle,
+                root_gap: self.root_gap,
+                land: self.land,
+                cap_width: self.cap_width,
+                root_radius: self.root_radius,
+            },
+            CoverageInput {
+                probe_offset: self.probe_offset,
+                angle_min: self.angle_min,
+                angle_max: self.angle_max,
+                angle_step: self.angle_step,
+            },
+        )
+    }
+
+    fn coverage_text(coverage: &Coverage) -> String {
+        let mut text = "| 熔合面 | 覆盖角度 | 最小法线夹角 |\n|---|---|---|".to_string();
+        let mut uncovered = 0;
+        for (segment, face) in coverage.faces.iter().enumerate() {
+            let hits: Vec<(f64, usize, f64)> = coverage
+                .beams
+                .iter()
+                .flat_map(|beam| {
+                    beam.hits
+                        .iter()
+                        .filter(move |hit| hit.segment == segment)
+                        .map(move |hit| (beam.angle, hit.leg, hit.incidence))
+                })
+                .collect();
+            if hits.is_empty() {
+                uncovered += 1;
+                text += &format!("\n| {} | **未覆盖** | - |", face.name);
+            } else {
+                let angles = hits
+                    .iter()
+                    .map(|(angle, leg, _)| format!("{:.1}度({}次波)", angle, leg))
+                    .collect::<Vec<_>>()
+                    .join("，");
+                let best = hits.iter().map(|hit| hit.2).fold(90.0, f64::min);
+                text += &format!("\n| {} | {} | {:.1}度 |", face.name, angles, best);
+            }
+        }
+        if uncovered > 0 {
+            text = format!("有{}段熔合面未被覆盖，请调整探头位置或角度范围\n\n{}", uncovered, text);
+        }
+        text
+    }
+
+    fn view_section(&self) -> Html {
+        let coverage = match self.coverage {
+            Some(ref coverage) => coverage,
+            None => return html! {},
+        };
+        let t = self.thickness;
+        let x_min = -self.probe_offset - 5.0;
+        let x_max = coverage
+            .faces
+            .iter()
+            .map(|face| face.start.0.max(face.end.0))
+            .fold(0.0, f64::max)
+            .max(self.probe_offset)
+            + 5.0;
+        let mut paths = vec![
+            Path {
+                points: vec![(x_min, 0.0), (x_max, 0.0)],
+                color: "gray",
+                width: 1.0,
+            },
+            Path {
+                points: vec![(x_min, t), (x_max, t)],
+                color: "gray",
+                width: 1.0,
+            },
+            Path {
+                points: coverage.cap.clone(),
+                color: "gray",
+                width: 1.0,
+            },
+        ];
+        for face in coverage.faces.iter() {
+            paths.push(Path {
+                points: vec![face.start, face.end],
+                color: "black",
+                width: 2.0,
+            });
+        }
+        for (i, beam) in coverage.beams.iter().enumerate() {
+            paths.push(Path {
+                points: beam.path.clone(),
+                color: if i % 2 == 0 { "steelblue" } else { "seagreen" },
+                width: 0.8,
+            });
+        }
+        let markers: Vec<Marker> = coverage
+            .beams
+            .iter()
+            .flat_map(|beam| beam.hits.iter())
+            .map(|hit| Marker {
+                point: hit.point,
+                color: "firebrick",
+            })
+            .collect();
+        plot::section((x_min, x_max), (-t * 0.2, t * 1.1), &paths, &markers)
+    }
+
+    fn view_inputs(&self, link: &ComponentLink<Model>) -> Html {
+        let bevel = match self.bevel {
+            Bevel::SingleV => "单V型",
+            Bevel::DoubleV => "双V型",
+            Bevel::J => "J型",
+        };
+        html! {
+            <div class="weld">
+                <button onclick=link.callback(|_| Msg::UpdateBevel(Bevel::SingleV))>{ "单V型" }</button>
+                <button onclick=link.callback(|_| Msg::UpdateBevel(Bevel::DoubleV))>{ "双V型" }</button>
+                <button onclick=link.callback(|_| Msg::UpdateBevel(Bevel::J))>{ "J型" }</button>
+                <p>{ format!("坡口形式：{}", bevel) }</p>
+                { view_number_input("weld", "板厚", self.thickness, link, Msg::UpdateThickness) }
+                { view_number_input("weld", "坡口角（单侧）", self.bevel_angle, link, Msg::UpdateBevelAngle) }
+                { view_number_input("weld", "根部间隙", self.root_gap, link, Msg::UpdateRootGap) }
+                { view_number_input("weld", "钝边高度", self.land, link, Msg::UpdateLand) }
+                { view_number_input("weld", "盖面宽度", self.cap_width, link, Msg::UpdateCapWidth) }
+                { view_number_input("weld", "J型根部半径", self.root_radius, link, Msg::UpdateRootRadius) }
+                { view_number_input("weld", "出射点到焊缝中心距离", self.probe_offset, link, Msg::UpdateProbeOffset) }
+                { view_number_input("weld", "起始折射角", self.angle_min, link, Msg::UpdateAngleStart) }
+                { view_number_input("weld", "终止折射角", self.angle_max, link, Msg::UpdateAngleEnd) }
+                { view_number_input("weld", "角度步进", self.angle_step, link, Msg::UpdateAngleStep) }
+            </div>
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DelayLaw {
+    scan: ScanType,
+    elements: usize,
+    aperture: usize,
+    pitch: f64,
+    wedge_angle: f64,
+    wedge_velocity: f64,
+    first_element_height: f64,
+    material_velocity: f64,
+    angle_start: f64,
+    angle_end: f64,
+    angle_step: f64,
+    focal_depth: f64,
+
+    result: String,
+}
+
+impl DelayLaw {
+    fn empty() -> Self {
+        DelayLaw {
+            scan: ScanType::Sectorial,
+            elements: 0,
+            aperture: 0,
+            pitch: 0.0,
+            wedge_angle: 0.0,
+            wedge_velocity: 0.0,
+            first_element_height: 0.0,
+            material_velocity: 0.0,
+            angle_start: 0.0,
+            angle_end: 0.0,
+            angle_step: 0.0,
+            focal_depth: 0.0,
+            result: "".into(),
+        }
+    }
+
+    fn input(&self) -> DelayLawInput {
+        DelayLawInput {
+            scan: self.scan,
+            elements: self.elements,
+            //未输入孔径时使用全部阵元
+            aperture: if self.aperture == 0 { self.elements } else { self.aperture },
+            pitch: self.pitch,
+            wedge_angle: self.wedge_angle,
+            wedge_velocity: self.wedge_velocity,
+            first_element_height: self.first_element_height,
+            material_velocity: self.material_velocity,
+            angle_start: self.angle_start,
+            angle_end: self.angle_end,
+            angle_step: self.angle_step,
+            focal_depth: self.focal_depth,
+        }
+    }
+
+    //延时表，每行一条聚焦法则，单位ns
+    fn laws_table(laws: &[FocalLaw]) -> String {
+        let aperture = laws.first().map(|law| law.delays.len()).unwrap_or(0);
+        let mut text = "| 法则 | 折射角 | 起始阵元 |".to_string();
+        for i in 1..=aperture {
+            text += &format!(" E{} |", i);
+        }
+        text += "\n|---|---|---|";
+        text += &"---|".repeat(aperture);
+        for (i, law) in laws.iter().enumerate() {
+            text += &format!("\n| {} | {:.1}度 | {} |", i + 1, law.angle, law.first_element);
+            for delay in law.delays.iter() {
+                text += &format!(" {:.1} |", delay);
+            }
+        }
+        text
+    }
+
+    fn view_inputs(&self, materials: &[Material], link: &ComponentLink<Model>) -> Html {
+        let scan = match self.scan {
+            ScanType::Linear => "线扫",
+            ScanType::Sectorial => "扇扫",
+            ScanType::Focused => "聚焦扇扫",
+        };
+        html! {
+            <div class="delay-law">
+                <button onclick=link.callback(|_| Msg::UpdateScanType(ScanType::Linear))>{ "线扫" }</button>
+                <button onclick=link.callback(|_| Msg::UpdateScanType(ScanType::Sectorial))>{ "扇扫" }</button>
+                <button onclick=link.callback(|_| Msg::UpdateScanType(ScanType::Focused))>{ "聚焦扇扫" }</button>
+                <p>{ format!("扫查方式：{}", scan) }</p>
+                { view_number_input("delay-law", "阵元数", self.elements as f64, link, |val| Msg::UpdateElements(val as usize)) }
+                { view_number_input("delay-law", "孔径阵元数", self.aperture as f64, link, |val| Msg::UpdateAperture(val as usize)) }
+                { view_number_input("delay-law", "阵元间距", self.pitch, link, Msg::UpdatePitch) }
+                { view_number_input("delay-law", "楔块角度", self.wedge_angle, link, Msg::UpdateWedgeAngle) }
+                { view_number_input("delay-law", "楔块声速", self.wedge_velocity, link, Msg::UpdateWedgeVelocity) }
+                { view_velocity_select(materials, link, Msg::UpdateWedgeVelocity) }
+                { view_number_input("delay-law", "第一阵元高度", self.first_element_height, link, Msg::UpdateFirstElementHeight) }
+                { view_number_input("delay-law", "工件声速", self.material_velocity, link, Msg::UpdateMaterialVelocity) }
+                { view_velocity_select(materials, link, Msg::UpdateMaterialVelocity) }
+                { view_number_input("delay-law", "起始折射角", self.angle_start, link, Msg::UpdateAngleStart) }
+                { view_number_input("delay-law", "终止折射角", self.angle_end, link, Msg::UpdateAngleEnd) }
+                { view_number_input("delay-law", "角度步进", self.angle_step, link, Msg::UpdateAngleStep) }
+                { view_number_input("delay-law", "聚焦深度", self.focal_depth, link, Msg::UpdateFocalDepth) }
+            </div>
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GratingLobe {
+    frequency: f64,
+    velocity: f64,
+    pitch: f64,
+    elements: usize,
+    //−6dB相对带宽，%
+    bandwidth: f64,
+    steering_min: f64,
+    steering_max: f64,
+    steering_step: f64,
+
+    output: Option<GratingOutput>,
+    result: String,
+}
+
+impl GratingLobe {
+    fn empty() -> Self {
+        GratingLobe {
+            frequency: 0.0,
+            velocity: 0.0,
+            pitch: 0.0,
+            elements: 0,
+            bandwidth: 0.0,
+            steering_min: 0.0,
+            steering_max: 0.0,
+            steering_step: 0.0,
+            output: None,
+            result: "".into(),
+        }
+    }
+
+    fn input(&self) -> GratingInput {
+        GratingInput {
+            frequency: self.frequency,
+            velocity: self.velocity,
+            pitch: self.pitch,
+            elements: self.elements,
+            bandwidth: self.bandwidth,
+            steering_min: self.steering_min,
+            steering_max: self.steering_max,
+            steering_step: self.steering_step,
+        }
+    }
+
+    fn grating_text(&self, output: &GratingOutput) -> String {
+        let mut text = format!(
+            "中心波长为{:.3}mm，带宽上限波长为{:.3}mm\n整个偏转范围内不出现栅瓣的最大间距为{:.3}mm（当前{}mm）",
+            output.lambda, output.lambda_min, output.max_pitch, self.pitch
+        );
+        text += "\n\n| 偏转角 | 栅瓣角度 | 是否安全 |\n|---|---|---|";
+        for row in output.rows.iter() {
+            let lobes = if row.lobes.is_empty() {
+                "-".to_string()
+            } else {
+                row.lobes
+                    .iter()
+                    .map(|lobe| format!("{:.1}度", lobe))
+                    .collect::<Vec<_>>()
+                    .join("，")
+            };
+            let safe = if row.safe { "安全" } else { "**有栅瓣**" };
+            text += &format!("\n| {:.1}度 | {} | {} |", row.steering, lobes, safe);
+        }
+        text
+    }
+
+    //最大偏转角下的阵列因子，中心频率和带宽上限各一条
+    fn view_array_factor(&self) -> Html {
+        let input = self.input();
+        let steering = if self.steering_max.abs() >= self.steering_min.abs() {
+            self.steering_max
+        } else {
+            self.steering_min
+        };
+        let centre = calc::grating::array_factor_curve(&input, steering, false);
+        let upper = calc::grating::array_factor_curve(&input, steering, true);
+        match (self.output.as_ref(), centre, upper) {
+            (Some(_), Ok(centre), Ok(upper)) => plot::line_chart(
+                &Axis { label: "角度（度）", min: -90.0, max: 90.0 },
+                &Axis { label: "dB", min: -60.0, max: 0.0 },
+                &[
+                    Series {
+                        label: format!("{}MHz，偏转{}度", self.frequency, steering),
+                        color: "steelblue",
+                        points: centre,
+                    },
+                    Series {
+                        label: "带宽上限频率".into(),
+                        color: "firebrick",
+                        points: upper,
+                    },
+                ],
+            ),
+            _ => html! {},
+        }
+    }
+
+    fn view_inputs(&self, materials: &[Material], link: &ComponentLink<Model>) -> Html {
+        html! {
+            <div class="grating-lobe">
+                { view_number_input("grating-lobe", "频率", self.frequency, link, Msg::UpdateFrequency) }
+                { view_number_input("grating-lobe", "声速", self.velocity, link, Msg::UpdateVelocity) }
+                { view_velocity_select(materials, link, Msg::UpdateVelocity) }
+                { view_number_input("grating-lobe", "阵元间距", self.pitch, link, Msg::UpdatePitch) }
+                { view_number_input("grating-lobe", "阵元数", self.elements as f64, link, |val| Msg::UpdateElements(val as usize)) }
+                { view_number_input("grating-lobe", "带宽（%）", self.bandwidth, link, Msg::UpdateBandwidth) }
+                { view_number_input("grating-lobe", "起始偏转角", self.steering_min, link, Msg::UpdateAngleStart) }
+                { view_number_input("grating-lobe", "终止偏转角", self.steering_max, link, Msg::UpdateAngleEnd) }
+                { view_number_input("grating-lobe", "角度步进", self.steering_step, link, Msg::UpdateAngleStep) }
+            </div>
+        }
+    }
+}
+
+//成像网格类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GridType {
+    Rectangular,
+    Polar,
+    Polygon,
+}
+
+#[derive(Debug)]
+pub struct Tfm {
+    elements: usize,
+    pitch: f64,
+    frequency: f64,
+    velocity: f64,
+    //−6dB相对带宽，%
+    bandwidth: f64,
+    //采样频率，MHz
+    sample_rate: f64,
+    //散射体坐标"x,z"或"x,z,幅度"，分号或换行分隔
+    scatterers: String,
+    //底面深度，0表示无底面
+    backwall: f64,
+    grid_type: GridType,
+    //矩形网格，多边形区域也在该网格上取像素
+    grid: Grid,
+    polar: PolarGrid,
+    //多边形顶点"x,z"，分号或换行分隔
+    polygon: String,
+    //显示动态范围，dB
+    dynamic_range: f64,
+    //平面波角度范围
+    angle_start: f64,
+    angle_end: f64,
+    angle_step: f64,
+    //多模式TFM：楔块参数，velocity作为纵波声速，backwall作为板厚
+    wedge_angle: f64,
+    wedge_velocity: f64,
+    first_element_height: f64,
+    probe_offset: f64,
+    velocity_shear: f64,
+    mode: TfmMode,
+    //AIM：平面反射体倾角（0为水平）、长度和计算间距
+    reflector_angle: f64,
+    reflector_length: f64,
+    aim_resolution: f64,
+
+    fmc: Option<FmcData>,
+    image: Option<TfmImage>,
+    pwi_image: Option<TfmImage>,
+    pwi_angles: usize,
+    wedge_fmc: Option<FmcData>,
+    mode_image: Option<TfmImage>,
+    aim_maps: Vec<AimMap>,
+    //导入FMC数据时已读取的文件和本次选择的文件数
+    files: Vec<FileData>,
+    file_count: usize,
+    //当前发射阵元
+    frame: usize,
+    result: String,
+}
+
+impl Tfm {
+    fn empty() -> Self {
+        Tfm {
+            elements: 0,
+            pitch: 0.0,
+            frequency: 0.0,
+            velocity: 0.0,
+            bandwidth: 60.0,
+            sample_rate: 50.0,
+            scatterers: "0,20;-5,30".into(),
+            backwall: 40.0,
+            grid_type: GridType::Rectangular,
+            grid: Grid {
+                x_min: -15.0,
+                x_max: 15.0,
+                z_min: 5.0,
+                z_max: 45.0,
+                resolution: 0.2,
+            },
+            polar: PolarGrid {
+                origin: (0.0, 0.0),
+                angle_min: -30.0,
+                angle_max: 30.0,
+                angle_step: 0.25,
+                radius_min: 5.0,
+                radius_max: 45.0,
+                radius_step: 0.2,
+            },
+            polygon: "-10,10;10,10;0,35".into(),
+            dynamic_range: 40.0,
+            angle_start: -10.0,
+            angle_end: 10.0,
+            angle_step: 5.0,
+            wedge_angle: 36.0,
+            wedge_velocity: 2337.0,
+            first_element_height: 6.0,
+            probe_offset: -35.0,
+            velocity_shear: 0.0,
+            mode: TfmMode::parse("T-T").unwrap(),
+            reflector_angle: 90.0,
+            reflector_length: 2.0,
+            aim_resolution: 1.0,
+            fmc: None,
+            image: None,
+            pwi_image: None,
+            pwi_angles: 0,
+            wedge_fmc: None,
+            mode_image: None,
+            aim_maps: Vec::new(),
+            files: Vec::new(),
+            file_count: 0,
+            frame: 0,
+            result: "".into(),
+        }
+    }
+
+    fn simulation_input(&self) -> calc::Result<SimulationInput> {
+        Ok(SimulationInput {
+            elements: self.elements,
+            pitch: self.pitch,
+            frequency: self.frequency,
+            velocity: self.velocity,
+            bandwidth: self.bandwidth,
+            sample_rate: self.sample_rate,
+            scatterers: calc::fmc::parse_scatterers(&self.scatterers)?,
+            backwall: if self.backwall > 0.0 { Some(self.backwall) } else { None },
+        })
+    }
+
+    fn imaging_grid(&self) -> calc::Result<ImagingGrid> {
+        Ok(match self.grid_type {
+            GridType::Rectangular => ImagingGrid::Rectangular(self.grid),
+            GridType::Polar => ImagingGrid::Polar(self.polar),
+            GridType::Polygon => ImagingGrid::Polygon(self.grid, calc::grid::parse_polygon(&self.polygon)?),
+        })
+    }
+
+    fn tfm_input(&self) -> calc::Result<TfmInput> {
+        Ok(TfmInput {
+            pitch: self.pitch,
+            velocity: self.velocity,
+            grid: self.imaging_grid()?,
+        })
+    }
+
+    fn image_text(image: &TfmImage) -> String {
+        let (_, (x, z)) = image.peak();
+        format!(
+            "成像区域共{}个像素，最大幅度位于x = {:.2}mm，z = {:.2}mm",
+            image.pixel_count(),
+            x,
+            z
+        )
+    }
+
+    //网格间距大于λ/5时提示，velocity取成像所用的最低声速
+    fn resolution_text(grid: &ImagingGrid, velocity: f64, frequency: f64) -> String {
+        match calc::probe::wavelength(velocity, frequency) {
+            Ok(lambda) if !grid.fine_enough(lambda) => format!(
+                "\n\n**注意：像素间距{:.3}mm大于λ/5 = {:.3}mm，图像可能丢失细节**",
+                grid.spacing(),
+                lambda / 5.0
+            ),
+            _ => "".into(),
+        }
+    }
+
+    fn view_tfm_image(&self, title: &str, image: &TfmImage) -> Html {
+        let range = if self.dynamic_range > 0.0 { self.dynamic_range } else { 40.0 };
+        let raster = image.raster();
+        plot::image(
+            format!("{}（{}dB）", title, range),
+            raster.x,
+            raster.z,
+            raster.columns,
+            raster.rows,
+            &raster.levels(image.peak().0, range),
+        )
+    }
+
+    //FMC-TFM和PWI图像并排显示
+    fn view_image(&self) -> Html {
+        let tfm = match self.image {
+            Some(ref image) => self.view_tfm_image("FMC-TFM", image),
+            None => html! {
+                <img src="RECONSTRUCTION-TFM-ET.gif"  alt="TFM数据重建" title="TFM数据重建"/>
+            },
+        };
+        let pwi = match self.pwi_image {
+            Some(ref image) => self.view_tfm_image(&format!("PWI（{}个角度）", self.pwi_angles), image),
+            None => html! {
+                <img src="PWI-01.gif"  alt="PWI激发" title="平面波激发"/>
+            },
+        };
+        html! {
+            <div class="compare">
+                <div style="display: flex; flex-wrap: wrap">
+                    { tfm }
+                    { pwi }
+                </div>
+                <img src="FMC-vs-PWI-Table.png"  alt="FMC与PWI对比" title="FMC与PWI对比"/>
+            </div>
+        }
+    }
+
+    //同一组散射体分别做FMC-TFM和PWI成像
+    fn compare(&self) -> calc::Result<(FmcData, TfmImage, PwiData, TfmImage)> {
+        let angles = calc::pwi::angles(self.angle_start, self.angle_end, self.angle_step)?;
+        let fmc = calc::fmc::simulate(&self.simulation_input()?)?;
+        let input = self.tfm_input()?;
+        let tfm_image = calc::tfm::reconstruct(&fmc, &input)?;
+        let pwi = calc::pwi::synthesize(&fmc, self.pitch, self.velocity, &angles)?;
+        let pwi_image = calc::pwi::reconstruct(&pwi, &input)?;
+        Ok((fmc, tfm_image, pwi, pwi_image))
+    }
+
+    fn wedge_array(&self) -> WedgeArray {
+        WedgeArray {
+            elements: self.elements,
+            pitch: self.pitch,
+            wedge_angle: self.wedge_angle,
+            wedge_velocity: self.wedge_velocity,
+            first_element_height: self.first_element_height,
+            probe_offset: self.probe_offset,
+            velocity_longitudinal: self.velocity,
+            velocity_shear: self.velocity_shear,
+            thickness: self.backwall,
+        }
+    }
+
+    fn wedge_simulation_input(&self) -> calc::Result<WedgeSimulationInput> {
+        Ok(WedgeSimulationInput {
+            array: self.wedge_array(),
+            frequency: self.frequency,
+            bandwidth: self.bandwidth,
+            sample_rate: self.sample_rate,
+            scatterers: calc::fmc::parse_scatterers(&self.scatterers)?,
+        })
+    }
+
+    //用已有的楔块FMC数据按当前模式成像
+    fn reconstruct_mode(&mut self) {
+        let image = match self.wedge_fmc {
+            Some(ref fmc) => self
+                .imaging_grid()
+                .and_then(|grid| calc::multimode::reconstruct(fmc, &self.wedge_array(), self.mode, &grid)),
+            None => return,
+        };
+        match image {
+            Ok(image) => {
+                //横波未输入时只能用纵波模式，按两者中较低的声速检查分辨率
+                let velocity = if self.velocity_shear > 0.0 { self.velocity.min(self.velocity_shear) } else { self.velocity };
+                self.result = format!(
+                    "{}模式：{}{}",
+                    self.mode,
+                    Tfm::image_text(&image),
+                    Tfm::resolution_text(&image.grid, velocity, self.frequency)
+                );
+                self.mode_image = Some(image);
+            }
+            Err(err) => {
+                self.result = err.to_string();
+                self.mode_image = None;
+            }
+        }
+    }
+
+    fn aim_input(&self) -> AimInput {
+        AimInput {
+            array: self.wedge_array(),
+            frequency: self.frequency,
+            reflector_angle: self.reflector_angle,
+            reflector_length: self.reflector_length,
+            grid: ImagingGrid::Rectangular(Grid {
+                resolution: self.aim_resolution,
+                ..self.grid
+            }),
+        }
+    }
+
+    //各模式灵敏度相对所有模式中的最大值
+    fn aim_text(maps: &[AimMap]) -> String {
+        let peak = maps.iter().map(|map| map.peak).fold(0.0, f64::max);
+        let db = |value: f64| {
+            if value > 0.0 && peak > 0.0 {
+                format!("{:.1}", 20.0 * (value / peak).log10())
+            } else {
+                "-".to_string()
+            }
+        };
+        let mut text = "| 模式 | 平均灵敏度（dB） | 最大灵敏度（dB） |\n|---|---|---|".to_string();
+        for map in maps.iter() {
+            text += &format!("\n| {} | {} | {} |", map.mode, db(map.mean), db(map.peak));
+        }
+        match calc::aim::recommend(maps) {
+            Some(best) if best.mean > 0.0 => text += &format!("\n\n推荐模式：**{}**", best.mode),
+            _ => text += "\n\n所有模式对该取向的反射体均不敏感",
+        }
+        text
+    }
+
+    //各模式统一色阶，便于比较
+    fn view_aim_maps(&self) -> Html {
+        if self.aim_maps.is_empty() {
+            return html! {
+                <img src="M2M-Gekko-Panther-Custom-Sectorial-TFM-Mode.png"  alt="TFM模式" title="TFM模式"/>
+            };
+        }
+        let peak = self.aim_maps.iter().map(|map| map.image.peak().0).fold(0f32, f32::max);
+        let range = if self.dynamic_range > 0.0 { self.dynamic_range } else { 40.0 };
+        let view_map = |map: &AimMap| {
+            let raster = map.image.raster();
+            plot::image(
+                format!("{}模式AIM（{}dB）", map.mode, range),
+                raster.x,
+                raster.z,
+                raster.columns,
+                raster.rows,
+                &raster.levels(peak, range),
+            )
+        };
+        html! {
+            <div style="display: flex; flex-wrap: wrap">
+                { for self.aim_maps.iter().map(view_map) }
+            </div>
+        }
+    }
+
+    fn view_mode_image(&self) -> Html {
+        match self.mode_image {
+            Some(ref image) => self.view_tfm_image(&format!("{}模式TFM", self.mode), image),
+            None => html! {},
+        }
+    }
+
+    fn view_mode_buttons(link: &ComponentLink<Model>) -> Html {
+        let button = |name: &&str| {
+            let mode = TfmMode::parse(name).unwrap();
+            html! {
+                <button onclick=link.callback(move |_| Msg::UpdateTfmMode(mode))>{ mode.to_string() }</button>
+            }
+        };
+        html! {
+            <div class="tfm-mode">
+                { for calc::multimode::COMMON_MODES.iter().map(button) }
+                <button onclick=link.callback(|_| Msg::SimulateMultiMode)>{ "楔块多模式TFM" }</button>
+                <button onclick=link.callback(|_| Msg::CalcAim)>{ "AIM模式选择" }</button>
+            </div>
+        }
+    }
+
+    fn compare_text(fmc: &FmcData, pwi: &PwiData) -> String {
+        let n = fmc.elements;
+        let m = pwi.angles.len();
+        format!(
+            "| | FMC-TFM | PWI |\n|---|---|---|\n| 发射次数 | {} | {} |\n| A扫描条数 | {} | {} |\n| 数据量（采样点） | {} | {} |\n| 每像素叠加次数 | {} | {} |\n\nPWI角度：{}",
+            n,
+            m,
+            n * n,
+            m * n,
+            n * n * fmc.samples,
+            m * n * pwi.samples,
+            n * n,
+            m * n,
+            pwi.angles.iter().map(|angle| format!("{}度", angle)).collect::<Vec<_>>().join("，")
+        )
+    }
+
+    //数据文件为.npy或原始数据，JSON文件为说明文件
+    fn import_fmc(&self) -> calc::Result<(String, FmcData)> {
+        let sidecar = match self.files.iter().find(|file| file.name.to_lowercase().ends_with(".json")) {
+            Some(file) => Some(calc::fmc_file::parse_sidecar(&String::from_utf8_lossy(&file.content))?),
+            None => None,
+        };
+        let data = self
+            .files
+            .iter()
+            .find(|file| !file.name.to_lowercase().ends_with(".json"))
+            .ok_or_else(|| calc::CalcError::Invalid("请选择FMC数据文件（.npy或int16/float32原始数据）".into()))?;
+        let fmc = if data.name.to_lowercase().ends_with(".npy") {
+            calc::fmc_file::read_npy(&data.content, sidecar.as_ref(), self.sample_rate)?
+        } else {
+            match sidecar {
+                Some(ref sidecar) => calc::fmc_file::read_raw(&data.content, sidecar)?,
+                None => return Err(calc::CalcError::Invalid("原始数据需要同时选择JSON说明文件".into())),
+            }
+        };
+        Ok((data.name.clone(), fmc))
+    }
+
+    fn fmc_text(fmc: &FmcData) -> String {
+        format!(
+            "共{}×{}条A扫描，每条{}个采样点（{:.1}µs）",
+            fmc.elements,
+            fmc.elements,
+            fmc.samples,
+            fmc.samples as f64 / fmc.sample_rate
+        )
+    }
+
+    //当前发射阵元激发时全部阵元接收到的信号
+    fn view_frame(&self, playing: bool, link: &ComponentLink<Model>) -> Html {
+        match self.fmc {
+            Some(ref fmc) => {
+                let traces: Vec<&[f32]> = (0..fmc.elements).map(|rx| fmc.ascan(self.frame, rx)).collect();
+                let play = if playing { "暂停" } else { "播放" };
+                html! {
+                    <div class="fmc">
+                        <p>{ format!("发射阵元{}/{}", self.frame + 1, fmc.elements) }</p>
+                        <button onclick=link.callback(|_| Msg::TogglePlay)>{ play }</button>
+                        <button onclick=link.callback(|_| Msg::ViewAscan)>{ "查看A扫描" }</button>
+                        { plot::fmc_frame(&traces, self.frame, fmc.samples as f64 / fmc.sample_rate) }
+                    </div>
+                }
+            }
+            None => html! {
+                <img src="Acquisition-FMC-ET-01.gif"  alt="TFM数据采集" title="TFM数据采集FMC"/>
+            },
+        }
+    }
+
+    //按网格类型显示对应的参数
+    fn view_grid_inputs(&self, link: &ComponentLink<Model>) -> Html {
+        let rectangular = html! {
+            <>
+                { view_number_input("tfm", "成像区域x最小值", self.grid.x_min, link, Msg::UpdateGridXMin) }
+                { view_number_input("tfm", "成像区域x最大值", self.grid.x_max, link, Msg::UpdateGridXMax) }
+                { view_number_input("tfm", "成像区域z最小值", self.grid.z_min, link, Msg::UpdateGridZMin) }
+                { view_number_input("tfm", "成像区域z最大值", self.grid.z_max, link, Msg::UpdateGridZMax) }
+                { view_number_input("tfm", "像素间距", self.grid.resolution, link, Msg::UpdateResolution) }
+            </>
+        };
+        let inputs = match self.grid_type {
+            GridType::Rectangular => rectangular,
+            GridType::Polar => html! {
+                <>
+                    { view_number_input("tfm", "扇形原点x", self.polar.origin.0, link, Msg::UpdatePolarOriginX) }
+                    { view_number_input("tfm", "扇形原点z", self.polar.origin.1, link, Msg::UpdatePolarOriginZ) }
+                    { view_number_input("tfm", "起始角", self.polar.angle_min, link, Msg::UpdatePolarAngleMin) }
+                    { view_number_input("tfm", "终止角", self.polar.angle_max, link, Msg::UpdatePolarAngleMax) }
+                    { view_number_input("tfm", "角度步进", self.polar.angle_step, link, Msg::UpdatePolarAngleStep) }
+                    { view_number_input("tfm", "最小声程", self.polar.radius_min, link, Msg::UpdatePolarRadiusMin) }
+                    { view_number_input("tfm", "最大声程", self.polar.radius_max, link, Msg::UpdatePolarRadiusMax) }
+                    { view_number_input("tfm", "声程步进", self.polar.radius_step, link, Msg::UpdatePolarRadiusStep) }
+                </>
+            },
+            GridType::Polygon => html! {
+                <>
+                    { rectangular }
+                    <textarea class="tfm"
+                              placeholder="多边形顶点x,z（mm），分号分隔"
+                              value=&self.polygon
+                              oninput=link.callback(|e: InputData| Msg::UpdatePolygon(e.value)) />
+                </>
+            },
+        };
+        html! {
+            <div class="tfm-grid">
+                <button onclick=link.callback(|_| Msg::UpdateGridType(GridType::Rectangular))>{ "矩形网格" }</button>
+                <button onclick=link.callback(|_| Msg::UpdateGridType(GridType::Polar))>{ "扇形网格" }</button>
+                <button onclick=link.callback(|_| Msg::UpdateGridType(GridType::Polygon))>{ "多边形区域" }</button>
+                { inputs }
+            </div>
+        }
+    }
+
+    fn view_inputs(&self, materials: &[Material], link: &ComponentLink<Model>) -> Html {
+        html! {
+            <div class="tfm">
+                { view_number_input("tfm", "阵元数", self.elements as f64, link, |val| Msg::UpdateElements(val as usize)) }
+                { view_number_input("tfm", "阵元间距", self.pitch, link, Msg::UpdatePitch) }
+                { view_number_input("tfm", "频率", self.frequency, link, Msg::UpdateFrequency) }
+                { view_number_input("tfm", "声速", self.velocity, link, Msg::UpdateVelocity) }
+                { view_velocity_select(materials, link, Msg::UpdateVelocity) }
+                { view_number_input("tfm", "带宽（%）", self.bandwidth, link, Msg::UpdateBandwidth) }
+                { view_number_input("tfm", "采样频率（MHz）", self.sample_rate, link, Msg::UpdateSampleRate) }
+                { view_number_input("tfm", "底面深度", self.backwall, link, Msg::UpdateBackwall) }
+                <textarea class="tfm"
+                          placeholder="散射体坐标x,z（mm），分号分隔"
+                          value=&self.scatterers
+                          oninput=link.callback(|e: InputData| Msg::UpdateScatterers(e.value)) />
+                { self.view_grid_inputs(link) }
+                { view_number_input("tfm", "动态范围（dB）", self.dynamic_range, link, Msg::UpdateDynamicRange) }
+                { view_number_input("tfm", "平面波起始角", self.angle_start, link, Msg::UpdateAngleStart) }
+                { view_number_input("tfm", "平面波终止角", self.angle_end, link, Msg::UpdateAngleEnd) }
+                { view_number_input("tfm", "平面波角度步进", self.angle_step, link, Msg::UpdateAngleStep) }
+                { view_number_input("tfm", "横波声速（多模式）", self.velocity_shear, link, Msg::UpdateVelocityShear) }
+                { view_velocity_select(materials, link, Msg::UpdateVelocityShear) }
+                { view_number_input("tfm", "楔块角度", self.wedge_angle, link, Msg::UpdateWedgeAngle) }
+                { view_number_input("tfm", "楔块声速", self.wedge_velocity, link, Msg::UpdateWedgeVelocity) }
+                { view_velocity_select(materials, link, Msg::UpdateWedgeVelocity) }
+                { view_number_input("tfm", "第一阵元高度", self.first_element_height, link, Msg::UpdateFirstElementHeight) }
+                { view_number_input("tfm", "第一阵元水平位置", self.probe_offset, link, Msg::UpdateProbeOffset) }
+                { view_number_input("tfm", "反射体倾角（0为水平）", self.reflector_angle, link, Msg::UpdateReflectorAngle) }
+                { view_number_input("tfm", "反射体长度", self.reflector_length, link, Msg::UpdateReflectorLength) }
+                { view_number_input("tfm", "AIM计算间距", self.aim_resolution, link, Msg::UpdateAimResolution) }
+            </div>
+        }
+    }
+}
+
+impl Renderable for Material {
+    fn render(&self) -> Html {
+        html! {
+            <div class="material">
+                <p>{ format!("Name: {}", self.name) }</p>
+                <p>{ format!("Velocity L: {}", self.velocity_longitudinal) }</p>
+                <p>{ format!("Velocity S: {}", self.velocity_shear) }</p>
+                <p>{ format!("Density: {}", self.density) }</p>
+                <p>{ format!("Impedance: {:.2}", self.impedance()) }</p>
+            </div>
+        }
+    }
+}
+
+//材料库表格
+fn materials_table(materials: &[Material]) -> String {
+    let mut text = "| 材料 | 纵波声速(m/s) | 横波声速(m/s) | 密度(g/cm³) | 声阻抗(MRayl) | 温度系数(m/s/°C) |\n|---|---|---|---|---|---|".to_string();
+    for material in materials.iter() {
+        text += &format!(
+            "\n| {} | {} | {} | {} | {:.2} | {} |",
+            material.name,
+            material.velocity_longitudinal,
+            if material.velocity_shear > 0.0 { material.velocity_shear.to_string() } else { "-".into() },
+            material.density,
+            material.impedance(),
+            if material.temperature_coefficient != 0.0 || material.temperature_coefficient_shear != 0.0 {
+                format!("{} / {}", material.temperature_coefficient, material.temperature_coefficient_shear)
+            } else {
+                "-".into()
+            }
+        );
+    }
+    text
+}
+
+fn view_material_inputs(material: &Material, link: &ComponentLink<Model>) -> Html {
+    html! {
+        <div class="material">
+            <input class="material"
+                   placeholder="材料名称"
+                   value=&material.name
+                   oninput=link.callback(|e: InputData| Msg::UpdateMaterialName(e.value)) />
+            { view_number_input("material", "纵波声速", material.velocity_longitudinal, link, Msg::UpdateVelocityLongitudinal) }
+            { view_number_input("material", "横波声速", material.velocity_shear, link, Msg::UpdateVelocityShear) }
+            { view_number_input("material", "密度", material.density, link, Msg::UpdateDensity) }
+            { view_number_input("material", "纵波声速温度系数（m/s/°C）", material.temperature_coefficient, link, Msg::UpdateTemperatureCoefficient) }
+            { view_number_input("material", "横波声速温度系数（m/s/°C）", material.temperature_coefficient_shear, link, Msg::UpdateTemperatureCoefficientShear) }
+        </div>
+    }
+}
+
+//从已保存的探头载入参数
+fn view_probe_buttons(probes: &[Probe], link: &ComponentLink<Model>) -> Html {
+    let button = |(i, probe): (usize, &Probe)| {
+        html! {
+            <button onclick=link.callback(move |_| Msg::LoadProbe(i))>
+                { format!("载入探头{}（{}MHz）", i + 1, probe.frequency) }
+            </button>
+        }
+    };
+    html! {
+        <div class="probe-buttons">
+            { for probes.iter().enumerate().map(button) }
+        </div>
+    }
+}
+
+//值为0时返回空字符串，输入框显示提示文字
+fn number_text(value: f64) -> String {
+    if value == 0.0 {
+        String::new()
+    } else {
+        value.to_string()
+    }
+}
+
+//数字输入框，无法解析时按0处理
+fn view_number_input(
+    class: &'static str,
+    placeholder: &'static str,
+    value: f64,
+    link: &ComponentLink<Model>,
+    to_msg: fn(f64) -> Msg,
+) -> Html {
+    html! {
+        <input class=class
+               placeholder=placeholder
+               value=number_text(value)
+               oninput=link.callback(move |e: InputData| to_msg(e.value.parse().unwrap_or(0.0))) />
+    }
+}
+
+//从材料库选择声速，每种材料按纵波/横波分别列出
+fn view_velocity_select(materials: &[Material], link: &ComponentLink<Model>, to_msg: fn(f64) -> Msg) -> Html {
+    let mut options = Vec::new();
+    for material in materials.iter() {
+        options.push((format!("{} 纵波 {}m/s", material.name, material.velocity_longitudinal), material.velocity_longitudinal));
+        if material.velocity_shear > 0.0 {
+            options.push((format!("{} 横波 {}m/s", material.name, material.velocity_shear), material.velocity_shear));
+        }
+    }
+    let labels: Vec<String> = options.iter().map(|(label, _)| label.clone()).collect();
+    let velocities: Vec<f64> = options.iter().map(|&(_, velocity)| velocity).collect();
+    html! {
+        <select class="material"
+                onchange=link.callback(move |e: ChangeData| match e {
+                    //第0项为提示文字
+                    ChangeData::Select(select) => match select.selected_index() {
+                        Some(i) if i > 0 && (i as usize) <= velocities.len() => to_msg(velocities[i as usize - 1]),
+                        _ => Msg::Ignore,
+                    },
+                    _ => Msg::Ignore,
+                })>
+            <option value="" hidden=true>{ "材料库" }</option>
+            { for labels.iter().map(|label| html! { <option>{ label }</option> }) }
+        </select>
+    }
+}
+
+/// 从材料库同时填入纵波、横波声速和密度
+fn view_material_select(prompt: &str, materials: &[Material], link: &ComponentLink<Model>, to_msg: fn(Material) -> Msg) -> Html {
+    let options = materials.to_vec();
+    html! {
+        <select class="material"
+                onchange=link.callback(move |e: ChangeData| match e {
+                    ChangeData::Select(select) => match select.selected_index() {
+                        Some(i) if i > 0 && (i as usize) <= options.len() => to_msg(options[i as usize - 1].clone()),
+                        _ => Msg::Ignore,
+                    },
+                    _ => Msg::Ignore,
+                })>
+            <option value="" hidden=true>{ format!("{}材料", prompt) }</option>
+            { for materials.iter().map(|material| html! { <option>{ &material.name }</option> }) }
+        </select>
+    }
+}
+
+fn angle_text(angle: Option<f64>) -> String {
+    match angle {
+        Some(angle) => format!("{:.2}度", angle),
+        None => "无透射波（超过临界角）".into(),
+    }
+}
+
+fn spread_text(angle: Option<f64>) -> String {
+    match angle {
+        Some(angle) => format!("{:.2}度", angle),
+        None => "无定义（晶片尺寸过小）".into(),
+    }
+}
+
+fn length_text(length: Option<f64>) -> String {
+    match length {
+        Some(length) => format!("{:.2}mm", length),
+        None => "-".into(),
+    }
+}
+
+#[derive(Debug)]
+pub enum Scene {
+    SceneList,
+    ProbeForm(Probe),
+    //BeamAngleForm(BeamAngle),
+    TFMPWIForm(Tfm),
+    RefractionAngle(BeamAngle),
+    DelayLawForm(DelayLaw),
+    GratingLobeForm(GratingLobe),
+    MaterialForm(Material),
+    WedgeForm(Wedge),
+    SkipDistanceForm(SkipDistance),
+    PipeForm(Pipe),
+    WeldCoverageForm(WeldCoverage),
+    ThroughputForm(Throughput),
+    AscanForm(Ascan),
+    DacForm(Dac),
+    GainTransferForm(GainTransfer),
+    TofdForm(Tofd),
+    Settings,
+}
+
+pub struct Model {
+    link: ComponentLink<Self>,
+    storage: StorageService,
+    dialog: DialogService,
+    database: Database,
+    scene: Scene,
+    interval: IntervalService,
+    //FMC逐个发射阵元的动画，切换场景时释放
+    animation: Option<IntervalTask>,
+    reader: ReaderService,
+    //正在读取的文件，切换场景时释放
+    reading: Vec<ReaderTask>,
+}
+
+#[derive(Debug)]
+pub enum Msg {
+    SwitchTo(Scene),
+    AddNew,
+    //探头计算
+    //UpdateFirstName(String),
+    //UpdateLastName(String),
+    UpdateDescription(String),
+    UpdateFrequency(f64),
+    UpdateVelocity(f64),
+    UpdateElementSize(f64),
+    UpdateFocalDepth(f64),
+    CalcLP,
+    //折射角计算
+    UpdateIncidenceAngleMin(f64),
+    //u32?
+    UpdateIncidenceAngleMax(f64),
+    //u32?
+    UpdateVelocityIncidence(f64),
+    //u32?
+    UpdateVelocityRefraction(f64),
+    //u32?
+    UpdateVelocityShear(f64),
+    //u32?
+    UpdateVelocitySteel(f64),
+    UpdateVelocityIncidenceShear(f64),
+    UpdateDensityIncidence(f64),
+    UpdateDensityMedium(f64),
+    SelectIncidenceMaterial(Material),
+    SelectMediumMaterial(Material),
+    UpdateCalibrationTemperature(f64),
+    UpdateInspectionTemperature(f64),
+    UpdateTemperatureIncidence(f64),
+    UpdateTemperatureMedium(f64),
+    UpdateTemperatureMediumShear(f64),
+    UpdateTemperatureSteel(f64),
+    UpdateTemperatureCoefficient(f64),
+    UpdateTemperatureCoefficientShear(f64),
+    //u32
+    CalcRefraction,
+    //延时法则计算
+    UpdateScanType(ScanType),
+    UpdateElements(usize),
+    UpdateAperture(usize),
+    UpdatePitch(f64),
+    UpdateWedgeAngle(f64),
+    UpdateWedgeVelocity(f64),
+    UpdateFirstElementHeight(f64),
+    UpdateMaterialVelocity(f64),
+    UpdateAngleStart(f64),
+    UpdateAngleEnd(f64),
+    UpdateAngleStep(f64),
+    CalcDelayLaw,
+    //栅瓣计算
+    LoadProbe(usize),
+    UpdateBandwidth(f64),
+    CalcGratingLobe,
+    //材料库
+    UpdateMaterialName(String),
+    UpdateVelocityLongitudinal(f64),
+    UpdateDensity(f64),
+    //楔块
+    UpdatePrimaryOffset(f64),
+    UpdateFrontLength(f64),
+    CalcWedge,
+    //跳距
+    UpdateThickness(f64),
+    UpdateRefractionAngle(f64),
+    UpdateSoundPath(f64),
+    CalcSkip,
+    //焊缝覆盖
+    UpdateBevel(Bevel),
+    UpdateBevelAngle(f64),
+    UpdateRootGap(f64),
+    UpdateLand(f64),
+    UpdateCapWidth(f64),
+    UpdateRootRadius(f64),
+    UpdateProbeOffset(f64),
+    CalcWeldCoverage,
+    //FMC仿真
+    UpdateSampleRate(f64),
+    UpdateScatterers(String),
+    UpdateBackwall(f64),
+    SimulateFmc,
+    TogglePlay,
+    Tick,
+    //导入FMC数据文件
+    LoadFmcFiles(Vec<File>),
+    FmcFileLoaded(FileData),
+    //A扫描
+    ViewAscan,
+    LoadAscanFile(File),
+    AscanFileLoaded(FileData),
+    UpdateFilterLow(f64),
+    UpdateFilterHigh(f64),
+    UpdateDetection(Detection),
+    UpdateViewStart(f64),
+    UpdateViewEnd(f64),
+    UpdateGateAStart(f64),
+    UpdateGateAWidth(f64),
+    UpdateGateAThreshold(f64),
+    UpdateGateBStart(f64),
+    UpdateGateBWidth(f64),
+    UpdateGateBThreshold(f64),
+    ProcessAscan,
+    SaveProbeFrequency(usize),
+    //DAC/TCG
+    UpdateCalibrationReflector(CalibrationReflector),
+    UpdateReflectorDiameter(f64),
+    UpdateReferenceHeight(f64),
+    UpdateDacPoints(String),
+    UpdateDacUnit(bool),
+    CalcDac,
+    SaveDac(usize),
+    LoadDac(usize, usize),
+    //管道曲面修正
+    UpdateScanDirection(ScanDirection),
+    UpdateOuterDiameter(f64),
+    UpdateWedgeSize(f64),
+    UpdateGapLimit(f64),
+    CalcPipe,
+    //反射体增益换算
+    UpdateLambda(f64),
+    UpdateNearField(f64),
+    UpdateReferenceKind(ReflectorKind),
+    UpdateReferenceDiameter(f64),
+    UpdateReferenceDistance(f64),
+    UpdateTargetKind(ReflectorKind),
+    UpdateTargetDiameter(f64),
+    UpdateTargetDistance(f64),
+    UpdateAttenuation(f64),
+    UpdateBlockGain(f64),
+    UpdateBlockDistance(f64),
+    UpdatePartGain(f64),
+    UpdatePartDistance(f64),
+    CalcGainTransfer,
+    //TOFD
+    UpdateTofdTab(TofdTab),
+    UpdateTargetDepth(f64),
+    UpdatePulseCycles(f64),
+    UpdatePcs(f64),
+    UpdateTipUpper(f64),
+    UpdateTipLower(f64),
+    CalcTofd,
+    CalcTipDepth,
+    //TFM成像
+    UpdateGridXMin(f64),
+    UpdateGridXMax(f64),
+    UpdateGridZMin(f64),
+    UpdateGridZMax(f64),
+    UpdateGridType(GridType),
+    UpdatePolarOriginX(f64),
+    UpdatePolarOriginZ(f64),
+    UpdatePolarAngleMin(f64),
+    UpdatePolarAngleMax(f64),
+    UpdatePolarAngleStep(f64),
+    UpdatePolarRadiusMin(f64),
+    UpdatePolarRadiusMax(f64),
+    UpdatePolarRadiusStep(f64),
+    UpdatePolygon(String),
+    UpdateResolution(f64),
+    UpdateDynamicRange(f64),
+    ReconstructTfm,
+    SimulatePwi,
+    UpdateTfmMode(TfmMode),
+    SimulateMultiMode,
+    UpdateReflectorAngle(f64),
+    UpdateReflectorLength(f64),
+    UpdateAimResolution(f64),
+    CalcAim,
+    //FMC/PWI数据率
+    UpdateBitDepth(u32),
+    UpdateRangeGate(f64),
+    UpdatePrf(f64),
+    UpdateAngleCount(usize),
+    UpdateAveraging(usize),
+    UpdateScanStep(f64),
+    UpdateTransferRate(f64),
+    CalcThroughput,
+    //下拉框提示项等无需处理的操作
+    Ignore,
+    //
+    Clear,
+}
+
+impl Model {
+    //内置材料在前，自定义材料在后
+    fn materials(&self) -> Vec<Material> {
+        let mut materials = material::presets();
+        materials.extend(self.database.materials.iter().cloned());
+        materials
+    }
+}
+
+impl Component for Model {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let storage = StorageService::new(Area::Local).expect("存储功能处于关闭状态");
+        let Json(database) = storage.restore(KEY);
+        let database = database.unwrap_or_else(|_| Database {
+            probes: Vec::new(),
+            materials: Vec::new(),
+            wedges: Vec::new(),
+        });
+        Model {
+            link,
+            storage,
+            dialog: DialogService::new(),
+            database,
+            scene: Scene::SceneList,
+            interval: IntervalService::new(),
+            animation: None,
+            reader: ReaderService::new(),
+            reading: Vec::new(),
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        if let Msg::Ignore = msg {
+            return false;
+        }
+        let mut new_scene = None;
+        match self.scene {
+            Scene::SceneList => match msg {
+                Msg::SwitchTo(Scene::ProbeForm(probe)) => {
+                    new_scene = Some(Scene::ProbeForm(probe));
+                }
+                Msg::SwitchTo(Scene::RefractionAngle(beam_abngle)) => {
+                    new_scene = Some(Scene::RefractionAngle(beam_abngle));
+                }
+                Msg::SwitchTo(Scene::TFMPWIForm(tfm)) => {
+                    new_scene = Some(Scene::TFMPWIForm(tfm));
+                }
+                Msg::SwitchTo(Scene::DelayLawForm(delay_law)) => {
+                    new_scene = Some(Scene::DelayLawForm(delay_law));
+                }
+                Msg::SwitchTo(Scene::GratingLobeForm(grating_lobe)) => {
+                    new_scene = Some(Scene::GratingLobeForm(grating_lobe));
+                }
+                Msg::SwitchTo(Scene::MaterialForm(material)) => {
+                    new_scene = Some(Scene::MaterialForm(material));
+                }
+                Msg::SwitchTo(Scene::WedgeForm(wedge)) => {
+                    new_scene = Some(Scene::WedgeForm(wedge));
+                }
+                Msg::SwitchTo(Scene::SkipDistanceForm(skip_distance)) => {
+                    new_scene = Some(Scene::SkipDistanceForm(skip_distance));
+                }
+                Msg::SwitchTo(Scene::PipeForm(pipe)) => {
+                    new_scene = Some(Scene::PipeForm(pipe));
+                }
+                Msg::SwitchTo(Scene::WeldCoverageForm(weld_coverage)) => {
+                    new_scene = Some(Scene::WeldCoverageForm(weld_coverage));
+                }
+                Msg::SwitchTo(Scene::ThroughputForm(throughput)) => {
+                    new_scene = Some(Scene::ThroughputForm(throughput));
+                }
+                Msg::SwitchTo(Scene::AscanForm(ascan)) => {
+                    new_scene = Some(Scene::AscanForm(ascan));
+                }
+                Msg::SwitchTo(Scene::DacForm(dac)) => {
+                    new_scene = Some(Scene::DacForm(dac));
+                }
+                Msg::SwitchTo(Scene::GainTransferForm(gain_transfer)) => {
+                    new_scene = Some(Scene::GainTransferForm(gain_transfer));
+                }
+                Msg::SwitchTo(Scene::TofdForm(tofd)) => {
+                    new_scene = Some(Scene::TofdForm(tofd));
+                }
+                Msg::SwitchTo(Scene::Settings) => {
+                    new_scene = Some(Scene::Settings);
+                }
+                unexpected => {
+                    panic!(
+                        "Unexpected message when probes list shown: {:?}",
+                        unexpected
+                    );
+                }
+            },
+            Scene::ProbeForm(ref mut probe) => match msg {
+                Msg::UpdateFrequency(val) => {
+                    probe.frequency = val;
+                }
+                Msg::UpdateVelocity(val) => {
+                    probe.velocity = val;
+                }
+                Msg::UpdateElementSize(val) => {
+                    probe.element_size = val;
+                }
+                Msg::UpdateFocalDepth(val) => {
+                    probe.focal_depth = val;
+                }
+                Msg::UpdateElements(val) => {
+                    probe.elements = val;
+                }
+                Msg::UpdatePitch(val) => {
+                    probe.element_pitch = val;
+                }
+                Msg::UpdateDescription(val) => {
+                    println!("Input: {}", val);
+                    probe.description = val;
+                }
+                Msg::CalcLP => {
+                    probe.recalc();
+                }
+
+                Msg::AddNew => {
+                    let mut new_probe = Probe::empty();
+                    ::std::mem::swap(probe, &mut new_probe);
+                    self.database.probes.push(new_probe);
+                    self.storage.store(KEY, Json(&self.database));
+                }
+                Msg::SwitchTo(Scene::SceneList) => {
+                    new_scene = Some(Scene::SceneList);
+                }
+                unexpected => {
+                    panic!(
+                        "Unexpected message during new probe editing: {:?}",
+                        unexpected
+                    );
+                    //错误处理需要更人性化
+                }
+            },
+            Scene::TFMPWIForm(ref mut tfm) => match msg {
+                Msg::LoadProbe(i) => {
+                    if let Some(probe) = self.database.probes.get(i) {
+                        tfm.frequency = probe.frequency;
+                        tfm.velocity = probe.velocity;
+                        tfm.pitch = probe.array_pitch();
+                        if probe.elements > 0 {
+                            tfm.elements = probe.elements;
+                        }
+                    }
+                }
+                Msg::UpdateElements(val) => {
+                    tfm.elements = val;
+                }
+                Msg::UpdatePitch(val) => {
+                    tfm.pitch = val;
+                }
+                Msg::UpdateFrequency(val) => {
+                    tfm.frequency = val;
+                }
+                Msg::UpdateVelocity(val) => {
+                    tfm.velocity = val;
+                }
+                Msg::UpdateBandwidth(val) => {
+                    tfm.bandwidth = val;
+                }
+                Msg::UpdateSampleRate(val) => {
+                    tfm.sample_rate = val;
+                }
+                Msg::UpdateScatterers(val) => {
+                    tfm.scatterers = val;
+                }
+                Msg::UpdateBackwall(val) => {
+                    tfm.backwall = val;
+                }
+                Msg::UpdateGridXMin(val) => {
+                    tfm.grid.x_min = val;
+                }
+                Msg::UpdateGridXMax(val) => {
+                    tfm.grid.x_max = val;
+                }
+                Msg::UpdateGridZMin(val) => {
+                    tfm.grid.z_min = val;
+                }
+                Msg::UpdateGridZMax(val) => {
+                    tfm.grid.z_max = val;
+                }
+                Msg::UpdateResolution(val) => {
+                    tfm.grid.resolution = val;
+                }
+                Msg::UpdateGridType(grid_type) => {
+                    tfm.grid_type = grid_type;
+                }
+                Msg::UpdatePolarOriginX(val) => {
+                    tfm.polar.origin.0 = val;
+                }
+                Msg::UpdatePolarOriginZ(val) => {
+                    tfm.polar.origin.1 = val;
+                }
+                Msg::UpdatePolarAngleMin(val) => {
+                    tfm.polar.angle_min = val;
+                }
+                Msg::UpdatePolarAngleMax(val) => {
+                    tfm.polar.angle_max = val;
+                }
+                Msg::UpdatePolarAngleStep(val) => {
+                    tfm.polar.angle_step = val;
+                }
+                Msg::UpdatePolarRadiusMin(val) => {
+                    tfm.polar.radius_min = val;
+                }
+                Msg::UpdatePolarRadiusMax(val) => {
+                    tfm.polar.radius_max = val;
+                }
+                Msg::UpdatePolarRadiusStep(val) => {
+                    tfm.polar.radius_step = val;
+                }
+                Msg::UpdatePolygon(val) => {
+                    tfm.polygon = val;
+                }
+                Msg::UpdateDynamicRange(val) => {
+                    tfm.dynamic_range = val;
+                }
+                Msg::ReconstructTfm => match tfm.fmc {
+                    Some(ref fmc) => match tfm.tfm_input().and_then(|input| calc::tfm::reconstruct(fmc, &input)) {
+                        Ok(image) => {
+                            tfm.result = Tfm::fmc_text(fmc)
+                                + "\n\n"
+                                + &Tfm::image_text(&image)
+                                + &Tfm::resolution_text(&image.grid, tfm.velocity, tfm.frequency);
+                            tfm.image = Some(image);
+                        }
+                        Err(err) => {
+                            tfm.result = err.to_string();
+                            tfm.image = None;
+                        }
+                    },
+                    None => {
+                        tfm.result = "请先生成FMC数据".into();
+                    }
+                },
+                Msg::SimulateFmc => {
+                    let fmc = tfm.simulation_input().and_then(|input| calc::fmc::simulate(&input));
+                    match fmc {
+                        Ok(fmc) => {
+                            tfm.result = Tfm::fmc_text(&fmc);
+                            tfm.fmc = Some(fmc);
+                            tfm.image = None;
+                            tfm.pwi_image = None;
+                        }
+                        Err(err) => {
+                            tfm.result = err.to_string();
+                            tfm.fmc = None;
+                            self.animation = None;
+                        }
+                    }
+                    tfm.frame = 0;
+                }
+                Msg::UpdateAngleStart(val) => {
+                    tfm.angle_start = val;
+                }
+                Msg::UpdateAngleEnd(val) => {
+                    tfm.angle_end = val;
+                }
+                Msg::UpdateAngleStep(val) => {
+                    tfm.angle_step = val;
+                }
+                Msg::SimulatePwi => {
+                    match tfm.compare() {
+                        Ok((fmc, tfm_image, pwi, pwi_image)) => {
+                            tfm.result = Tfm::compare_text(&fmc, &pwi)
+                                + &Tfm::resolution_text(&tfm_image.grid, tfm.velocity, tfm.frequency);
+                            tfm.pwi_angles = pwi.angles.len();
+                            tfm.fmc = Some(fmc);
+                            tfm.image = Some(tfm_image);
+                            tfm.pwi_image = Some(pwi_image);
+                        }
+                        Err(err) => {
+                            tfm.result = err.to_string();
+                            tfm.pwi_image = None;
+                        }
+                    }
+                    tfm.frame = 0;
+                }
+                Msg::UpdateVelocityShear(val) => {
+                    tfm.velocity_shear = val;
+                }
+                Msg::UpdateWedgeAngle(val) => {
+                    tfm.wedge_angle = val;
+                }
+                Msg::UpdateWedgeVelocity(val) => {
+                    tfm.wedge_velocity = val;
+                }
+                Msg::UpdateFirstElementHeight(val) => {
+                    tfm.first_element_height = val;
+                }
+                Msg::UpdateProbeOffset(val) => {
+                    tfm.probe_offset = val;
+                }
+                Msg::UpdateTfmMode(mode) => {
+                    tfm.mode = mode;
+                    tfm.reconstruct_mode();
+                }
+                Msg::UpdateReflectorAngle(val) => {
+                    tfm.reflector_angle = val;
+                }
+                Msg::UpdateReflectorLength(val) => {
+                    tfm.reflector_length = val;
+                }
+                Msg::UpdateAimResolution(val) => {
+                    tfm.aim_resolution = val;
+                }
+                Msg::CalcAim => {
+                    let modes: Vec<TfmMode> = calc::multimode::COMMON_MODES
+                        .iter()
+                        .map(|name| TfmMode::parse(name).unwrap())
+                        .collect();
+                    match calc::aim::aim(&tfm.aim_input(), &modes) {
+                        Ok(maps) => {
+                            tfm.result = Tfm::aim_text(&maps);
+                            tfm.aim_maps = maps;
+                        }
+                        Err(err) => {
+                            tfm.result = err.to_string();
+                            tfm.aim_maps.clear();
+                        }
+                    }
+                }
+                Msg::SimulateMultiMode => {
+                    match tfm.wedge_simulation_input().and_then(|input| calc::multimode::simulate(&input)) {
+                        Ok(fmc) => {
+                            tfm.wedge_fmc = Some(fmc);
+                            tfm.reconstruct_mode();
+                        }
+                        Err(err) => {
+                            tfm.result = err.to_string();
+                            tfm.wedge_fmc = None;
+                            tfm.mode_image = None;
+                        }
+                    }
+                }
+                Msg::LoadFmcFiles(files) => {
+                    tfm.files.clear();
+                    tfm.file_count = files.len();
+                    self.reading.clear();
+                    for file in files {
+                        let callback = self.link.callback(Msg::FmcFileLoaded);
+                        match self.reader.read_file(file, callback) {
+                            Ok(task) => self.reading.push(task),
+                            Err(err) => {
+                                tfm.result = format!("无法读取文件：{}", err);
+                                tfm.file_count = 0;
+                                self.reading.clear();
+                                break;
+                            }
+                        }
+                    }
+                }
+                Msg::FmcFileLoaded(file) => {
+                    tfm.files.push(file);
+                    if tfm.files.len() == tfm.file_count {
+                        self.reading.clear();
+                        match tfm.import_fmc() {
+                            Ok((name, fmc)) => {
+                                tfm.result = format!(
+                                    "已导入{}：{}，时间偏移{}µs",
+                                    name,
+                                    Tfm::fmc_text(&fmc),
+                                    fmc.time_offset
+                                );
+                                tfm.elements = fmc.elements;
+                                tfm.sample_rate = fmc.sample_rate;
+                                tfm.fmc = Some(fmc);
+                                tfm.image = None;
+                                tfm.pwi_image = None;
+                            }
+                            Err(err) => {
+                                tfm.result = err.to_string();
+                            }
+                        }
+                        tfm.files.clear();
+                        tfm.frame = 0;
+                    }
+                }
+                Msg::ViewAscan => {
+                    if let Some(ref fmc) = tfm.fmc {
+                        new_scene = Some(Scene::AscanForm(Ascan::from_fmc(fmc, tfm.frame)));
+                    }
+                }
+                Msg::TogglePlay => {
+                    if self.animation.is_some() {
+                        self.animation = None;
+                    } else if tfm.fmc.is_some() {
+                        let callback = self.link.callback(|_| Msg::Tick);
+                        self.animation = Some(self.interval.spawn(Duration::from_millis(300), callback));
+                    }
+                }
+                Msg::Tick => match tfm.fmc {
+                    Some(ref fmc) => {
+                        tfm.frame = (tfm.frame + 1) % fmc.elements;
+                    }
+                    None => {
+                        self.animation = None;
+                    }
+                },
+                Msg::SwitchTo(Scene::SceneList) => {
+                    new_scene = Some(Scene::SceneList);
+                }
+                unexpected => {
+                    panic!("未知参数，TFM演示模块{:?}", unexpected);
+                }
+            },
+            Scene::RefractionAngle(ref mut beam_angle) => match msg {
+                Msg::SwitchTo(Scene::SceneList) => {
+                    new_scene = Some(Scene::SceneList);
+                }
+                Msg::SwitchTo(Scene::SkipDistanceForm(skip_distance)) => {
+                    new_scene = Some(Scene::SkipDistanceForm(skip_distance));
+                }
+                Msg::SwitchTo(Scene::PipeForm(pipe)) => {
+                    new_scene = Some(Scene::PipeForm(pipe));
+                }
+                Msg::SwitchTo(Scene::WeldCoverageForm(weld_coverage)) => {
+                    new_scene = Some(Scene::WeldCoverageForm(weld_coverage));
+                }
+                Msg::SwitchTo(Scene::WedgeForm(wedge)) => {
+                    new_scene = Some(Scene::WedgeForm(wedge));
+                }
+                Msg::UpdateIncidenceAngleMin(val) => {
+                    beam_angle.incidence_min = val;
+                }
+                Msg::UpdateIncidenceAngleMax(val) => {
+                    beam_angle.incidence_max = val;
+                }
+                Msg::UpdateVelocityIncidence(val) => {
+                    beam_angle.velocity_incidence = val;
+                }
+                Msg::UpdateVelocityRefraction(val) => {
+                    beam_angle.velocity_medium = val;
+                }
+                Msg::UpdateVelocityShear(val) => {
+                    beam_angle.velocity_medium_shear = val;
+                }
+                Msg::UpdateVelocitySteel(val) => {
+                    beam_angle.velocity_steel = val;
+                }
+                Msg::UpdateVelocityIncidenceShear(val) => {
+                    beam_angle.velocity_incidence_shear = val;
+                }
+                Msg::UpdateDensityIncidence(val) => {
+                    beam_angle.density_incidence = val;
+                }
+                Msg::UpdateDensityMedium(val) => {
+                    beam_angle.density_medium = val;
+                }
+                Msg::SelectIncidenceMaterial(material) => {
+                    beam_angle.velocity_incidence = material.velocity_longitudinal;
+                    beam_angle.velocity_incidence_shear = material.velocity_shear;
+                    beam_angle.density_incidence = material.density;
+                    beam_angle.temperature_incidence = material.temperature_coefficient;
+                }
+                Msg::SelectMediumMaterial(material) => {
+                    beam_angle.velocity_medium = material.velocity_longitudinal;
+                    beam_angle.velocity_medium_shear = material.velocity_shear;
+                    beam_angle.density_medium = material.density;
+                    beam_angle.temperature_medium = material.temperature_coefficient;
+                    beam_angle.temperature_medium_shear = material.temperature_coefficient_shear;
+                }
+                Msg::UpdateCalibrationTemperature(val) => {
+                    beam_angle.temperature_calibration = val;
+                }
+                Msg::UpdateInspectionTemperature(val) => {
+                    beam_angle.temperature_inspection = val;
+                }
+                Msg::UpdateTemperatureIncidence(val) => {
+                    beam_angle.temperature_incidence = val;
+                }
+                Msg::UpdateTemperatureMedium(val) => {
+                    beam_angle.temperature_medium = val;
+                }
+                Msg::UpdateTemperatureMediumShear(val) => {
+                    beam_angle.temperature_medium_shear = val;
+                }
+                Msg::UpdateTemperatureSteel(val) => {
+                    beam_angle.temperature_steel = val;
+                }
+                Msg::CalcRefraction => {
+                    //未输入的横波/钢中声速为0，不参与计算
+                    let optional = |velocity: f64| if velocity > 0.0 { Some(velocity) } else { None };
+                    let input = RefractionInput {
+                        incidence_min: beam_angle.incidence_min,
+                        incidence_max: beam_angle.incidence_max,
+                        velocity_incidence: beam_angle.velocity_incidence,
+                        velocity_longitudinal: beam_angle.velocity_medium,
+                        velocity_shear: optional(beam_angle.velocity_medium_shear),
+                        velocity_steel: optional(beam_angle.velocity_steel),
+                    };
+                    if let Err(err) = beam_angle.calc_refraction(&input) {
+                        beam_angle.result = err.to_string();
+                    }
+                    //输入两侧密度后计算反射/透射系数
+                    beam_angle.coefficients = Vec::new();
+                    if beam_angle.density_incidence > 0.0 && beam_angle.density_medium > 0.0 {
+                        let (incidence, medium) = (beam_angle.incidence_medium(), beam_angle.refraction_medium());
+                        match (
+                            calc::interface::normal_incidence(&incidence, &medium),
+                            calc::interface::sweep(&incidence, &medium, 1.0),
+                        ) {
+                            (Ok(normal), Ok(coefficients)) => {
+                                beam_angle.coefficients = coefficients;
+                                let text = beam_angle.coefficient_text(&normal);
+                                beam_angle.result += &text;
+                            }
+                            (Err(err), _) | (_, Err(err)) => beam_angle.result += &format!("\n\n反射/透射系数：{}", err),
+                        }
+                    }
+                }
+                unexpected => {
+                    panic!("未知参数，折射角计算模块{:?}", unexpected);
+                }
+            },
+            Scene::DelayLawForm(ref mut delay_law) => match msg {
+                Msg::UpdateScanType(val) => {
+                    delay_law.scan = val;
+                }
+                Msg::UpdateElements(val) => {
+                    delay_law.elements = val;
+                }
+                Msg::UpdateAperture(val) => {
+                    delay_law.aperture = val;
+                }
+                Msg::UpdatePitch(val) => {
+                    delay_law.pitch = val;
+                }
+                Msg::UpdateWedgeAngle(val) => {
+                    delay_law.wedge_angle = val;
+                }
+                Msg::UpdateWedgeVelocity(val) => {
+                    delay_law.wedge_velocity = val;
+                }
+                Msg::UpdateFirstElementHeight(val) => {
+                    delay_law.first_element_height = val;
+                }
+                Msg::UpdateMaterialVelocity(val) => {
+                    delay_law.material_velocity = val;
+                }
+                Msg::UpdateAngleStart(val) => {
+                    delay_law.angle_start = val;
+                }
+                Msg::UpdateAngleEnd(val) => {
+                    delay_law.angle_end = val;
+                }
+                Msg::UpdateAngleStep(val) => {
+                    delay_law.angle_step = val;
+                }
+                Msg::UpdateFocalDepth(val) => {
+                    delay_law.focal_depth = val;
+                }
+                Msg::CalcDelayLaw => {
+                    delay_law.result = match calc::delay_law::delay_laws(&delay_law.input()) {
+                        Ok(laws) => format!(
+                            "共{}条聚焦法则，延时单位ns\n\n{}",
+                            laws.len(),
+                            DelayLaw::laws_table(&laws)
+                        ),
+                        Err(err) => err.to_string(),
+                    };
+                }
+                Msg::SwitchTo(Scene::SceneList) => {
+                    new_scene = Some(Scene::SceneList);
+                }
+                unexpected => {
+                    panic!("未知参数，延时法则计算模块{:?}", unexpected);
+                }
+            },
+            Scene::GratingLobeForm(ref mut grating_lobe) => match msg {
+                Msg::LoadProbe(i) => {
+                    if let Some(probe) = self.database.probes.get(i) {
+                        grating_lobe.frequency = probe.frequency;
+                        grating_lobe.velocity = probe.velocity;
+                        if probe.elements > 0 {
+                            grating_lobe.elements = probe.elements;
+                        }
+                        //栅瓣只取决于实际阵元间距，不能用λ/2代替
+                        if probe.element_pitch > 0.0 {
+                            grating_lobe.pitch = probe.element_pitch;
+                        } else {
+                            grating_lobe.pitch = 0.0;
+                            grating_lobe.output = None;
+                            grating_lobe.result = format!("探头{}未填写实际阵元间距，请输入阵元间距", i + 1);
+                        }
+                    }
+                }
+                Msg::UpdateFrequency(val) => {
+                    grating_lobe.frequency = val;
+                }
+                Msg::UpdateVelocity(val) => {
+                    grating_lobe.velocity = val;
+                }
+                Msg::UpdatePitch(val) => {
+                    grating_lobe.pitch = val;
+                }
+                Msg::UpdateElements(val) => {
+                    grating_lobe.elements = val;
+                }
+                Msg::UpdateBandwidth(val) => {
+                    grating_lobe.bandwidth = val;
+                }
+                Msg::UpdateAngleStart(val) => {
+                    grating_lobe.steering_min = val;
+                }
+                Msg::UpdateAngleEnd(val) => {
+                    grating_lobe.steering_max = val;
+                }
+                Msg::UpdateAngleStep(val) => {
+                    grating_lobe.steering_step = val;
+                }
+                Msg::CalcGratingLobe => match calc::grating::grating_lobes(&grating_lobe.input()) {
+                    Ok(output) => {
+                        grating_lobe.result = grating_lobe.grating_text(&output);
+                        grating_lobe.output = Some(output);
+                    }
+                    Err(err) => {
+                        grating_lobe.result = err.to_string();
+                        grating_lobe.output = None;
+                    }
+                },
+                Msg::SwitchTo(Scene::SceneList) => {
+                    new_scene = Some(Scene::SceneList);
                 }
-                Msg::SwitchTo(Scene::RefractionAngle(beam_abngle)) => {
-                    new_scene = Some(Scene::RefractionAngle(beam_abngle));
+                unexpected => {
+                    panic!("未知参数，栅瓣计算模块{:?}", unexpected);
                 }
-                Msg::SwitchTo(Scene::TFMPWIForm) => {
-                    new_scene = Some(Scene::TFMPWIForm);
+            },
+            Scene::MaterialForm(ref mut material) => match msg {
+                Msg::UpdateMaterialName(val) => {
+                    material.name = val;
                 }
-                Msg::SwitchTo(Scene::Settings) => {
-                    new_scene = Some(Scene::Settings);
+                Msg::UpdateVelocityLongitudinal(val) => {
+                    material.velocity_longitudinal = val;
                 }
-                unexpected => {
-                    panic!(
-                        "Unexpected message when probes list shown: {:?}",
-                        unexpected
-                    );
+                Msg::UpdateVelocityShear(val) => {
+                    material.velocity_shear = val;
                 }
-            },
-            Scene::ProbeForm(ref mut probe) => match msg {
-                Msg::UpdateFrequency(val) => {
-                    probe.frequency = val;
+                Msg::UpdateDensity(val) => {
+                    material.density = val;
                 }
-                Msg::UpdateVelocity(val) => {
-                    probe.velocity = val;
+                Msg::UpdateTemperatureCoefficient(val) => {
+                    material.temperature_coefficient = val;
                 }
-                Msg::UpdateDescription(val) => {
-                    println!("Input: {}", val);
-                    probe.description = val;
+                Msg::UpdateTemperatureCoefficientShear(val) => {
+                    material.temperature_coefficient_shear = val;
                 }
-                Msg::CalcLP => {
-                    if (probe.frequency == 0.0) | (probe.velocity == 0.0) {
-                        probe.description = "频率/声速中有0值，请检查".into()
+                Msg::AddNew => {
+                    if material.name.is_empty() || material.velocity_longitudinal <= 0.0 {
+                        self.dialog.alert("请输入材料名称和纵波声速");
                     } else {
-                        probe.lambda = probe.velocity / 1000.0 / probe.frequency;
-                        probe.pitch = probe.lambda / 2.0;
-                        probe.description =
-                            format!("波长为{}mm\npitch最小值为{}mm", probe.lambda, probe.pitch);
+                        let mut new_material = Material::empty();
+                        ::std::mem::swap(material, &mut new_material);
+                        self.database.materials.push(new_material);
+                        self.storage.store(KEY, Json(&self.database));
                     }
                 }
-
+                Msg::SwitchTo(Scene::SceneList) => {
+                    new_scene = Some(Scene::SceneList);
+                }
+                unexpected => {
+                    panic!("未知参数，材料库模块{:?}", unexpected);
+                }
+            },
+            Scene::WedgeForm(ref mut wedge) => match msg {
+                Msg::UpdateWedgeAngle(val) => {
+                    wedge.angle = val;
+                }
+                Msg::UpdateWedgeVelocity(val) => {
+                    wedge.velocity = val;
+                }
+                Msg::UpdateFirstElementHeight(val) => {
+                    wedge.first_element_height = val;
+                }
+                Msg::UpdatePitch(val) => {
+                    wedge.pitch = val;
+                }
+                Msg::UpdateAperture(val) => {
+                    wedge.aperture = val;
+                }
+                Msg::UpdatePrimaryOffset(val) => {
+                    wedge.primary_offset = val;
+                }
+                Msg::UpdateFrontLength(val) => {
+                    wedge.front_length = val;
+                }
+                Msg::UpdateMaterialVelocity(val) => {
+                    wedge.material_velocity = val;
+                }
+                Msg::UpdateAngleStart(val) => {
+                    wedge.refraction_min = val;
+                }
+                Msg::UpdateAngleEnd(val) => {
+                    wedge.refraction_max = val;
+                }
+                Msg::UpdateAngleStep(val) => {
+                    wedge.refraction_step = val;
+                }
+                Msg::CalcWedge => {
+                    let input = wedge.input();
+                    wedge.description = match (
+                        calc::wedge::natural_refraction(&input),
+                        calc::wedge::exit_points(&input),
+                    ) {
+                        (Ok(natural), Ok(points)) => wedge.exit_points_text(natural, &points),
+                        (Err(err), _) | (_, Err(err)) => err.to_string(),
+                    };
+                }
                 Msg::AddNew => {
-                    let mut new_probe = Probe::empty();
-                    ::std::mem::swap(probe, &mut new_probe);
-                    self.database.probes.push(new_probe);
+                    let mut new_wedge = Wedge::empty();
+                    ::std::mem::swap(wedge, &mut new_wedge);
+                    self.database.wedges.push(new_wedge);
                     self.storage.store(KEY, Json(&self.database));
                 }
                 Msg::SwitchTo(Scene::SceneList) => {
                     new_scene = Some(Scene::SceneList);
                 }
                 unexpected => {
-                    panic!(
-                        "Unexpected message during new probe editing: {:?}",
-                        unexpected
-                    );
-                    //错误处理需要更人性化
+                    panic!("未知参数，楔块计算模块{:?}", unexpected);
                 }
             },
-            Scene::TFMPWIForm => match msg {
+            Scene::SkipDistanceForm(ref mut skip_distance) => match msg {
+                Msg::UpdateThickness(val) => {
+                    skip_distance.thickness = val;
+                }
+                Msg::UpdateRefractionAngle(val) => {
+                    skip_distance.angle = val;
+                }
+                Msg::UpdateSoundPath(val) => {
+                    skip_distance.sound_path = val;
+                }
+                Msg::CalcSkip => {
+                    skip_distance.result = skip_distance.skip_text();
+                }
                 Msg::SwitchTo(Scene::SceneList) => {
                     new_scene = Some(Scene::SceneList);
                 }
                 unexpected => {
-                    panic!("Unexpected message for settings scene: {:?}", unexpected);
+                    panic!("未知参数，跳距计算模块{:?}", unexpected);
                 }
-                //错误处理方式需改进
             },
-            Scene::RefractionAngle(ref mut beam_angle) => match msg {
+            Scene::PipeForm(ref mut pipe) => match msg {
+                Msg::UpdateScanDirection(val) => {
+                    pipe.direction = val;
+                }
+                Msg::UpdateOuterDiameter(val) => {
+                    pipe.outer_diameter = val;
+                }
+                Msg::UpdateThickness(val) => {
+                    pipe.thickness = val;
+                }
+                Msg::UpdateRefractionAngle(val) => {
+                    pipe.angle = val;
+                }
+                Msg::UpdateSoundPath(val) => {
+                    pipe.sound_path = val;
+                }
+                Msg::UpdateWedgeSize(val) => {
+                    pipe.wedge_size = val;
+                }
+                Msg::UpdateGapLimit(val) => {
+                    pipe.gap_limit = val;
+                }
+                Msg::CalcPipe => {
+                    pipe.result = match pipe.calc() {
+                        Ok(text) => text,
+                        Err(err) => err.to_string(),
+                    };
+                }
                 Msg::SwitchTo(Scene::SceneList) => {
                     new_scene = Some(Scene::SceneList);
                 }
-                Msg::UpdateIncidenceAngleMin(val) => {
-                    beam_angle.incidence_min = val;
+                unexpected => {
+                    panic!("未知参数，管道曲面修正模块{:?}", unexpected);
                 }
-                Msg::UpdateIncidenceAngleMax(val) => {
-                    beam_angle.incidence_max = val;
+            },
+            Scene::ThroughputForm(ref mut throughput) => match msg {
+                Msg::UpdateElements(val) => {
+                    throughput.elements = val;
                 }
-                Msg::UpdateVelocityIncidence(val) => {
-                    beam_angle.velocity_incidence = val;
+                Msg::UpdateSampleRate(val) => {
+                    throughput.sample_rate = val;
                 }
-                Msg::UpdateVelocityRefraction(val) => {
-                    beam_angle.velocity_medium = val;
+                Msg::UpdateBitDepth(val) => {
+                    throughput.bit_depth = val;
                 }
-                /*
-                Msg::UpdateRefractionSteelMin(val) => {
-                    beam_angle.refraction_steel_min = val;
+                Msg::UpdateRangeGate(val) => {
+                    throughput.range_gate = val;
                 }
-                Msg::UpdateRefractionSteelMax(val) => {
-                    beam_angle.refraction_steel_max = val;
+                Msg::UpdatePrf(val) => {
+                    throughput.prf = val;
                 }
-                */
-                Msg::CalcRefraction => {
-                    //还未计算角度
-                    beam_angle.refraction_min = beam_angle.incidence_min * 1.0;
-                    beam_angle.refraction_max = beam_angle.incidence_max * 1.0;
-                    beam_angle.result = format!(
-                        "折射角范围为{}度～{}度\n按入射声速{}m/s折射声速{}m/s计算",
-                        beam_angle.refraction_min, beam_angle.refraction_max, beam_angle.velocity_incidence, beam_angle.velocity_medium
-                    );
+                Msg::UpdateAngleCount(val) => {
+                    throughput.angles = val;
+                }
+                Msg::UpdateAveraging(val) => {
+                    throughput.averaging = val;
+                }
+                Msg::UpdateScanStep(val) => {
+                    throughput.scan_step = val;
+                }
+                Msg::UpdateTransferRate(val) => {
+                    throughput.transfer_rate = val;
+                }
+                Msg::CalcThroughput => {
+                    throughput.result = match calc::throughput::throughput(&throughput.input()) {
+                        Ok(output) => Throughput::throughput_text(&output),
+                        Err(err) => err.to_string(),
+                    };
+                }
+                Msg::SwitchTo(Scene::SceneList) => {
+                    new_scene = Some(Scene::SceneList);
                 }
                 unexpected => {
-                    panic!("未知参数，折射角计算模块{:?}", unexpected);
+                    panic!("未知参数，数据率计算模块{:?}", unexpected);
+                }
+            },
+            Scene::AscanForm(ref mut ascan) => match msg {
+                Msg::LoadAscanFile(file) => {
+                    self.reading.clear();
+                    let callback = self.link.callback(Msg::AscanFileLoaded);
+                    match self.reader.read_file(file, callback) {
+                        Ok(task) => self.reading.push(task),
+                        Err(err) => ascan.result = format!("无法读取文件：{}", err),
+                    }
+                }
+                Msg::AscanFileLoaded(file) => {
+                    self.reading.clear();
+                    ascan.load(&file);
+                }
+                Msg::UpdateSampleRate(val) => {
+                    ascan.sample_rate = val;
+                }
+                Msg::UpdateAveraging(val) => {
+                    ascan.averaging = val;
+                }
+                Msg::UpdateFilterLow(val) => {
+                    ascan.filter_low = val;
+                }
+                Msg::UpdateFilterHigh(val) => {
+                    ascan.filter_high = val;
+                }
+                Msg::UpdateDetection(detection) => {
+                    ascan.detection = detection;
+                    if ascan.waveform.is_some() {
+                        ascan.process();
+                    }
+                }
+                Msg::UpdateViewStart(val) => {
+                    ascan.view_start = val;
+                }
+                Msg::UpdateViewEnd(val) => {
+                    ascan.view_end = val;
+                }
+                Msg::UpdateGateAStart(val) => {
+                    ascan.gate_a.start = val;
+                }
+                Msg::UpdateGateAWidth(val) => {
+                    ascan.gate_a.width = val;
+                }
+                Msg::UpdateGateAThreshold(val) => {
+                    ascan.gate_a.threshold = val;
+                }
+                Msg::UpdateGateBStart(val) => {
+                    ascan.gate_b.start = val;
+                }
+                Msg::UpdateGateBWidth(val) => {
+                    ascan.gate_b.width = val;
+                }
+                Msg::UpdateGateBThreshold(val) => {
+                    ascan.gate_b.threshold = val;
+                }
+                Msg::ProcessAscan => {
+                    ascan.process();
+                }
+                Msg::SaveProbeFrequency(i) => {
+                    if let (Some(probe), Some(spectrum)) = (self.database.probes.get_mut(i), &ascan.spectrum) {
+                        probe.frequency = (spectrum.centre * 100.0).round() / 100.0;
+                        //频率变化后波长、近场和扩散角随之更新
+                        probe.recalc();
+                        ascan.result += &format!("\n\n已将中心频率{}MHz写入探头{}", probe.frequency, i + 1);
+                        self.storage.store(KEY, Json(&self.database));
+                    }
+                }
+                Msg::SwitchTo(Scene::SceneList) => {
+                    new_scene = Some(Scene::SceneList);
+                }
+                unexpected => {
+                    panic!("未知参数，A扫描模块{:?}", unexpected);
+                }
+            },
+            Scene::DacForm(ref mut dac) => match msg {
+                Msg::UpdateDescription(val) => {
+                    dac.description = val;
+                }
+                Msg::UpdateCalibrationReflector(val) => {
+                    dac.reflector = val;
+                }
+                Msg::UpdateReflectorDiameter(val) => {
+                    dac.diameter = val;
+                }
+                Msg::UpdateReferenceHeight(val) => {
+                    dac.reference = val;
+                }
+                Msg::UpdateDacPoints(val) => {
+                    dac.points = val;
+                }
+                Msg::UpdateDacUnit(val) => {
+                    dac.decibel = val;
+                }
+                Msg::CalcDac => {
+                    dac.calc();
+                }
+                Msg::SaveDac(i) => {
+                    dac.calc();
+                    if dac.output.is_some() {
+                        if let Some(probe) = self.database.probes.get_mut(i) {
+                            probe.dac_curves.push(dac.clone());
+                            dac.result += &format!("\n\n已保存到探头{}", i + 1);
+                            self.storage.store(KEY, Json(&self.database));
+                        }
+                    }
+                }
+                Msg::LoadDac(i, j) => {
+                    if let Some(saved) = self.database.probes.get(i).and_then(|probe| probe.dac_curves.get(j)) {
+                        *dac = saved.clone();
+                        dac.calc();
+                    }
+                }
+                Msg::SwitchTo(Scene::SceneList) => {
+                    new_scene = Some(Scene::SceneList);
+                }
+                unexpected => {
+                    panic!("未知参数，DAC曲线模块{:?}", unexpected);
+                }
+            },
+            Scene::GainTransferForm(ref mut gain_transfer) => match msg {
+                Msg::LoadProbe(i) => {
+                    if let Some(probe) = self.database.probes.get(i) {
+                        //探头页未计算时按频率和声速求波长
+                        gain_transfer.lambda = if probe.lambda > 0.0 {
+                            probe.lambda
+                        } else {
+                            calc::probe::wavelength(probe.velocity, probe.frequency).unwrap_or(0.0)
+                        };
+                        gain_transfer.near_field = probe.near_field;
+                    }
+                }
+                Msg::UpdateLambda(val) => {
+                    gain_transfer.lambda = val;
+                }
+                Msg::UpdateNearField(val) => {
+                    gain_transfer.near_field = val;
+                }
+                Msg::UpdateReferenceKind(val) => {
+                    gain_transfer.reference.kind = val;
+                }
+                Msg::UpdateReferenceDiameter(val) => {
+                    gain_transfer.reference.diameter = val;
+                }
+                Msg::UpdateReferenceDistance(val) => {
+                    gain_transfer.reference.distance = val;
+                }
+                Msg::UpdateTargetKind(val) => {
+                    gain_transfer.target.kind = val;
+                }
+                Msg::UpdateTargetDiameter(val) => {
+                    gain_transfer.target.diameter = val;
+                }
+                Msg::UpdateTargetDistance(val) => {
+                    gain_transfer.target.distance = val;
+                }
+                Msg::UpdateAttenuation(val) => {
+                    gain_transfer.attenuation = val;
+                }
+                Msg::UpdateBlockGain(val) => {
+                    gain_transfer.block_gain = val;
+                }
+                Msg::UpdateBlockDistance(val) => {
+                    gain_transfer.block_distance = val;
+                }
+                Msg::UpdatePartGain(val) => {
+                    gain_transfer.part_gain = val;
+                }
+                Msg::UpdatePartDistance(val) => {
+                    gain_transfer.part_distance = val;
+                }
+                Msg::CalcGainTransfer => {
+                    gain_transfer.result = match gain_transfer.calc() {
+                        Ok(text) => text,
+                        Err(err) => err.to_string(),
+                    };
+                }
+                Msg::SwitchTo(Scene::SceneList) => {
+                    new_scene = Some(Scene::SceneList);
+                }
+                unexpected => {
+                    panic!("未知参数，反射体增益换算模块{:?}", unexpected);
+                }
+            },
+            Scene::TofdForm(ref mut tofd) => match msg {
+                Msg::LoadProbe(i) => {
+                    if let Some(probe) = self.database.probes.get(i) {
+                        tofd.frequency = probe.frequency;
+                        tofd.velocity = probe.velocity;
+                        tofd.element_size = probe.element_size;
+                    }
+                }
+                Msg::UpdateTofdTab(val) => {
+                    tofd.tab = val;
+                }
+                Msg::UpdateFrequency(val) => {
+                    tofd.frequency = val;
+                }
+                Msg::UpdateVelocity(val) => {
+                    tofd.velocity = val;
+                }
+                Msg::UpdateElementSize(val) => {
+                    tofd.element_size = val;
+                }
+                Msg::UpdateRefractionAngle(val) => {
+                    tofd.angle = val;
+                }
+                Msg::UpdateThickness(val) => {
+                    tofd.thickness = val;
+                }
+                Msg::UpdateTargetDepth(val) => {
+                    tofd.target_depth = val;
+                }
+                Msg::UpdatePulseCycles(val) => {
+                    tofd.pulse_cycles = val;
+                }
+                Msg::UpdatePcs(val) => {
+                    tofd.pcs = val;
+                }
+                Msg::UpdateTipUpper(val) => {
+                    tofd.tip_upper = val;
+                }
+                Msg::UpdateTipLower(val) => {
+                    tofd.tip_lower = val;
+                }
+                Msg::CalcTofd => {
+                    let input = tofd.input();
+                    match calc::tofd::setup(&input) {
+                        Ok(output) => {
+                            tofd.pcs = output.pcs;
+                            tofd.result = Tofd::setup_text(&input, &output);
+                        }
+                        Err(err) => tofd.result = err.to_string(),
+                    }
+                }
+                Msg::CalcTipDepth => {
+                    tofd.depth_result = match tofd.tip_depth_text() {
+                        Ok(text) => text,
+                        Err(err) => err.to_string(),
+                    };
+                }
+                Msg::SwitchTo(Scene::SceneList) => {
+                    new_scene = Some(Scene::SceneList);
+                }
+                unexpected => {
+                    panic!("未知参数，TOFD模块{:?}", unexpected);
+                }
+            },
+            Scene::WeldCoverageForm(ref mut weld_coverage) => match msg {
+                Msg::UpdateBevel(val) => {
+                    weld_coverage.bevel = val;
+                }
+                Msg::UpdateThickness(val) => {
+                    weld_coverage.thickness = val;
+                }
+                Msg::UpdateBevelAngle(val) => {
+                    weld_coverage.bevel_angle = val;
+                }
+                Msg::UpdateRootGap(val) => {
+                    weld_coverage.root_gap = val;
+                }
+                Msg::UpdateLand(val) => {
+                    weld_coverage.land = val;
+                }
+                Msg::UpdateCapWidth(val) => {
+                    weld_coverage.cap_width = val;
+                }
+                Msg::UpdateRootRadius(val) => {
+                    weld_coverage.root_radius = val;
+                }
+                Msg::UpdateProbeOffset(val) => {
+                    weld_coverage.probe_offset = val;
+                }
+                Msg::UpdateAngleStart(val) => {
+                    weld_coverage.angle_min = val;
+                }
+                Msg::UpdateAngleEnd(val) => {
+                    weld_coverage.angle_max = val;
+                }
+                Msg::UpdateAngleStep(val) => {
+                    weld_coverage.angle_step = val;
+                }
+                Msg::CalcWeldCoverage => {
+                    let (weld, input) = weld_coverage.inputs();
+                    match calc::weld::trace(&weld, &input) {
+                        Ok(coverage) => {
+                            weld_coverage.result = WeldCoverage::coverage_text(&coverage);
+                            weld_coverage.coverage = Some(coverage);
+                        }
+                        Err(err) => {
+                            weld_coverage.result = err.to_string();
+                            weld_coverage.coverage = None;
+                        }
+                    }
+                }
+                Msg::SwitchTo(Scene::SceneList) => {
+                    new_scene = Some(Scene::SceneList);
+                }
+                unexpected => {
+                    panic!("未知参数，焊缝覆盖模块{:?}", unexpected);
                 }
             },
             Scene::Settings => match msg {
@@ -311,6 +4159,8 @@ impl Component for Model {
                     let ok = { self.dialog.confirm("确实要清除数据吗?") };
                     if ok {
                         self.database.probes.clear();
+                        self.database.materials.clear();
+                        self.database.wedges.clear();
                         self.storage.remove(KEY);
                     }
                 }
@@ -323,6 +4173,8 @@ impl Component for Model {
             },
         }
         if let Some(new_scene) = new_scene.take() {
+            self.animation = None;
+            self.reading.clear();
             self.scene = new_scene;
         }
         true
@@ -339,15 +4191,31 @@ impl Component for Model {
                     <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::Settings))>{ "Settings" }</button>
                 </div>";
         */
+        let materials = self.materials();
         match self.scene {
             Scene::SceneList => html! {
                 <div class="crm">
                     <div class="probes">
                         { for self.database.probes.iter().map(Renderable::render) }
                     </div>
+                    <div class="wedges">
+                        { for self.database.wedges.iter().map(Renderable::render) }
+                    </div>
                     <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::ProbeForm(Probe::empty())))>{ "波长&Pitch" }</button>
                     <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::RefractionAngle(BeamAngle::empty())))>{ "PA探头折射角" }</button>
-                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::TFMPWIForm))>{ "TFM PWI演示" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::WedgeForm(Wedge::empty())))>{ "楔块出射点" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SkipDistanceForm(SkipDistance::empty())))>{ "跳距&声程" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::PipeForm(Pipe::empty())))>{ "管道曲面修正" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::WeldCoverageForm(WeldCoverage::empty())))>{ "焊缝覆盖" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::DelayLawForm(DelayLaw::empty())))>{ "延时法则" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::GratingLobeForm(GratingLobe::empty())))>{ "栅瓣预测" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::MaterialForm(Material::empty())))>{ "材料库" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::TFMPWIForm(Tfm::empty())))>{ "TFM PWI演示" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::ThroughputForm(Throughput::empty())))>{ "FMC/PWI数据率" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::AscanForm(Ascan::empty())))>{ "A扫描分析" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::DacForm(Dac::empty())))>{ "DAC/TCG曲线" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::GainTransferForm(GainTransfer::empty())))>{ "反射体增益换算" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::TofdForm(Tofd::empty())))>{ "TOFD布置" }</button>
                     <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::Settings))>{ "Settings" }</button>
                 </div>
             },
@@ -359,6 +4227,11 @@ impl Component for Model {
                         { probe.view_description_textarea(&self.link) }
                         { probe.view_frequency_input(&self.link) }
                         { probe.view_velocity_input(&self.link) }
+                        { view_velocity_select(&materials, &self.link, Msg::UpdateVelocity) }
+                        { probe.view_element_size_input(&self.link) }
+                        { probe.view_focal_depth_input(&self.link) }
+                        { view_number_input("new-probe", "阵元数", probe.elements as f64, &self.link, |val| Msg::UpdateElements(val as usize)) }
+                        { view_number_input("new-probe", "阵元间距", probe.element_pitch, &self.link, Msg::UpdatePitch) }
                     </div>
                     <button onclick=self.link.callback(|_| Msg::CalcLP)>{ "计算" }</button>
                     <button //disabled=probe.first_name.is_empty() || probe.last_name.is_empty()
@@ -366,17 +4239,35 @@ impl Component for Model {
                     <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
                 </div>
             },
-            Scene::TFMPWIForm => html! {
+            Scene::TFMPWIForm(ref tfm) => html! {
                 <div class="tfm">
-                    <button>{"FMC-TFM演示"}</button>
-                    <button>{"PWI-TFM演示"}</button>
+                    { view_probe_buttons(&self.database.probes, &self.link) }
+                    { tfm.view_inputs(&materials, &self.link) }
+                    <button onclick=self.link.callback(|_| Msg::SimulateFmc)>{"FMC-TFM演示"}</button>
+                    <button onclick=self.link.callback(|_| Msg::ReconstructTfm)>{"TFM重建"}</button>
+                    <button onclick=self.link.callback(|_| Msg::SimulatePwi)>{"PWI-TFM演示"}</button>
+                    <label>{ "导入FMC数据（.npy，或原始数据和JSON说明文件）" }
+                        <input type="file" multiple=true accept=".npy,.bin,.raw,.dat,.json"
+                               onchange=self.link.callback(|value| {
+                                   let mut files = Vec::new();
+                                   if let ChangeData::Files(list) = value {
+                                       files.extend(list);
+                                   }
+                                   Msg::LoadFmcFiles(files)
+                               }) />
+                    </label>
                     <a href="https://eddyfi.com/academy.html">
                     <button>{"TFM线上学习课程"}</button>
                     </a>
                     <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
                     <hr/>
-                    <img src="Acquisition-FMC-ET-01.gif"  alt="TFM数据采集" title="TFM数据采集FMC"/>
-                    <img src="RECONSTRUCTION-TFM-ET.gif"  alt="TFM数据重建" title="TFM数据重建"/>
+                    { markdown::render_markdown(&tfm.result) }
+                    { tfm.view_frame(self.animation.is_some(), &self.link) }
+                    { tfm.view_image() }
+                    <hr/>
+                    { Tfm::view_mode_buttons(&self.link) }
+                    { tfm.view_mode_image() }
+                    { tfm.view_aim_maps() }
                     <hr/>
                     //<img src="Acquisition-FMC-ET-01.gif"  alt="TFM数据采集" title="TFM数据重建"/>
                     //视频播放,替换
@@ -385,17 +4276,196 @@ impl Component for Model {
                     //<img  dynsrc="file:///D:/Rust/webTools/img/N600_HVAC_HEATEXCHANGER_ECTINSPECTION_SUBTITLEMASTER_w(2)_480.mp4"  start="mouseover" alt="PWI激发"/>
                 </div>
             },
-            Scene::RefractionAngle(ref beam_angle) => html! {
-                <div class="refraction">
-                    { beam_angle.incidence_min_input(&self.link) }
-                    { beam_angle.incidence_max_input(&self.link) }
-                    { beam_angle.velocity_incidence_input(&self.link)}
-                    { beam_angle.velocity_refraction_input(&self.link) }
+            Scene::RefractionAngle(ref beam_angle) => {
+                //焊缝检测一般用横波，未计算横波时取纵波
+                let skip_angle = beam_angle
+                    .refraction_shear_max
+                    .or(beam_angle.refraction_max)
+                    .unwrap_or(0.0);
+                let coverage_min = beam_angle
+                    .refraction_shear_min
+                    .or(beam_angle.refraction_min)
+                    .unwrap_or(0.0);
+                //楔块出射点的上下限必须是同一波型：横波范围完整时取横波，否则取纵波
+                let (wedge_min, wedge_max, material_velocity) = match (
+                    beam_angle.refraction_shear_min,
+                    beam_angle.refraction_shear_max,
+                    beam_angle.refraction_min,
+                    beam_angle.refraction_max,
+                ) {
+                    (Some(min), Some(max), _, _) => (min, max, beam_angle.velocity_medium_shear),
+                    (_, _, Some(min), Some(max)) => (min, max, beam_angle.velocity_medium),
+                    _ => (0.0, 0.0, 0.0),
+                };
+                html! {
+                    <div class="refraction">
+                        { beam_angle.incidence_min_input(&self.link) }
+                        { beam_angle.incidence_max_input(&self.link) }
+                        { beam_angle.velocity_incidence_input(&self.link)}
+                        { view_velocity_select(&materials, &self.link, Msg::UpdateVelocityIncidence) }
+                        { view_number_input("beam-angle", "入射介质横波声速（液体为0）", beam_angle.velocity_incidence_shear, &self.link, Msg::UpdateVelocityIncidenceShear) }
+                        { view_number_input("beam-angle", "入射介质密度", beam_angle.density_incidence, &self.link, Msg::UpdateDensityIncidence) }
+                        { view_material_select("入射介质", &materials, &self.link, Msg::SelectIncidenceMaterial) }
+                        { beam_angle.velocity_refraction_input(&self.link) }
+                        { view_velocity_select(&materials, &self.link, Msg::UpdateVelocityRefraction) }
+                        { beam_angle.velocity_shear_input(&self.link) }
+                        { view_velocity_select(&materials, &self.link, Msg::UpdateVelocityShear) }
+                        { view_number_input("beam-angle", "折射介质密度", beam_angle.density_medium, &self.link, Msg::UpdateDensityMedium) }
+                        { view_material_select("折射介质", &materials, &self.link, Msg::SelectMediumMaterial) }
+                        { beam_angle.velocity_steel_input(&self.link) }
+                        { view_velocity_select(&materials, &self.link, Msg::UpdateVelocitySteel) }
+                        { beam_angle.view_temperature_inputs(&self.link) }
+                        <hr/>
+                        <button onclick=self.link.callback(|_| Msg::CalcRefraction)>{"计算折射角"}</button>
+                        <button onclick=self.link.callback(move |_| Msg::SwitchTo(Scene::SkipDistanceForm(SkipDistance::with_angle(skip_angle))))>{ "跳距计算" }</button>
+                        <button onclick=self.link.callback(move |_| Msg::SwitchTo(Scene::PipeForm(Pipe::with_angle(skip_angle))))>{ "管道曲面修正" }</button>
+                        <button onclick=self.link.callback(move |_| Msg::SwitchTo(Scene::WeldCoverageForm(WeldCoverage::with_angles(coverage_min, skip_angle))))>{ "焊缝覆盖" }</button>
+                        <button onclick=self.link.callback(move |_| Msg::SwitchTo(Scene::WedgeForm(Wedge::with_range(wedge_min, wedge_max, material_velocity))))>{ "楔块出射点" }</button>
+                        <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
+                        <hr/>
+                        { beam_angle.view_result(&self.link)}
+                        { beam_angle.view_coefficients() }
+                    </div>
+                }
+            }
+            Scene::DelayLawForm(ref delay_law) => html! {
+                <div class="delay-law">
+                    { delay_law.view_inputs(&materials, &self.link) }
+                    <hr/>
+                    <button onclick=self.link.callback(|_| Msg::CalcDelayLaw)>{ "计算延时法则" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
+                    <hr/>
+                    { markdown::render_markdown(&delay_law.result) }
+                </div>
+            },
+            Scene::GratingLobeForm(ref grating_lobe) => html! {
+                <div class="grating-lobe">
+                    { view_probe_buttons(&self.database.probes, &self.link) }
+                    { grating_lobe.view_inputs(&materials, &self.link) }
+                    <hr/>
+                    <button onclick=self.link.callback(|_| Msg::CalcGratingLobe)>{ "计算栅瓣" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
+                    <hr/>
+                    { markdown::render_markdown(&grating_lobe.result) }
+                    { grating_lobe.view_array_factor() }
+                </div>
+            },
+            Scene::MaterialForm(ref material) => html! {
+                <div class="material">
+                    { view_material_inputs(material, &self.link) }
+                    <button onclick=self.link.callback(|_| Msg::AddNew)>{ "保存" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
+                    <hr/>
+                    { markdown::render_markdown(&materials_table(&materials)) }
+                </div>
+            },
+            Scene::WedgeForm(ref wedge) => html! {
+                <div class="wedge">
+                    { wedge.view_inputs(&materials, &self.link) }
+                    <hr/>
+                    <button onclick=self.link.callback(|_| Msg::CalcWedge)>{ "计算出射点" }</button>
+                    <button onclick=self.link.callback(|_| Msg::AddNew)>{ "保存" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
+                    <hr/>
+                    { markdown::render_markdown(&wedge.description) }
+                </div>
+            },
+            Scene::ThroughputForm(ref throughput) => html! {
+                <div class="throughput">
+                    { throughput.view_inputs(&self.link) }
+                    <hr/>
+                    <button onclick=self.link.callback(|_| Msg::CalcThroughput)>{ "计算" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
+                    <hr/>
+                    { markdown::render_markdown(&throughput.result) }
+                </div>
+            },
+            Scene::GainTransferForm(ref gain_transfer) => html! {
+                <div class="gain-transfer">
+                    { view_probe_buttons(&self.database.probes, &self.link) }
+                    { gain_transfer.view_inputs(&self.link) }
+                    <hr/>
+                    <button onclick=self.link.callback(|_| Msg::CalcGainTransfer)>{ "计算" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
+                    <hr/>
+                    { markdown::render_markdown(&gain_transfer.result) }
+                </div>
+            },
+            Scene::TofdForm(ref tofd) => {
+                let (calc_button, result) = match tofd.tab {
+                    TofdTab::Setup => (html! { <button onclick=self.link.callback(|_| Msg::CalcTofd)>{ "计算布置" }</button> }, &tofd.result),
+                    TofdTab::TipDepth => (html! { <button onclick=self.link.callback(|_| Msg::CalcTipDepth)>{ "换算深度" }</button> }, &tofd.depth_result),
+                };
+                html! {
+                    <div class="tofd">
+                        { view_probe_buttons(&self.database.probes, &self.link) }
+                        { tofd.view_inputs(&self.link) }
+                        <hr/>
+                        { calc_button }
+                        <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
+                        <hr/>
+                        { markdown::render_markdown(result) }
+                        { if tofd.tab == TofdTab::TipDepth { tofd.view_depth_curve() } else { html! {} } }
+                    </div>
+                }
+            }
+            Scene::DacForm(ref dac) => html! {
+                <div class="dac">
+                    { Dac::view_saved(&self.database.probes, &self.link) }
+                    { dac.view_inputs(&self.link) }
+                    <hr/>
+                    <button onclick=self.link.callback(|_| Msg::CalcDac)>{ "计算" }</button>
+                    { dac.view_export() }
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
+                    { Dac::view_save_buttons(&self.database.probes, &self.link) }
+                    <hr/>
+                    { markdown::render_markdown(&dac.result) }
+                    { dac.view_chart() }
+                </div>
+            },
+            Scene::AscanForm(ref ascan) => html! {
+                <div class="ascan">
+                    { ascan.view_inputs(&self.link) }
+                    <hr/>
+                    <button onclick=self.link.callback(|_| Msg::ProcessAscan)>{ "处理" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
+                    <hr/>
+                    { markdown::render_markdown(&ascan.result) }
+                    { ascan.view_save_buttons(&self.database.probes, &self.link) }
+                    { ascan.view_chart() }
+                    { markdown::render_markdown(&ascan.gate_text()) }
+                    { ascan.view_spectrum() }
+                </div>
+            },
+            Scene::SkipDistanceForm(ref skip_distance) => html! {
+                <div class="skip-distance">
+                    { skip_distance.view_inputs(&self.link) }
+                    <hr/>
+                    <button onclick=self.link.callback(|_| Msg::CalcSkip)>{ "计算" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
+                    <hr/>
+                    { markdown::render_markdown(&skip_distance.result) }
+                </div>
+            },
+            Scene::PipeForm(ref pipe) => html! {
+                <div class="pipe">
+                    { pipe.view_inputs(&self.link) }
+                    <hr/>
+                    <button onclick=self.link.callback(|_| Msg::CalcPipe)>{ "计算" }</button>
+                    <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
+                    <hr/>
+                    { markdown::render_markdown(&pipe.result) }
+                </div>
+            },
+            Scene::WeldCoverageForm(ref weld_coverage) => html! {
+                <div class="weld">
+                    { weld_coverage.view_inputs(&self.link) }
                     <hr/>
-                    <button onclick=self.link.callback(|_| Msg::CalcRefraction)>{"计算折射角"}</button>
+                    <button onclick=self.link.callback(|_| Msg::CalcWeldCoverage)>{ "计算覆盖" }</button>
                     <button onclick=self.link.callback(|_| Msg::SwitchTo(Scene::SceneList))>{ "返回" }</button>
                     <hr/>
-                    { beam_angle.view_result(&self.link)}
+                    { weld_coverage.view_section() }
+                    { markdown::render_markdown(&weld_coverage.result) }
                 </div>
             },
             Scene::Settings => html! {
@@ -420,6 +4490,15 @@ impl Renderable for Probe {
                 <p>{ format!("Velocity: {}", self.velocity) }</p>
                 <p>{ format!("Lambda: {}", self.lambda) }</p>
                 <p>{ format!("Pitch: {}", self.pitch) }</p>
+                <p>{ format!("Element Size: {}", self.element_size) }</p>
+                <p>{ format!("Near Field: {:.2}", self.near_field) }</p>
+                <p>{ format!("Spread -6dB: {}", spread_text(self.spread_6db)) }</p>
+                <p>{ format!("Spread -20dB: {}", spread_text(self.spread_20db)) }</p>
+                <p>{ format!("Focal Depth: {}", self.focal_depth) }</p>
+                <p>{ format!("Focal Spot: {}", length_text(self.spot_width)) }</p>
+                <p>{ format!("Depth of Field: {}", length_text(self.depth_of_field)) }</p>
+                <p>{ format!("Elements: {}", self.elements) }</p>
+                <p>{ format!("Element Pitch: {}", self.element_pitch) }</p>
                 <p>{ "Description:" }</p>
                 { markdown::render_markdown(&self.description) }
             </div>
@@ -428,6 +4507,15 @@ impl Renderable for Probe {
 }
 
 impl Probe {
+    //未输入实际阵元间距时按λ/2
+    fn array_pitch(&self) -> f64 {
+        if self.element_pitch > 0.0 {
+            self.element_pitch
+        } else {
+            self.pitch
+        }
+    }
+
     fn view_frequency_input(&self, link: &ComponentLink<Model>) -> Html {
         html! {
             <input class="new-probe"
@@ -438,11 +4526,13 @@ impl Probe {
         }
     }
     fn view_velocity_input(&self, link: &ComponentLink<Model>) -> Html {
-        html! {
-            <input class="new-probe"
-                   placeholder="声速"
-                   oninput=link.callback(|e: InputData| Msg::UpdateVelocity(e.value.parse().unwrap())) />
-        }
+        view_number_input("new-probe", "声速", self.velocity, link, Msg::UpdateVelocity)
+    }
+    fn view_element_size_input(&self, link: &ComponentLink<Model>) -> Html {
+        view_number_input("new-probe", "晶片尺寸", self.element_size, link, Msg::UpdateElementSize)
+    }
+    fn view_focal_depth_input(&self, link: &ComponentLink<Model>) -> Html {
+        view_number_input("new-probe", "聚焦深度", self.focal_depth, link, Msg::UpdateFocalDepth)
     }
     /*
     fn view_first_name_input(&self, link: &ComponentLink<Model>) -> Html {
@@ -0,0 +1,69 @@
+//! 材料声学参数库
+
+/// 材料声速单位m/s，密度单位g/cm³
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Material {
+    pub name: String,
+    pub velocity_longitudinal: f64,
+    /// 液体中不传播横波，为0
+    pub velocity_shear: f64,
+    pub density: f64,
+    /// 纵波/横波声速温度系数，m/s/°C，0为不补偿
+    #[serde(default)]
+    pub temperature_coefficient: f64,
+    #[serde(default)]
+    pub temperature_coefficient_shear: f64,
+}
+
+impl Material {
+    pub fn empty() -> Self {
+        Material {
+            name: "".into(),
+            velocity_longitudinal: 0.0,
+            velocity_shear: 0.0,
+            density: 0.0,
+            temperature_coefficient: 0.0,
+            temperature_coefficient_shear: 0.0,
+        }
+    }
+
+    /// 纵波声阻抗，单位MRayl（10⁶kg/m²s）
+    pub fn impedance(&self) -> f64 {
+        self.density * self.velocity_longitudinal / 1000.0
+    }
+}
+
+fn preset(name: &str, velocity_longitudinal: f64, velocity_shear: f64, density: f64) -> Material {
+    Material {
+        name: name.into(),
+        velocity_longitudinal,
+        velocity_shear,
+        density,
+        temperature_coefficient: 0.0,
+        temperature_coefficient_shear: 0.0,
+    }
+}
+
+fn with_temperature(material: Material, longitudinal: f64, shear: f64) -> Material {
+    Material {
+        temperature_coefficient: longitudinal,
+        temperature_coefficient_shear: shear,
+        ..material
+    }
+}
+
+/// 内置材料，数值为20°C下的典型值，楔块材料和钢给出声速温度系数
+pub fn presets() -> Vec<Material> {
+    vec![
+        with_temperature(preset("碳钢", 5920.0, 3240.0, 7.85), -0.6, -0.4),
+        preset("不锈钢", 5790.0, 3100.0, 7.89),
+        preset("铝", 6320.0, 3130.0, 2.70),
+        preset("铜", 4660.0, 2330.0, 8.93),
+        preset("钛", 6100.0, 3120.0, 4.50),
+        preset("Inconel", 5820.0, 3020.0, 8.25),
+        with_temperature(preset("Rexolite", 2337.0, 1157.0, 1.05), -2.4, -1.2),
+        with_temperature(preset("亚克力", 2730.0, 1430.0, 1.18), -3.2, -1.6),
+        preset("水", 1480.0, 0.0, 1.00),
+        preset("甘油", 1920.0, 0.0, 1.26),
+    ]
+}
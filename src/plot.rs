@@ -0,0 +1,314 @@
+//! 用内联SVG绘制计算结果曲线和工件截面
+use yew::{html, Html};
+
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 300.0;
+const MARGIN: f64 = 40.0;
+const TICKS: usize = 5;
+
+/// 坐标轴范围与名称
+pub struct Axis {
+    pub label: &'static str,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Axis {
+    fn scale(&self, value: f64, length: f64) -> f64 {
+        let span = self.max - self.min;
+        if span == 0.0 {
+            0.0
+        } else {
+            (value - self.min) / span * length
+        }
+    }
+}
+
+/// 一条曲线
+pub struct Series {
+    pub label: String,
+    pub color: &'static str,
+    pub points: Vec<(f64, f64)>,
+}
+
+fn to_screen(x: &Axis, y: &Axis, point: (f64, f64)) -> (f64, f64) {
+    (
+        MARGIN + x.scale(point.0, WIDTH - 2.0 * MARGIN),
+        HEIGHT - MARGIN - y.scale(point.1, HEIGHT - 2.0 * MARGIN),
+    )
+}
+
+fn view_series(x: &Axis, y: &Axis, series: &Series) -> Html {
+    let points = series
+        .points
+        .iter()
+        .map(|&point| {
+            let (sx, sy) = to_screen(x, y, point);
+            format!("{:.1},{:.1}", sx, sy)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    html! {
+        <polyline points=points fill="none" stroke=series.color stroke-width="1.5" />
+    }
+}
+
+fn view_x_tick(x: &Axis, i: usize) -> Html {
+    let value = x.min + (x.max - x.min) * i as f64 / TICKS as f64;
+    let sx = format!("{:.1}", MARGIN + x.scale(value, WIDTH - 2.0 * MARGIN));
+    let sy = format!("{:.1}", HEIGHT - MARGIN + 14.0);
+    html! {
+        <text x=sx y=sy font-size="10" text-anchor="middle">{ format!("{:.1}", value) }</text>
+    }
+}
+
+fn view_y_tick(y: &Axis, i: usize) -> Html {
+    let value = y.min + (y.max - y.min) * i as f64 / TICKS as f64;
+    let sx = format!("{:.1}", MARGIN - 4.0);
+    let sy = format!("{:.1}", HEIGHT - MARGIN - y.scale(value, HEIGHT - 2.0 * MARGIN) + 3.0);
+    html! {
+        <text x=sx y=sy font-size="10" text-anchor="end">{ format!("{:.1}", value) }</text>
+    }
+}
+
+fn view_legend(i: usize, series: &Series) -> Html {
+    let sx = format!("{:.1}", MARGIN + 10.0 + 150.0 * i as f64);
+    html! {
+        <text x=sx y="14" font-size="11" fill=series.color>{ &series.label }</text>
+    }
+}
+
+/// 折线图，曲线超出坐标范围的部分不裁剪
+pub fn line_chart(x: &Axis, y: &Axis, series: &[Series]) -> Html {
+    let left = format!("{}", MARGIN);
+    let right = format!("{}", WIDTH - MARGIN);
+    let top = format!("{}", MARGIN);
+    let bottom = format!("{}", HEIGHT - MARGIN);
+    let x_label = format!("{}", HEIGHT - 6.0);
+    let centre = format!("{}", WIDTH / 2.0);
+    html! {
+        <svg class="plot" width="600" height="300" viewBox="0 0 600 300">
+            <line x1=&left y1=&bottom x2=&right y2=&bottom stroke="black" />
+            <line x1=&left y1=&top x2=&left y2=&bottom stroke="black" />
+            { for (0..=TICKS).map(|i| view_x_tick(x, i)) }
+            { for (0..=TICKS).map(|i| view_y_tick(y, i)) }
+            { for series.iter().map(|series| view_series(x, y, series)) }
+            { for series.iter().enumerate().map(|(i, series)| view_legend(i, series)) }
+            <text x=&centre y=&x_label font-size="11" text-anchor="middle">{ x.label }</text>
+            <text x="4" y=&top font-size="11">{ y.label }</text>
+        </svg>
+    }
+}
+
+/// 截面图中的折线
+pub struct Path {
+    pub points: Vec<(f64, f64)>,
+    pub color: &'static str,
+    pub width: f64,
+}
+
+/// 截面图中的标记点
+pub struct Marker {
+    pub point: (f64, f64),
+    pub color: &'static str,
+}
+
+//等比例缩放，z向下为正
+struct Frame {
+    x_min: f64,
+    z_min: f64,
+    scale: f64,
+}
+
+impl Frame {
+    fn new(x: (f64, f64), z: (f64, f64)) -> Self {
+        let x_span = (x.1 - x.0).max(1e-9);
+        let z_span = (z.1 - z.0).max(1e-9);
+        Frame {
+            x_min: x.0,
+            z_min: z.0,
+            scale: ((WIDTH - 2.0 * MARGIN) / x_span).min((HEIGHT - 2.0 * MARGIN) / z_span),
+        }
+    }
+
+    fn to_screen(&self, point: (f64, f64)) -> (f64, f64) {
+        (
+            MARGIN + (point.0 - self.x_min) * self.scale,
+            MARGIN + (point.1 - self.z_min) * self.scale,
+        )
+    }
+}
+
+fn view_path(frame: &Frame, path: &Path) -> Html {
+    let points = path
+        .points
+        .iter()
+        .map(|&point| {
+            let (sx, sy) = frame.to_screen(point);
+            format!("{:.1},{:.1}", sx, sy)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let width = path.width.to_string();
+    html! {
+        <polyline points=points fill="none" stroke=path.color stroke-width=width />
+    }
+}
+
+fn view_marker(frame: &Frame, marker: &Marker) -> Html {
+    let (sx, sy) = frame.to_screen(marker.point);
+    let cx = format!("{:.1}", sx);
+    let cy = format!("{:.1}", sy);
+    html! {
+        <circle cx=cx cy=cy r="3" fill=marker.color />
+    }
+}
+
+/// 工件截面图，x、z方向等比例，z向下
+pub fn section(x: (f64, f64), z: (f64, f64), paths: &[Path], markers: &[Marker]) -> Html {
+    let frame = Frame::new(x, z);
+    html! {
+        <svg class="section" width="600" height="300" viewBox="0 0 600 300">
+            { for paths.iter().map(|path| view_path(&frame, path)) }
+            { for markers.iter().map(|marker| view_marker(&frame, marker)) }
+        </svg>
+    }
+}
+
+/// 每条A扫描最多绘制的点数
+const TRACE_POINTS: usize = 400;
+
+//按区间取绝对值最大的采样点抽稀，保留回波峰值
+fn decimate(trace: &[f32]) -> Vec<(usize, f32)> {
+    let bucket = trace.len().div_ceil(TRACE_POINTS).max(1);
+    trace
+        .chunks(bucket)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let peak = chunk.iter().cloned().fold(0f32, |a, b| if b.abs() > a.abs() { b } else { a });
+            (i * bucket, peak)
+        })
+        .collect()
+}
+
+fn view_trace(index: usize, count: usize, trace: &[f32], color: &'static str) -> Html {
+    let row = (HEIGHT - 2.0 * MARGIN) / count as f64;
+    let base = MARGIN + row * (index as f64 + 0.5);
+    let length = trace.len().max(1) as f64;
+    let points = decimate(trace)
+        .iter()
+        .map(|&(i, value)| {
+            let sx = MARGIN + i as f64 / length * (WIDTH - 2.0 * MARGIN);
+            let sy = base - value as f64 * row * 0.5;
+            format!("{:.1},{:.1}", sx, sy)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    html! {
+        <polyline points=points fill="none" stroke=color stroke-width="0.8" />
+    }
+}
+
+fn view_element(index: usize, count: usize, firing: bool) -> Html {
+    let row = (HEIGHT - 2.0 * MARGIN) / count as f64;
+    let y = format!("{:.1}", MARGIN + row * index as f64);
+    let height = format!("{:.1}", row.max(1.0) * 0.8);
+    let color = if firing { "firebrick" } else { "lightgray" };
+    html! {
+        <rect x="10" y=y width="20" height=height fill=color />
+    }
+}
+
+/// 一次发射的全部接收A扫描，自上而下按接收阵元排列，左侧阵元条中发射阵元标红
+pub fn fmc_frame(traces: &[&[f32]], tx: usize, duration: f64) -> Html {
+    let count = traces.len().max(1);
+    let color = |i: usize| if i == tx { "firebrick" } else { "steelblue" };
+    let bottom = format!("{}", HEIGHT - 8.0);
+    html! {
+        <svg class="fmc" width="600" height="300" viewBox="0 0 600 300">
+            { for (0..traces.len()).map(|i| view_element(i, count, i == tx)) }
+            { for traces.iter().enumerate().map(|(i, trace)| view_trace(i, count, trace, color(i))) }
+            <text x="40" y=&bottom font-size="11">{ "0µs" }</text>
+            <text x="560" y=&bottom font-size="11" text-anchor="end">{ format!("{:.1}µs", duration) }</text>
+        </svg>
+    }
+}
+
+//把0~1映射为蓝-青-黄-红色阶
+fn colormap(level: f64) -> [u8; 3] {
+    let t = level.clamp(0.0, 1.0) * 4.0;
+    let channel = |centre: f64| ((1.5 - (t - centre).abs()).clamp(0.0, 1.0) * 255.0) as u8;
+    [channel(3.0), channel(2.0), channel(1.0)]
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// 数据URI用的base64编码
+pub fn base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(BASE64[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+//24位BMP，行从下往上存放且按4字节对齐
+fn bitmap(columns: usize, rows: usize, levels: &[f64]) -> Vec<u8> {
+    let stride = (columns * 3).div_ceil(4) * 4;
+    let size = 54 + stride * rows;
+    let mut bytes = Vec::with_capacity(size);
+    bytes.extend_from_slice(b"BM");
+    bytes.extend_from_slice(&(size as u32).to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&54u32.to_le_bytes());
+    bytes.extend_from_slice(&40u32.to_le_bytes());
+    bytes.extend_from_slice(&(columns as i32).to_le_bytes());
+    bytes.extend_from_slice(&(rows as i32).to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&24u16.to_le_bytes());
+    bytes.extend_from_slice(&[0; 24]);
+    for row in (0..rows).rev() {
+        for column in 0..columns {
+            //成像区域以外显示为白色
+            let level = levels[row * columns + column];
+            let [r, g, b] = if level.is_nan() { [255; 3] } else { colormap(level) };
+            bytes.extend_from_slice(&[b, g, r]);
+        }
+        bytes.resize(bytes.len() + stride - columns * 3, 0);
+    }
+    bytes
+}
+
+/// 伪彩色图像，`levels`按行存放、取值0~1（NaN为空白），第一行对应z最小处；x、z方向等比例
+pub fn image(title: String, x: (f64, f64), z: (f64, f64), columns: usize, rows: usize, levels: &[f64]) -> Html {
+    let frame = Frame::new(x, z);
+    let (left, top) = frame.to_screen((x.0, z.0));
+    let (right, bottom) = frame.to_screen((x.1, z.1));
+    let uri = format!("data:image/bmp;base64,{}", base64(&bitmap(columns, rows, levels)));
+    let sx = format!("{:.1}", left);
+    let sy = format!("{:.1}", top);
+    let width = format!("{:.1}", right - left);
+    let height = format!("{:.1}", bottom - top);
+    let label_y = format!("{:.1}", bottom + 14.0);
+    let right_x = format!("{:.1}", right);
+    let depth_x = format!("{:.1}", left - 4.0);
+    let bottom_y = format!("{:.1}", bottom);
+    html! {
+        <svg class="image" width="600" height="300" viewBox="0 0 600 300">
+            <text x=&sx y="14" font-size="11">{ title }</text>
+            <image x=&sx y=&sy width=width height=height href=uri preserveAspectRatio="none" style="image-rendering: pixelated" />
+            <text x=&sx y=&label_y font-size="10" text-anchor="middle">{ format!("{:.1}", x.0) }</text>
+            <text x=&right_x y=&label_y font-size="10" text-anchor="middle">{ format!("{:.1}mm", x.1) }</text>
+            <text x=&depth_x y=&sy font-size="10" text-anchor="end">{ format!("{:.1}", z.0) }</text>
+            <text x=&depth_x y=&bottom_y font-size="10" text-anchor="end">{ format!("{:.1}mm", z.1) }</text>
+        </svg>
+    }
+}